@@ -280,3 +280,266 @@ fn utf16_needs_extra_unit(language: &str,  b: &mut Bencher) {
 #[bench] fn utf16_needs_extra_unit_es(b: &mut Bencher) {utf16_needs_extra_unit("es", b)}
 #[bench] fn utf16_needs_extra_unit_ru(b: &mut Bencher) {utf16_needs_extra_unit("ru", b)}
 #[bench] fn utf16_needs_extra_unit_zh(b: &mut Bencher) {utf16_needs_extra_unit("zh", b)}
+
+
+fn utf16_len(language: &str,  b: &mut Bencher) {
+    let string = &EQUAL_BYTES[language];
+    let units = string.encode_utf16().count();
+    b.iter(|| assert_eq!(encode_unicode::slice::utf16_len(black_box(string)), units) );
+}
+#[bench] fn utf16_len_ascii(b: &mut Bencher) {utf16_len("ascii", b)}
+#[bench] fn utf16_len_en(b: &mut Bencher) {utf16_len("en", b)}
+#[bench] fn utf16_len_es(b: &mut Bencher) {utf16_len("es", b)}
+#[bench] fn utf16_len_ru(b: &mut Bencher) {utf16_len("ru", b)}
+#[bench] fn utf16_len_zh(b: &mut Bencher) {utf16_len("zh", b)}
+
+fn std_encode_utf16_count(language: &str,  b: &mut Bencher) {
+    let string = &EQUAL_BYTES[language];
+    let units = string.encode_utf16().count();
+    b.iter(|| assert_eq!(black_box(string).encode_utf16().count(), units) );
+}
+#[bench] fn std_encode_utf16_count_ascii(b: &mut Bencher) {std_encode_utf16_count("ascii", b)}
+#[bench] fn std_encode_utf16_count_en(b: &mut Bencher) {std_encode_utf16_count("en", b)}
+#[bench] fn std_encode_utf16_count_es(b: &mut Bencher) {std_encode_utf16_count("es", b)}
+#[bench] fn std_encode_utf16_count_ru(b: &mut Bencher) {std_encode_utf16_count("ru", b)}
+#[bench] fn std_encode_utf16_count_zh(b: &mut Bencher) {std_encode_utf16_count("zh", b)}
+
+fn utf16_len_of_utf8(language: &str,  b: &mut Bencher) {
+    let string = EQUAL_BYTES[language].as_bytes();
+    let units = EQUAL_BYTES[language].encode_utf16().count();
+    b.iter(|| assert_eq!(encode_unicode::slice::utf16_len_of_utf8(black_box(string)), Ok(units)) );
+}
+#[bench] fn utf16_len_of_utf8_ascii(b: &mut Bencher) {utf16_len_of_utf8("ascii", b)}
+#[bench] fn utf16_len_of_utf8_en(b: &mut Bencher) {utf16_len_of_utf8("en", b)}
+#[bench] fn utf16_len_of_utf8_es(b: &mut Bencher) {utf16_len_of_utf8("es", b)}
+#[bench] fn utf16_len_of_utf8_ru(b: &mut Bencher) {utf16_len_of_utf8("ru", b)}
+#[bench] fn utf16_len_of_utf8_zh(b: &mut Bencher) {utf16_len_of_utf8("zh", b)}
+
+fn std_from_utf8_encode_utf16_count(language: &str,  b: &mut Bencher) {
+    let string = EQUAL_BYTES[language].as_bytes();
+    let units = EQUAL_BYTES[language].encode_utf16().count();
+    b.iter(|| {
+        let string = std::str::from_utf8(black_box(string)).unwrap();
+        assert_eq!(string.encode_utf16().count(), units);
+    });
+}
+#[bench] fn std_from_utf8_encode_utf16_count_ascii(b: &mut Bencher) {std_from_utf8_encode_utf16_count("ascii", b)}
+#[bench] fn std_from_utf8_encode_utf16_count_en(b: &mut Bencher) {std_from_utf8_encode_utf16_count("en", b)}
+#[bench] fn std_from_utf8_encode_utf16_count_es(b: &mut Bencher) {std_from_utf8_encode_utf16_count("es", b)}
+#[bench] fn std_from_utf8_encode_utf16_count_ru(b: &mut Bencher) {std_from_utf8_encode_utf16_count("ru", b)}
+#[bench] fn std_from_utf8_encode_utf16_count_zh(b: &mut Bencher) {std_from_utf8_encode_utf16_count("zh", b)}
+
+fn utf8_len(language: &str,  b: &mut Bencher) {
+    let string = &EQUAL_UNITS[language];
+    let units = string.encode_utf16().collect::<Vec<u16>>();
+    b.iter(|| assert_eq!(encode_unicode::slice::utf8_len(black_box(&units)), Ok(string.len())) );
+}
+#[bench] fn utf8_len_ascii(b: &mut Bencher) {utf8_len("ascii", b)}
+#[bench] fn utf8_len_en(b: &mut Bencher) {utf8_len("en", b)}
+#[bench] fn utf8_len_es(b: &mut Bencher) {utf8_len("es", b)}
+#[bench] fn utf8_len_ru(b: &mut Bencher) {utf8_len("ru", b)}
+#[bench] fn utf8_len_zh(b: &mut Bencher) {utf8_len("zh", b)}
+
+fn std_decode_utf16_len_utf8(language: &str,  b: &mut Bencher) {
+    let string = &EQUAL_UNITS[language];
+    let units = string.encode_utf16().collect::<Vec<u16>>();
+    b.iter(|| {
+        let len = std::char::decode_utf16(black_box(&units).iter().cloned())
+            .map(|r| r.map(char::len_utf8) )
+            .sum::<Result<usize,_>>();
+        assert_eq!(len.ok(), Some(string.len()));
+    });
+}
+#[bench] fn std_decode_utf16_len_utf8_ascii(b: &mut Bencher) {std_decode_utf16_len_utf8("ascii", b)}
+#[bench] fn std_decode_utf16_len_utf8_en(b: &mut Bencher) {std_decode_utf16_len_utf8("en", b)}
+#[bench] fn std_decode_utf16_len_utf8_es(b: &mut Bencher) {std_decode_utf16_len_utf8("es", b)}
+#[bench] fn std_decode_utf16_len_utf8_ru(b: &mut Bencher) {std_decode_utf16_len_utf8("ru", b)}
+#[bench] fn std_decode_utf16_len_utf8_zh(b: &mut Bencher) {std_decode_utf16_len_utf8("zh", b)}
+
+fn transcode_utf8_to_utf16(language: &str,  b: &mut Bencher) {
+    let string = &EQUAL_BYTES[language];
+    let mut buf = vec![0u16; string.len()];
+    let units = string.encode_utf16().count();
+    b.iter(|| {
+        let result = encode_unicode::slice::transcode_utf8_to_utf16(black_box(string.as_bytes()), &mut buf);
+        assert_eq!(result, (string.len(), units, Ok(())));
+    });
+}
+#[bench] fn transcode_utf8_to_utf16_ascii(b: &mut Bencher) {transcode_utf8_to_utf16("ascii", b)}
+#[bench] fn transcode_utf8_to_utf16_en(b: &mut Bencher) {transcode_utf8_to_utf16("en", b)}
+#[bench] fn transcode_utf8_to_utf16_es(b: &mut Bencher) {transcode_utf8_to_utf16("es", b)}
+#[bench] fn transcode_utf8_to_utf16_ru(b: &mut Bencher) {transcode_utf8_to_utf16("ru", b)}
+#[bench] fn transcode_utf8_to_utf16_zh(b: &mut Bencher) {transcode_utf8_to_utf16("zh", b)}
+
+fn std_from_utf8_encode_utf16(language: &str,  b: &mut Bencher) {
+    let string = &EQUAL_BYTES[language];
+    let mut buf = vec![0u16; string.len()];
+    let units = string.encode_utf16().count();
+    b.iter(|| {
+        let string = std::str::from_utf8(black_box(string.as_bytes())).unwrap();
+        let written = buf.iter_mut().zip(string.encode_utf16()).map(|(dst, unit)| *dst = unit ).count();
+        assert_eq!(written, units);
+    });
+}
+#[bench] fn std_from_utf8_encode_utf16_ascii(b: &mut Bencher) {std_from_utf8_encode_utf16("ascii", b)}
+#[bench] fn std_from_utf8_encode_utf16_en(b: &mut Bencher) {std_from_utf8_encode_utf16("en", b)}
+#[bench] fn std_from_utf8_encode_utf16_es(b: &mut Bencher) {std_from_utf8_encode_utf16("es", b)}
+#[bench] fn std_from_utf8_encode_utf16_ru(b: &mut Bencher) {std_from_utf8_encode_utf16("ru", b)}
+#[bench] fn std_from_utf8_encode_utf16_zh(b: &mut Bencher) {std_from_utf8_encode_utf16("zh", b)}
+
+fn transcode_utf16_to_utf8(language: &str,  b: &mut Bencher) {
+    let string = &EQUAL_UNITS[language];
+    let units = string.encode_utf16().collect::<Vec<u16>>();
+    let mut buf = vec![0u8; string.len()];
+    b.iter(|| {
+        let result = encode_unicode::slice::transcode_utf16_to_utf8(black_box(&units), &mut buf);
+        assert_eq!(result, (units.len(), string.len(), Ok(())));
+    });
+}
+#[bench] fn transcode_utf16_to_utf8_ascii(b: &mut Bencher) {transcode_utf16_to_utf8("ascii", b)}
+#[bench] fn transcode_utf16_to_utf8_en(b: &mut Bencher) {transcode_utf16_to_utf8("en", b)}
+#[bench] fn transcode_utf16_to_utf8_es(b: &mut Bencher) {transcode_utf16_to_utf8("es", b)}
+#[bench] fn transcode_utf16_to_utf8_ru(b: &mut Bencher) {transcode_utf16_to_utf8("ru", b)}
+#[bench] fn transcode_utf16_to_utf8_zh(b: &mut Bencher) {transcode_utf16_to_utf8("zh", b)}
+
+fn std_decode_utf16_encode_utf8(language: &str,  b: &mut Bencher) {
+    let string = &EQUAL_UNITS[language];
+    let units = string.encode_utf16().collect::<Vec<u16>>();
+    let mut buf = vec![0u8; string.len()];
+    b.iter(|| {
+        let mut written = 0;
+        for c in std::char::decode_utf16(black_box(&units).iter().cloned()) {
+            written += c.unwrap().encode_utf8(&mut buf[written..]).len();
+        }
+        assert_eq!(written, string.len());
+    });
+}
+#[bench] fn std_decode_utf16_encode_utf8_ascii(b: &mut Bencher) {std_decode_utf16_encode_utf8("ascii", b)}
+#[bench] fn std_decode_utf16_encode_utf8_en(b: &mut Bencher) {std_decode_utf16_encode_utf8("en", b)}
+#[bench] fn std_decode_utf16_encode_utf8_es(b: &mut Bencher) {std_decode_utf16_encode_utf8("es", b)}
+#[bench] fn std_decode_utf16_encode_utf8_ru(b: &mut Bencher) {std_decode_utf16_encode_utf8("ru", b)}
+#[bench] fn std_decode_utf16_encode_utf8_zh(b: &mut Bencher) {std_decode_utf16_encode_utf8("zh", b)}
+
+fn validate_utf16(language: &str,  b: &mut Bencher) {
+    let units = EQUAL_UNITS[language].encode_utf16().collect::<Vec<u16>>();
+    b.iter(|| assert_eq!(encode_unicode::slice::validate_utf16(black_box(&units)), Ok(())) );
+}
+#[bench] fn validate_utf16_ascii(b: &mut Bencher) {validate_utf16("ascii", b)}
+#[bench] fn validate_utf16_en(b: &mut Bencher) {validate_utf16("en", b)}
+#[bench] fn validate_utf16_es(b: &mut Bencher) {validate_utf16("es", b)}
+#[bench] fn validate_utf16_ru(b: &mut Bencher) {validate_utf16("ru", b)}
+#[bench] fn validate_utf16_zh(b: &mut Bencher) {validate_utf16("zh", b)}
+
+fn std_decode_utf16_validate(language: &str,  b: &mut Bencher) {
+    let units = EQUAL_UNITS[language].encode_utf16().collect::<Vec<u16>>();
+    b.iter(|| assert!(std::char::decode_utf16(black_box(&units).iter().cloned()).all(|r| r.is_ok() )) );
+}
+#[bench] fn std_decode_utf16_validate_ascii(b: &mut Bencher) {std_decode_utf16_validate("ascii", b)}
+#[bench] fn std_decode_utf16_validate_en(b: &mut Bencher) {std_decode_utf16_validate("en", b)}
+#[bench] fn std_decode_utf16_validate_es(b: &mut Bencher) {std_decode_utf16_validate("es", b)}
+#[bench] fn std_decode_utf16_validate_ru(b: &mut Bencher) {std_decode_utf16_validate("ru", b)}
+#[bench] fn std_decode_utf16_validate_zh(b: &mut Bencher) {std_decode_utf16_validate("zh", b)}
+
+fn utf8char_indices(language: &str,  b: &mut Bencher) {
+    use encode_unicode::SliceExt;
+    let string = &EQUAL_BYTES[language];
+    b.iter(|| {
+        let bytes = black_box(string.as_bytes()).utf8char_indices()
+            .map(|(_, r, len)| {assert!(r.is_ok()); len})
+            .sum::<usize>();
+        assert_eq!(bytes, string.len());
+    });
+}
+#[bench] fn utf8char_indices_ascii(b: &mut Bencher) {utf8char_indices("ascii", b)}
+#[bench] fn utf8char_indices_en(b: &mut Bencher) {utf8char_indices("en", b)}
+#[bench] fn utf8char_indices_es(b: &mut Bencher) {utf8char_indices("es", b)}
+#[bench] fn utf8char_indices_ru(b: &mut Bencher) {utf8char_indices("ru", b)}
+#[bench] fn utf8char_indices_zh(b: &mut Bencher) {utf8char_indices("zh", b)}
+
+fn utf8char_indices_rev(language: &str,  b: &mut Bencher) {
+    use encode_unicode::SliceExt;
+    let string = &EQUAL_BYTES[language];
+    b.iter(|| {
+        let bytes = black_box(string.as_bytes()).utf8char_indices().rev()
+            .map(|(_, r, len)| {assert!(r.is_ok()); len})
+            .sum::<usize>();
+        assert_eq!(bytes, string.len());
+    });
+}
+#[bench] fn utf8char_indices_rev_ascii(b: &mut Bencher) {utf8char_indices_rev("ascii", b)}
+#[bench] fn utf8char_indices_rev_en(b: &mut Bencher) {utf8char_indices_rev("en", b)}
+#[bench] fn utf8char_indices_rev_es(b: &mut Bencher) {utf8char_indices_rev("es", b)}
+#[bench] fn utf8char_indices_rev_ru(b: &mut Bencher) {utf8char_indices_rev("ru", b)}
+#[bench] fn utf8char_indices_rev_zh(b: &mut Bencher) {utf8char_indices_rev("zh", b)}
+
+fn to_utf8chars(language: &str,  b: &mut Bencher) {
+    use encode_unicode::IterExt;
+    let string = &EQUAL_BYTES[language];
+    b.iter(|| {
+        let bytes = black_box(string.as_bytes()).iter().to_utf8chars()
+            .map(|r| r.unwrap().len() )
+            .sum::<usize>();
+        assert_eq!(bytes, string.len());
+    });
+}
+#[bench] fn to_utf8chars_ascii(b: &mut Bencher) {to_utf8chars("ascii", b)}
+#[bench] fn to_utf8chars_en(b: &mut Bencher) {to_utf8chars("en", b)}
+#[bench] fn to_utf8chars_es(b: &mut Bencher) {to_utf8chars("es", b)}
+#[bench] fn to_utf8chars_ru(b: &mut Bencher) {to_utf8chars("ru", b)}
+#[bench] fn to_utf8chars_zh(b: &mut Bencher) {to_utf8chars("zh", b)}
+
+fn std_from_utf8_char_indices(language: &str,  b: &mut Bencher) {
+    let string = &EQUAL_BYTES[language];
+    b.iter(|| {
+        let bytes = std::str::from_utf8(black_box(string.as_bytes())).unwrap()
+            .chars()
+            .map(|c| c.len_utf8() )
+            .sum::<usize>();
+        assert_eq!(bytes, string.len());
+    });
+}
+#[bench] fn std_from_utf8_char_indices_ascii(b: &mut Bencher) {std_from_utf8_char_indices("ascii", b)}
+#[bench] fn std_from_utf8_char_indices_en(b: &mut Bencher) {std_from_utf8_char_indices("en", b)}
+#[bench] fn std_from_utf8_char_indices_es(b: &mut Bencher) {std_from_utf8_char_indices("es", b)}
+#[bench] fn std_from_utf8_char_indices_ru(b: &mut Bencher) {std_from_utf8_char_indices("ru", b)}
+#[bench] fn std_from_utf8_char_indices_zh(b: &mut Bencher) {std_from_utf8_char_indices("zh", b)}
+
+fn ascii_runs(language: &str,  b: &mut Bencher) {
+    use encode_unicode::StrExt;
+    use encode_unicode::iterator::AsciiRunOrChar;
+    let string = &EQUAL_BYTES[language];
+    let mut copy = String::with_capacity(string.len());
+    b.iter(|| {
+        copy.clear();
+        for item in black_box(string.as_str()).ascii_runs() {
+            match item {
+                AsciiRunOrChar::Ascii(run) => copy.push_str(run),
+                AsciiRunOrChar::Char(c) => copy.push_str(c.as_str()),
+            }
+        }
+        assert_eq!(copy.len(), string.len());
+    });
+}
+#[bench] fn ascii_runs_ascii(b: &mut Bencher) {ascii_runs("ascii", b)}
+#[bench] fn ascii_runs_en(b: &mut Bencher) {ascii_runs("en", b)}
+#[bench] fn ascii_runs_es(b: &mut Bencher) {ascii_runs("es", b)}
+#[bench] fn ascii_runs_ru(b: &mut Bencher) {ascii_runs("ru", b)}
+#[bench] fn ascii_runs_zh(b: &mut Bencher) {ascii_runs("zh", b)}
+
+fn utf8chars_push(language: &str,  b: &mut Bencher) {
+    use encode_unicode::StrExt;
+    let string = &EQUAL_BYTES[language];
+    let mut copy = String::with_capacity(string.len());
+    b.iter(|| {
+        copy.clear();
+        for c in black_box(string.as_str()).utf8chars() {
+            copy.push_str(c.as_str());
+        }
+        assert_eq!(copy.len(), string.len());
+    });
+}
+#[bench] fn utf8chars_push_ascii(b: &mut Bencher) {utf8chars_push("ascii", b)}
+#[bench] fn utf8chars_push_en(b: &mut Bencher) {utf8chars_push("en", b)}
+#[bench] fn utf8chars_push_es(b: &mut Bencher) {utf8chars_push("es", b)}
+#[bench] fn utf8chars_push_ru(b: &mut Bencher) {utf8chars_push("ru", b)}
+#[bench] fn utf8chars_push_zh(b: &mut Bencher) {utf8chars_push("zh", b)}
@@ -2,6 +2,8 @@
 set -e -o pipefail
 
 MSRV=1.56.1
+# all the optional features that don't require a newer Rust version
+MSRV_FEATURES=std,ascii,bytemuck,zerocopy,serde,arbitrary,proptest,nom,arrayvec,tokio-codec,tokio-io,futures,bytes,memchr,rayon
 FUZZ_DURATION=60
 FUZZ_PAUSE=2
 
@@ -21,9 +23,33 @@ fi
 if [[ $1 == setup ]] || ! rustup show | grep --silent "$MSRV"; then
     rustup install "$MSRV" --no-self-update
 fi
+msrv_restore() {
+    mv Cargo.toml.msrv-backup Cargo.toml
+    if [[ -e Cargo.lock.msrv-backup ]]; then
+        mv Cargo.lock.msrv-backup Cargo.lock
+    else
+        rm -f Cargo.lock
+    fi
+}
 if [[ -z $1 || $1 == msrv ]]; then
-    # FIXME modify Cargo.toml like on CI, and then restore it and Cargo.lock afterwards
-    cargo "+$MSRV" build --all-features
+    # Lock to the specified minor versions of dependencies like on CI,
+    # and restore Cargo.toml and Cargo.lock afterwards, also if a check fails.
+    # (The lock file is removed because newer cargo versions write a format $MSRV can't read.)
+    cp Cargo.toml Cargo.toml.msrv-backup
+    if [[ -e Cargo.lock ]]; then
+        mv Cargo.lock Cargo.lock.msrv-backup
+    fi
+    trap msrv_restore EXIT
+    sed -i 's/"^/"~/' Cargo.toml
+    # nom 8 requires a newer Rust version, so check nom 7 here and nom 8 in the other steps.
+    cargo "+$MSRV" generate-lockfile
+    cargo "+$MSRV" update -p nom --precise 7.1.3
+    cargo "+$MSRV" check --no-default-features
+    cargo "+$MSRV" check --no-default-features --features std
+    cargo "+$MSRV" check --no-default-features --features ascii
+    cargo "+$MSRV" check --no-default-features --features "$MSRV_FEATURES"
+    trap - EXIT
+    msrv_restore
 fi
 
 # check all feature combinations, stable
@@ -32,14 +58,38 @@ if [[ $1 == setup ]] || ! rustup show | grep --silent stable; then
 fi
 if [[ -z $1 || $1 == check ]]; then
     cargo +stable check --examples --tests --no-default-features
+    cargo +stable check --examples --tests --no-default-features --features alloc
+    cargo +stable check --examples --tests --no-default-features --features core_error
     cargo +stable check --examples --tests --no-default-features --features std
     cargo +stable check --examples --tests --no-default-features --features ascii
+    cargo +stable check --examples --tests --no-default-features --features bytemuck
+    cargo +stable check --examples --tests --no-default-features --features zerocopy
+    cargo +stable check --examples --tests --no-default-features --features serde
+    cargo +stable check --examples --tests --no-default-features --features arbitrary
+    cargo +stable check --examples --tests --no-default-features --features proptest
+    cargo +stable check --examples --tests --no-default-features --features nom
+    cargo +stable check --examples --tests --no-default-features --features heapless,arrayvec
+    cargo +stable check --examples --tests --no-default-features --features tokio-codec,tokio-io
+    cargo +stable check --examples --tests --no-default-features --features futures
+    cargo +stable check --examples --tests --no-default-features --features bytes
+    cargo +stable check --examples --tests --no-default-features --features memchr
+    cargo +stable check --examples --tests --no-default-features --features rayon
+    cargo +stable check --examples --tests --no-default-features --features widestring
     cargo +stable check --examples --tests --all-features
+    # the neon feature only has an effect on aarch64
+    if rustup +stable target list --installed | grep --silent aarch64-unknown-linux-gnu; then
+        cargo +stable check --no-default-features --features neon --target aarch64-unknown-linux-gnu
+        cargo +stable check --no-default-features --features neon,std --target aarch64-unknown-linux-gnu
+    fi
 fi
 
 # tests, stable
 if [[ -z $1 || $1 == test ]]; then
     cargo +stable test --all-features -- --quiet
+    # also test the older supported nom version, and then go back to the newest
+    cargo +stable update -p nom --precise 7.1.3
+    cargo +stable test --all-features --test nom -- --quiet
+    cargo +stable update -p nom
 elif [[ $1 == ignored ]]; then
     cargo +stable test --all-features -- --quiet --ignored
 fi
@@ -43,14 +43,8 @@ fuzz_target!(|data: &[u8]| {
     }
 
     let from_slice: Vec<_> = data.utf8char_indices().map(|(_,r,_)| r ).collect();
-    for (i, (&br, &sr)) in from_bytes.iter().zip(&from_slice).enumerate() {
-        match sr {
-            // the slice-based iterator might detect too short earlier,
-            // but that should be the only difference
-            Err(e) if e.kind() == TooFewBytes || e.kind() == InterruptedSequence
-                => assert!(br.is_err(), "byte {}", i),
-            _ => assert_eq!(sr, br, "byte {}", i),
-        }
-    }
-    assert_eq!(from_slice.len(), from_bytes.len());
+    assert_eq!(from_slice, from_bytes);
+    let mut from_back: Vec<_> = data.utf8char_indices().rev().map(|(_,r,_)| r ).collect();
+    from_back.reverse();
+    assert_eq!(from_back, from_bytes);
 });
@@ -0,0 +1,18 @@
+#![no_main]
+#[macro_use]
+extern crate libfuzzer_sys;
+extern crate encode_unicode;
+
+use encode_unicode::{SliceExt, Utf8Char, Utf16Char};
+
+fuzz_target!(|chars: Vec<Utf8Char>| {
+    // encode the generated characters as UTF-8 and UTF-16, and decode them again
+    let string = chars.iter().collect::<String>();
+    let units = chars.iter().map(|&u8c| Utf16Char::from(u8c) ).collect::<Vec<u16>>();
+    assert_eq!(units, string.encode_utf16().collect::<Vec<u16>>());
+
+    let from_bytes = string.as_bytes().utf8char_indices().map(|(_, r, _)| r.unwrap() );
+    assert!(from_bytes.eq(chars.iter().cloned()));
+    let from_units = units.utf16char_indices().map(|(_, r, _)| Utf8Char::from(r.unwrap()) );
+    assert!(from_units.eq(chars.iter().cloned()));
+});
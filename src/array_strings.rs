@@ -0,0 +1,468 @@
+/* Copyright 2022 Torbjørn Birch Moltu
+ *
+ * Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+ * http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+ * http://opensource.org/licenses/MIT>, at your option. This file may not be
+ * copied, modified, or distributed except according to those terms.
+ */
+
+//! Fixed-capacity strings, and filling them and other fixed-capacity
+//! containers from iterators without panicking.
+
+use crate::utf8_char::Utf8Char;
+use crate::utf16_char::Utf16Char;
+use crate::errors::CapacityError;
+use crate::lengths::utf16_len;
+use core::{fmt, hash, str};
+use core::borrow::Borrow;
+use core::cmp::Ordering;
+use core::convert::TryFrom;
+use core::ops::Deref;
+
+
+/// Append characters to a fixed-capacity container, and report when it's full.
+///
+/// Unlike `Extend`, this neither panics nor silently drops the characters
+/// that don't fit:
+/// `try_extend()` stops at the first character that doesn't fit,
+/// and the characters before it are kept.
+/// A character is never partially added.
+///
+/// It's implemented for [`Utf8ArrayString`](struct.Utf8ArrayString.html) and
+/// [`Utf16ArrayString`](struct.Utf16ArrayString.html), and with the features
+/// "heapless" and "arrayvec" for the strings and `u8` or `u16` vectors of those crates.
+///
+/// # Examples
+///
+/// ```
+/// use encode_unicode::{TryExtend, Utf8ArrayString, StrExt};
+///
+/// let mut buffer = Utf8ArrayString::<8>::try_from_iter("Æble".utf8chars()).unwrap();
+/// assert_eq!(buffer.try_extend("grød".utf8chars()), Err(encode_unicode::error::CapacityError));
+/// assert_eq!(buffer, "Æblegr");
+/// ```
+pub trait TryExtend<T> {
+    /// Append characters until the iterator is exhausted or a character doesn't fit.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CapacityError` if a character didn't fit,
+    /// in which case it and the rest of the iterator is not added.
+    fn try_extend<I: IntoIterator<Item=T>>(&mut self,  iter: I) -> Result<(), CapacityError>;
+
+    /// Create a container with all the characters of the iterator,
+    /// or fail if they don't fit.
+    fn try_from_iter<I: IntoIterator<Item=T>>(iter: I) -> Result<Self, CapacityError>
+    where Self: Default+Sized {
+        let mut container = Self::default();
+        container.try_extend(iter)?;
+        Ok(container)
+    }
+}
+
+macro_rules! try_extend {($([$($generics:tt)*] $container:ty: $item:ty => |$dst:ident, $c:ident| $push:expr;)*) => {$(
+    impl<$($generics)*> TryExtend<$item> for $container {
+        fn try_extend<I: IntoIterator<Item=$item>>(&mut self,  iter: I) -> Result<(), CapacityError> {
+            for $c in iter {
+                let $dst = &mut *self;
+                $push?;
+            }
+            Ok(())
+        }
+    }
+)*}}
+
+try_extend!{
+    [const N: usize] Utf8ArrayString<N>: Utf8Char => |dst, u8c| dst.try_push(u8c);
+    [const N: usize] Utf8ArrayString<N>: Utf16Char => |dst, u16c| dst.try_push(u16c);
+    [const N: usize] Utf16ArrayString<N>: Utf16Char => |dst, u16c| dst.try_push(u16c);
+    [const N: usize] Utf16ArrayString<N>: Utf8Char => |dst, u8c| dst.try_push(u8c);
+}
+
+#[cfg(feature="heapless")]
+try_extend!{
+    [const N: usize] heapless::String<N>: Utf8Char
+        => |dst, u8c| dst.push_str(u8c.as_str()).map_err(|_| CapacityError );
+    [const N: usize] heapless::String<N>: Utf16Char
+        => |dst, u16c| dst.push(u16c.to_char()).map_err(|_| CapacityError );
+    [const N: usize] heapless::Vec<u8, N>: Utf8Char
+        => |dst, u8c| dst.extend_from_slice(u8c.as_bytes()).map_err(|_| CapacityError );
+    [const N: usize] heapless::Vec<u16, N>: Utf16Char
+        => |dst, u16c| dst.extend_from_slice(&u16c).map_err(|_| CapacityError );
+}
+
+#[cfg(feature="arrayvec")]
+try_extend!{
+    [const N: usize] arrayvec::ArrayString<N>: Utf8Char
+        => |dst, u8c| dst.try_push_str(u8c.as_str()).map_err(|_| CapacityError );
+    [const N: usize] arrayvec::ArrayString<N>: Utf16Char
+        => |dst, u16c| dst.try_push(u16c.to_char()).map_err(|_| CapacityError );
+    [const N: usize] arrayvec::ArrayVec<u8, N>: Utf8Char
+        => |dst, u8c| dst.try_extend_from_slice(u8c.as_bytes()).map_err(|_| CapacityError );
+    [const N: usize] arrayvec::ArrayVec<u16, N>: Utf16Char
+        => |dst, u16c| dst.try_extend_from_slice(&u16c).map_err(|_| CapacityError );
+}
+
+
+
+/// A string of at most `N` bytes of UTF-8, stored inline.
+///
+/// This is for building text without an allocator.
+/// Operations that would exceed the capacity return `CapacityError` and
+/// leave the string unchanged, instead of panicking.
+///
+/// # Examples
+///
+/// ```
+/// use encode_unicode::Utf8ArrayString;
+/// use core::fmt::Write;
+///
+/// let mut s = Utf8ArrayString::<16>::new();
+/// s.try_push('¡').unwrap();
+/// write!(s, "Hola {}", 2022).unwrap();
+/// assert_eq!(s.as_str(), "¡Hola 2022");
+/// assert_eq!(s.remaining_capacity(), 5);
+/// assert!(s.try_push_str("mundo!").is_err());
+/// assert_eq!(s.len(), 11);
+/// ```
+#[derive(Clone,Copy)]
+pub struct Utf8ArrayString<const N: usize> {
+    // only bytes[..len] is meaningful, and it is always valid UTF-8
+    bytes: [u8; N],
+    len: usize,
+}
+
+impl<const N: usize> Utf8ArrayString<N> {
+    /// Create an empty string.
+    pub const fn new() -> Self {
+        Utf8ArrayString { bytes: [0; N], len: 0 }
+    }
+    /// The maximum length in bytes, `N`.
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+    /// The length in bytes.
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+    /// Whether the string contains no characters.
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+    /// The number of bytes that can be added before it's full.
+    pub const fn remaining_capacity(&self) -> usize {
+        N - self.len
+    }
+    /// View the content as a string slice.
+    pub fn as_str(&self) -> &str {
+        unsafe { str::from_utf8_unchecked(self.as_bytes()) }
+    }
+    /// View the content as UTF-8 bytes.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes[..self.len]
+    }
+    /// Append a character, or fail if it doesn't fit.
+    pub fn try_push<C: Into<Utf8Char>>(&mut self,  c: C) -> Result<(), CapacityError> {
+        self.try_push_str(c.into().as_str())
+    }
+    /// Append a string slice if all of it fits.
+    pub fn try_push_str(&mut self,  s: &str) -> Result<(), CapacityError> {
+        if s.len() > self.remaining_capacity() {
+            return Err(CapacityError);
+        }
+        self.bytes[self.len..self.len+s.len()].copy_from_slice(s.as_bytes());
+        self.len += s.len();
+        Ok(())
+    }
+    /// Remove and return the last character, or `None` if the string is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut s = encode_unicode::Utf8ArrayString::<8>::try_from("hæ").unwrap();
+    /// assert_eq!(s.pop(), Some('æ'.into()));
+    /// assert_eq!(s.pop(), Some('h'.into()));
+    /// assert_eq!(s.pop(), None);
+    /// ```
+    pub fn pop(&mut self) -> Option<Utf8Char> {
+        let mut start = self.len.checked_sub(1)?;
+        while self.bytes[start] & 0b1100_0000 == 0b1000_0000 {
+            start -= 1;
+        }
+        let (u8c, _) = unsafe { Utf8Char::from_slice_start_unchecked(&self.bytes[start..self.len]) };
+        self.len = start;
+        Some(u8c)
+    }
+    /// Remove all characters.
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+}
+
+impl<const N: usize> Default for Utf8ArrayString<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl<'a, const N: usize> TryFrom<&'a str> for Utf8ArrayString<N> {
+    type Error = CapacityError;
+    fn try_from(s: &'a str) -> Result<Self, CapacityError> {
+        let mut string = Self::new();
+        string.try_push_str(s)?;
+        Ok(string)
+    }
+}
+impl<const N: usize> Deref for Utf8ArrayString<N> {
+    type Target = str;
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+impl<const N: usize> AsRef<str> for Utf8ArrayString<N> {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+impl<const N: usize> AsRef<[u8]> for Utf8ArrayString<N> {
+    fn as_ref(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
+impl<const N: usize> Borrow<str> for Utf8ArrayString<N> {
+    fn borrow(&self) -> &str {
+        self.as_str()
+    }
+}
+impl<const N: usize> fmt::Write for Utf8ArrayString<N> {
+    fn write_str(&mut self,  s: &str) -> fmt::Result {
+        self.try_push_str(s).map_err(|_| fmt::Error )
+    }
+}
+impl<const N: usize> fmt::Debug for Utf8ArrayString<N> {
+    fn fmt(&self,  fmtr: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), fmtr)
+    }
+}
+impl<const N: usize> fmt::Display for Utf8ArrayString<N> {
+    fn fmt(&self,  fmtr: &mut fmt::Formatter) -> fmt::Result {
+        fmtr.write_str(self.as_str())
+    }
+}
+// compare the content, not the unused part of the array
+impl<const N: usize> hash::Hash for Utf8ArrayString<N> {
+    fn hash<H : hash::Hasher>(&self,  state: &mut H) {
+        self.as_str().hash(state);
+    }
+}
+impl<const N: usize, const M: usize> PartialEq<Utf8ArrayString<M>> for Utf8ArrayString<N> {
+    fn eq(&self,  other: &Utf8ArrayString<M>) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+impl<const N: usize> Eq for Utf8ArrayString<N> {}
+impl<const N: usize> PartialOrd for Utf8ArrayString<N> {
+    fn partial_cmp(&self,  other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+impl<const N: usize> Ord for Utf8ArrayString<N> {
+    fn cmp(&self,  other: &Self) -> Ordering {
+        self.as_str().cmp(other.as_str())
+    }
+}
+impl<const N: usize> PartialEq<str> for Utf8ArrayString<N> {
+    fn eq(&self,  s: &str) -> bool {
+        self.as_str() == s
+    }
+}
+impl<'a, const N: usize> PartialEq<&'a str> for Utf8ArrayString<N> {
+    fn eq(&self,  s: &&'a str) -> bool {
+        self.as_str() == *s
+    }
+}
+
+
+
+/// A string of at most `N` units of UTF-16, stored inline.
+///
+/// This is for building UTF-16 text without an allocator.
+/// Operations that would exceed the capacity return `CapacityError` and
+/// leave the string unchanged, instead of panicking.
+///
+/// # Examples
+///
+/// ```
+/// use encode_unicode::Utf16ArrayString;
+///
+/// let mut s = Utf16ArrayString::<4>::new();
+/// s.try_push_str("a😀").unwrap();
+/// assert_eq!(s.as_slice(), [0x61, 0xd83d, 0xde00]);
+/// assert!(s.try_push('😀').is_err());
+/// s.try_push('b').unwrap();
+/// assert_eq!(s, "a😀b");
+/// assert_eq!(s.to_string(), "a😀b");
+/// ```
+#[derive(Clone,Copy)]
+pub struct Utf16ArrayString<const N: usize> {
+    // only units[..len] is meaningful, and it is always valid UTF-16
+    units: [u16; N],
+    len: usize,
+}
+
+impl<const N: usize> Utf16ArrayString<N> {
+    /// Create an empty string.
+    pub const fn new() -> Self {
+        Utf16ArrayString { units: [0; N], len: 0 }
+    }
+    /// The maximum length in units, `N`.
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+    /// The length in units.
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+    /// Whether the string contains no characters.
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+    /// The number of units that can be added before it's full.
+    pub const fn remaining_capacity(&self) -> usize {
+        N - self.len
+    }
+    /// View the content as UTF-16 units.
+    pub fn as_slice(&self) -> &[u16] {
+        &self.units[..self.len]
+    }
+    fn chars(&self) -> impl Iterator<Item=Utf16Char> + '_ {
+        let mut rest = self.as_slice();
+        core::iter::from_fn(move || {
+            if rest.is_empty() {
+                return None;
+            }
+            let (u16c, len) = unsafe { Utf16Char::from_slice_start_unchecked(rest) };
+            rest = &rest[len..];
+            Some(u16c)
+        })
+    }
+    /// Append a character, or fail if it doesn't fit.
+    pub fn try_push<C: Into<Utf16Char>>(&mut self,  c: C) -> Result<(), CapacityError> {
+        let u16c = c.into();
+        if u16c.len() > self.remaining_capacity() {
+            return Err(CapacityError);
+        }
+        self.len += u16c.to_slice(&mut self.units[self.len..]);
+        Ok(())
+    }
+    /// Append a string slice if all of it fits.
+    pub fn try_push_str(&mut self,  s: &str) -> Result<(), CapacityError> {
+        if utf16_len(s) > self.remaining_capacity() {
+            return Err(CapacityError);
+        }
+        for (dst, unit) in self.units[self.len..].iter_mut().zip(s.encode_utf16()) {
+            *dst = unit;
+            self.len += 1;
+        }
+        Ok(())
+    }
+    /// Remove and return the last character, or `None` if the string is empty.
+    pub fn pop(&mut self) -> Option<Utf16Char> {
+        let mut start = self.len.checked_sub(1)?;
+        if self.units[start] & 0xfc_00 == 0xdc_00 {
+            start -= 1;
+        }
+        let (u16c, _) = unsafe { Utf16Char::from_slice_start_unchecked(&self.units[start..self.len]) };
+        self.len = start;
+        Some(u16c)
+    }
+    /// Remove all characters.
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+}
+
+impl<const N: usize> Default for Utf16ArrayString<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl<'a, const N: usize> TryFrom<&'a str> for Utf16ArrayString<N> {
+    type Error = CapacityError;
+    fn try_from(s: &'a str) -> Result<Self, CapacityError> {
+        let mut string = Self::new();
+        string.try_push_str(s)?;
+        Ok(string)
+    }
+}
+impl<const N: usize> Deref for Utf16ArrayString<N> {
+    type Target = [u16];
+    fn deref(&self) -> &[u16] {
+        self.as_slice()
+    }
+}
+impl<const N: usize> AsRef<[u16]> for Utf16ArrayString<N> {
+    fn as_ref(&self) -> &[u16] {
+        self.as_slice()
+    }
+}
+impl<const N: usize> Borrow<[u16]> for Utf16ArrayString<N> {
+    fn borrow(&self) -> &[u16] {
+        self.as_slice()
+    }
+}
+impl<const N: usize> fmt::Write for Utf16ArrayString<N> {
+    fn write_str(&mut self,  s: &str) -> fmt::Result {
+        self.try_push_str(s).map_err(|_| fmt::Error )
+    }
+}
+impl<const N: usize> fmt::Debug for Utf16ArrayString<N> {
+    fn fmt(&self,  fmtr: &mut fmt::Formatter) -> fmt::Result {
+        use fmt::Write;
+        fmtr.write_char('"')?;
+        for u16c in self.chars() {
+            for escaped in u16c.to_char().escape_debug() {
+                fmtr.write_char(escaped)?;
+            }
+        }
+        fmtr.write_char('"')
+    }
+}
+impl<const N: usize> fmt::Display for Utf16ArrayString<N> {
+    fn fmt(&self,  fmtr: &mut fmt::Formatter) -> fmt::Result {
+        self.chars().try_for_each(|u16c| fmt::Display::fmt(&u16c, fmtr) )
+    }
+}
+// compare the content, not the unused part of the array
+impl<const N: usize> hash::Hash for Utf16ArrayString<N> {
+    fn hash<H : hash::Hasher>(&self,  state: &mut H) {
+        self.as_slice().hash(state);
+    }
+}
+impl<const N: usize, const M: usize> PartialEq<Utf16ArrayString<M>> for Utf16ArrayString<N> {
+    fn eq(&self,  other: &Utf16ArrayString<M>) -> bool {
+        self.as_slice() == other.as_slice()
+    }
+}
+impl<const N: usize> Eq for Utf16ArrayString<N> {}
+impl<const N: usize> PartialOrd for Utf16ArrayString<N> {
+    fn partial_cmp(&self,  other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+/// Orders by codepoint like `str` and `Utf16Char`,
+/// so surrogate pairs sort after U+E000..=U+FFFF.
+impl<const N: usize> Ord for Utf16ArrayString<N> {
+    fn cmp(&self,  other: &Self) -> Ordering {
+        self.chars().cmp(other.chars())
+    }
+}
+impl<const N: usize> PartialEq<str> for Utf16ArrayString<N> {
+    fn eq(&self,  s: &str) -> bool {
+        self.as_slice().iter().cloned().eq(s.encode_utf16())
+    }
+}
+impl<'a, const N: usize> PartialEq<&'a str> for Utf16ArrayString<N> {
+    fn eq(&self,  s: &&'a str) -> bool {
+        *self == **s
+    }
+}
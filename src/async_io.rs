@@ -0,0 +1,396 @@
+/* Copyright 2022 Torbjørn Birch Moltu
+ *
+ * Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+ * http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+ * http://opensource.org/licenses/MIT>, at your option. This file may not be
+ * copied, modified, or distributed except according to those terms.
+ */
+
+//! Asynchronous readers which validate or transcode text as it's read.
+//!
+//! Requires the feature "tokio-io" for tokio's `AsyncRead` and `AsyncBufRead`,
+//! or "futures" for those of [futures-io](https://docs.rs/futures-io/0.3/futures_io/).
+//!
+//! The readers implement `AsyncBufRead` as well as `AsyncRead`, and only ever
+//! return complete codepoints, so every buffer from `poll_fill_buf()` is valid
+//! UTF-8 or UTF-16 on its own.
+//!
+//! Invalid input is reported as an `io::Error` of kind `InvalidData` which wraps a
+//! [`PositionedError`](../error/struct.PositionedError.html) with the offset in
+//! bytes of the invalid sequence in the source stream.
+//! The text before the invalid sequence is returned first, and once the error has
+//! been returned, all further reads return it again.
+//! A codepoint that is incomplete at the end of the source is reported as
+//! [`TooFewBytes`](../error/enum.Utf8ErrorKind.html#variant.TooFewBytes) or
+//! [`Incomplete`](../error/enum.Utf16PairError.html#variant.Incomplete).
+//!
+//! Unlike the iterator adapters, which decode one codepoint at a time, the
+//! readers convert everything a read returned with the slice functions, so that
+//! runs of ASCII are handled a block at a time.
+//! An incomplete codepoint at the end of a read is kept and converted together
+//! with the next read, so each byte is only converted once,
+//! and the buffers are reused between reads.
+//!
+//! # Examples
+//!
+//! Reading text from a socket or pipe:
+//!
+#![cfg_attr(feature="tokio-io", doc=" ```")]
+#![cfg_attr(not(feature="tokio-io"), doc=" ```ignore")]
+//! use encode_unicode::async_io::Utf16ToUtf8Reader;
+//! use encode_unicode::error::{PositionedError, Utf16PairError};
+//! use tokio::io::{AsyncReadExt, AsyncWriteExt};
+//!
+//! # tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(async {
+//! let (mut socket, other_end) = tokio::io::duplex(64);
+//! socket.write_all(b"\0W\0h\xd8\x3d\xdc\x0b\0?\xdc\x00").await.unwrap();
+//! drop(socket);
+//!
+//! let mut reader = Utf16ToUtf8Reader::new_be(other_end);
+//! let mut text = Vec::new();
+//! let error = reader.read_to_end(&mut text).await.unwrap_err();
+//! assert_eq!(text, "Wh🐋?".as_bytes());
+//! let positioned = error.get_ref().unwrap().downcast_ref::<PositionedError<Utf16PairError>>();
+//! assert_eq!(positioned.map(|e| (e.offset(), *e.error()) ), Some((10, Utf16PairError::UnexpectedTrailingSurrogate)));
+//! # });
+//! ```
+
+use crate::errors::{PositionedError, TranscodeError, Utf8Error, Utf8ErrorKind, Utf16PairError};
+use crate::lengths::utf8_codepoint_count;
+use crate::transcoding::{transcode_utf8_to_utf16, transcode_utf16_to_utf8};
+use std::error::Error;
+use std::{fmt, io};
+use std::vec::Vec;
+use core::pin::Pin;
+use core::task::{Context, Poll};
+
+/// How much to read from the underlying reader at a time.
+const READ_SIZE: usize = 8*1024;
+
+
+/// What each reader does to the text.
+trait Conversion {
+    type Error: Error+Copy+Send+Sync+'static;
+    /// Convert as much of `src` as possible, and return how many bytes were used.
+    ///
+    /// If the result is an error, it is at the returned offset.
+    fn convert(&mut self,  src: &[u8],  dst: &mut Vec<u8>,  eof: bool) -> (usize, Result<(), Self::Error>);
+}
+
+#[derive(Clone,Copy, Debug)]
+struct ValidateUtf8;
+impl Conversion for ValidateUtf8 {
+    type Error = Utf8Error;
+    fn convert(&mut self,  src: &[u8],  dst: &mut Vec<u8>,  eof: bool) -> (usize, Result<(), Utf8Error>) {
+        let (valid, result) = match utf8_codepoint_count(src) {
+            Ok(_) => (src.len(), Ok(())),
+            Err(e) if e.error().kind() == Utf8ErrorKind::TooFewBytes && !eof => (e.offset(), Ok(())),
+            Err(e) => (e.offset(), Err(*e.error())),
+        };
+        dst.extend_from_slice(&src[..valid]);
+        (valid, result)
+    }
+}
+
+#[derive(Clone, Debug)]
+struct Utf16ToUtf8 {
+    to_unit: fn([u8; 2]) -> u16,
+    /// The units of the current read, kept to reuse the allocation.
+    units: Vec<u16>,
+}
+impl Conversion for Utf16ToUtf8 {
+    type Error = Utf16PairError;
+    fn convert(&mut self,  src: &[u8],  dst: &mut Vec<u8>,  eof: bool) -> (usize, Result<(), Utf16PairError>) {
+        let to_unit = self.to_unit;
+        self.units.clear();
+        self.units.extend(src.chunks_exact(2).map(|pair| to_unit([pair[0], pair[1]]) ));
+        let start = dst.len();
+        // no codepoint is more than three bytes per unit
+        dst.resize(start + 3*self.units.len(), 0);
+        let (read, written, result) = transcode_utf16_to_utf8(&self.units, &mut dst[start..]);
+        dst.truncate(start + written);
+        let result = match result {
+            Ok(()) if eof && src.len() != 2*self.units.len() => Err(Utf16PairError::Incomplete),
+            Ok(()) => Ok(()),
+            Err(TranscodeError::Invalid(Utf16PairError::Incomplete)) if !eof => Ok(()),
+            Err(TranscodeError::Invalid(e)) => Err(e),
+            Err(TranscodeError::OutputFull { .. }) => unreachable!("the output has room for everything"),
+        };
+        (2*read, result)
+    }
+}
+
+#[derive(Clone, Debug)]
+struct Utf8ToUtf16 {
+    to_bytes: fn(u16) -> [u8; 2],
+    /// The units of the current read, kept to reuse the allocation.
+    units: Vec<u16>,
+}
+impl Conversion for Utf8ToUtf16 {
+    type Error = Utf8Error;
+    fn convert(&mut self,  src: &[u8],  dst: &mut Vec<u8>,  eof: bool) -> (usize, Result<(), Utf8Error>) {
+        // every byte produces at most one unit
+        self.units.clear();
+        self.units.resize(src.len(), 0);
+        let (read, written, result) = transcode_utf8_to_utf16(src, &mut self.units);
+        dst.reserve(2*written);
+        for &unit in &self.units[..written] {
+            dst.extend_from_slice(&(self.to_bytes)(unit));
+        }
+        let result = match result {
+            Ok(()) => Ok(()),
+            Err(TranscodeError::Invalid(e)) if e.kind() == Utf8ErrorKind::TooFewBytes && !eof => Ok(()),
+            Err(TranscodeError::Invalid(e)) => Err(e),
+            Err(TranscodeError::OutputFull { .. }) => unreachable!("the output has room for everything"),
+        };
+        (read, result)
+    }
+}
+
+
+/// The buffering and error handling shared by all the readers.
+struct Reader<R, C: Conversion> {
+    inner: R,
+    conversion: C,
+    /// Read but not yet converted, usually an incomplete codepoint.
+    input: Vec<u8>,
+    /// The offset of `input[0]` in the source.
+    offset: usize,
+    /// Converted, and not consumed before `output[consumed..]`.
+    output: Vec<u8>,
+    consumed: usize,
+    eof: bool,
+    error: Option<PositionedError<C::Error>>,
+}
+impl<R, C: Conversion> Reader<R, C> {
+    fn new(inner: R,  conversion: C) -> Self {
+        Reader {
+            inner,
+            conversion,
+            input: Vec::new(),
+            offset: 0,
+            output: Vec::new(),
+            consumed: 0,
+            eof: false,
+            error: None,
+        }
+    }
+    /// Whether `poll_fill_buf()` can return without reading more.
+    fn is_ready(&self) -> bool {
+        self.consumed < self.output.len()  ||  self.error.is_some()  ||  self.eof
+    }
+    fn available(&self) -> io::Result<&[u8]> {
+        match self.error {
+            Some(error) if self.consumed == self.output.len() => {
+                Err(io::Error::new(io::ErrorKind::InvalidData, error))
+            }
+            _ => Ok(&self.output[self.consumed..]),
+        }
+    }
+    /// Make room for reading into `input[unconverted..]`.
+    fn read_space(&mut self) -> usize {
+        let unconverted = self.input.len();
+        self.input.resize(unconverted + READ_SIZE, 0);
+        unconverted
+    }
+    /// Convert what was read into `input[unconverted..]`.
+    fn convert_read(&mut self,  unconverted: usize,  read: usize) {
+        self.input.truncate(unconverted + read);
+        self.eof = read == 0;
+        self.output.clear();
+        self.consumed = 0;
+        let (used, result) = self.conversion.convert(&self.input, &mut self.output, self.eof);
+        if let Err(error) = result {
+            self.error = Some(PositionedError { offset: self.offset + used,  error });
+        }
+        self.input.drain(..used);
+        self.offset += used;
+    }
+    /// Undo `read_space()` if the read failed or is pending.
+    fn cancel_read(&mut self,  unconverted: usize) {
+        self.input.truncate(unconverted);
+    }
+    fn consume(&mut self,  amount: usize) {
+        self.consumed = usize::min(self.consumed + amount, self.output.len());
+    }
+}
+
+
+macro_rules! reader {($(#[$doc:meta])* $name:ident<R>: $conversion:ty) => {
+    $(#[$doc])*
+    pub struct $name<R>(Reader<R, $conversion>);
+    impl<R> $name<R> {
+        /// Get a reference to the underlying reader.
+        pub fn get_ref(&self) -> &R {
+            &self.0.inner
+        }
+        /// Get a mutable reference to the underlying reader.
+        ///
+        /// Reading directly from it will likely corrupt the text.
+        pub fn get_mut(&mut self) -> &mut R {
+            &mut self.0.inner
+        }
+        /// Get back the underlying reader.
+        ///
+        /// Anything that has been read from it but not from this wrapper is lost.
+        pub fn into_inner(self) -> R {
+            self.0.inner
+        }
+    }
+    impl<R: fmt::Debug> fmt::Debug for $name<R> {
+        fn fmt(&self,  fmtr: &mut fmt::Formatter) -> fmt::Result {
+            fmtr.debug_struct(stringify!($name))
+                .field("inner", &self.0.inner)
+                .field("offset", &self.0.offset)
+                .field("error", &self.0.error)
+                .finish()
+        }
+    }
+    #[cfg(feature="tokio-io")]
+    impl<R: tokio::io::AsyncRead+Unpin> tokio::io::AsyncBufRead for $name<R> {
+        fn poll_fill_buf(self: Pin<&mut Self>,  cx: &mut Context<'_>) -> Poll<io::Result<&[u8]>> {
+            let reader = &mut self.get_mut().0;
+            while !reader.is_ready() {
+                let unconverted = reader.read_space();
+                let mut buf = tokio::io::ReadBuf::new(&mut reader.input[unconverted..]);
+                match Pin::new(&mut reader.inner).poll_read(cx, &mut buf) {
+                    Poll::Ready(Ok(())) => {
+                        let read = buf.filled().len();
+                        reader.convert_read(unconverted, read);
+                    }
+                    Poll::Ready(Err(e)) => {
+                        reader.cancel_read(unconverted);
+                        return Poll::Ready(Err(e));
+                    }
+                    Poll::Pending => {
+                        reader.cancel_read(unconverted);
+                        return Poll::Pending;
+                    }
+                }
+            }
+            Poll::Ready(reader.available())
+        }
+        fn consume(self: Pin<&mut Self>,  amount: usize) {
+            self.get_mut().0.consume(amount);
+        }
+    }
+    #[cfg(feature="tokio-io")]
+    impl<R: tokio::io::AsyncRead+Unpin> tokio::io::AsyncRead for $name<R> {
+        fn poll_read(mut self: Pin<&mut Self>,  cx: &mut Context<'_>,  buf: &mut tokio::io::ReadBuf<'_>)
+        -> Poll<io::Result<()>> {
+            use tokio::io::AsyncBufRead;
+            let available = match self.as_mut().poll_fill_buf(cx) {
+                Poll::Ready(Ok(available)) => available,
+                Poll::Ready(Err(e)) => return Poll::Ready(Err(e)),
+                Poll::Pending => return Poll::Pending,
+            };
+            let amount = usize::min(available.len(), buf.remaining());
+            buf.put_slice(&available[..amount]);
+            self.as_mut().consume(amount);
+            Poll::Ready(Ok(()))
+        }
+    }
+    #[cfg(feature="futures")]
+    impl<R: futures_io::AsyncRead+Unpin> futures_io::AsyncBufRead for $name<R> {
+        fn poll_fill_buf(self: Pin<&mut Self>,  cx: &mut Context<'_>) -> Poll<io::Result<&[u8]>> {
+            let reader = &mut self.get_mut().0;
+            while !reader.is_ready() {
+                let unconverted = reader.read_space();
+                match Pin::new(&mut reader.inner).poll_read(cx, &mut reader.input[unconverted..]) {
+                    Poll::Ready(Ok(read)) => reader.convert_read(unconverted, read),
+                    Poll::Ready(Err(e)) => {
+                        reader.cancel_read(unconverted);
+                        return Poll::Ready(Err(e));
+                    }
+                    Poll::Pending => {
+                        reader.cancel_read(unconverted);
+                        return Poll::Pending;
+                    }
+                }
+            }
+            Poll::Ready(reader.available())
+        }
+        fn consume(self: Pin<&mut Self>,  amount: usize) {
+            self.get_mut().0.consume(amount);
+        }
+    }
+    #[cfg(feature="futures")]
+    impl<R: futures_io::AsyncRead+Unpin> futures_io::AsyncRead for $name<R> {
+        fn poll_read(mut self: Pin<&mut Self>,  cx: &mut Context<'_>,  buf: &mut [u8])
+        -> Poll<io::Result<usize>> {
+            use futures_io::AsyncBufRead;
+            let available = match self.as_mut().poll_fill_buf(cx) {
+                Poll::Ready(Ok(available)) => available,
+                Poll::Ready(Err(e)) => return Poll::Ready(Err(e)),
+                Poll::Pending => return Poll::Pending,
+            };
+            let amount = usize::min(available.len(), buf.len());
+            buf[..amount].copy_from_slice(&available[..amount]);
+            self.as_mut().consume(amount);
+            Poll::Ready(Ok(amount))
+        }
+    }
+}}
+
+reader!{
+    /// Pass through UTF-8 text after validating it.
+    ///
+    /// Errors contain a `PositionedError<Utf8Error>`.
+    ///
+    /// # Examples
+    ///
+    #[cfg_attr(feature="tokio-io", doc=" ```")]
+    #[cfg_attr(not(feature="tokio-io"), doc=" ```ignore")]
+    /// use encode_unicode::async_io::ValidatingReader;
+    /// use tokio::io::AsyncBufReadExt;
+    ///
+    /// # tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(async {
+    /// let mut lines = ValidatingReader::new(&b"one\ntwo\n\xc0\xaf\n"[..]).lines();
+    /// assert_eq!(lines.next_line().await.unwrap().as_deref(), Some("one"));
+    /// assert_eq!(lines.next_line().await.unwrap().as_deref(), Some("two"));
+    /// assert!(lines.next_line().await.is_err());
+    /// # });
+    /// ```
+    ValidatingReader<R>: ValidateUtf8
+}
+impl<R> ValidatingReader<R> {
+    /// Validate the UTF-8 read from `inner`.
+    pub fn new(inner: R) -> Self {
+        ValidatingReader(Reader::new(inner, ValidateUtf8))
+    }
+}
+
+reader!{
+    /// Convert UTF-16 to UTF-8.
+    ///
+    /// Errors contain a `PositionedError<Utf16PairError>`,
+    /// and an odd number of bytes is reported as an incomplete codepoint.
+    Utf16ToUtf8Reader<R>: Utf16ToUtf8
+}
+impl<R> Utf16ToUtf8Reader<R> {
+    /// Convert the little-endian UTF-16 read from `inner`.
+    pub fn new_le(inner: R) -> Self {
+        Utf16ToUtf8Reader(Reader::new(inner, Utf16ToUtf8 { to_unit: u16::from_le_bytes,  units: Vec::new() }))
+    }
+    /// Convert the big-endian UTF-16 read from `inner`.
+    pub fn new_be(inner: R) -> Self {
+        Utf16ToUtf8Reader(Reader::new(inner, Utf16ToUtf8 { to_unit: u16::from_be_bytes,  units: Vec::new() }))
+    }
+}
+
+reader!{
+    /// Convert UTF-8 to UTF-16.
+    ///
+    /// Errors contain a `PositionedError<Utf8Error>`.
+    Utf8ToUtf16Reader<R>: Utf8ToUtf16
+}
+impl<R> Utf8ToUtf16Reader<R> {
+    /// Convert the UTF-8 read from `inner` to little-endian UTF-16.
+    pub fn new_le(inner: R) -> Self {
+        Utf8ToUtf16Reader(Reader::new(inner, Utf8ToUtf16 { to_bytes: u16::to_le_bytes,  units: Vec::new() }))
+    }
+    /// Convert the UTF-8 read from `inner` to big-endian UTF-16.
+    pub fn new_be(inner: R) -> Self {
+        Utf8ToUtf16Reader(Reader::new(inner, Utf8ToUtf16 { to_bytes: u16::to_be_bytes,  units: Vec::new() }))
+    }
+}
@@ -0,0 +1,276 @@
+/* Copyright 2022 Torbjørn Birch Moltu
+ *
+ * Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+ * http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+ * http://opensource.org/licenses/MIT>, at your option. This file may not be
+ * copied, modified, or distributed except according to those terms.
+ */
+
+//! Finding codepoint boundaries in slices of `u8` or `u16` which might not
+//! be valid UTF-8 or UTF-16.
+//!
+//! The boundaries are purely structural: A sequence is never split if it
+//! *looks* like a codepoint, even if it encodes an invalid or overlong one.
+//! This means that splitting at a boundary never turns a valid codepoint into
+//! errors, and never turns an error into a valid codepoint.
+
+extern crate core;
+use core::fmt;
+
+/// Number of continuation bytes an UTF-8 start byte says follow it,
+/// including for invalid start bytes with a plausible bit pattern.
+#[inline]
+fn structural_extra_bytes(byte: u8) -> usize {
+    match byte {
+        0xc0..=0xdf => 1,
+        0xe0..=0xef => 2,
+        0xf0..=0xf7 => 3,
+        _ => 0,
+    }
+}
+
+#[inline]
+fn is_continuation_byte(byte: u8) -> bool {
+    byte & 0b1100_0000 == 0b1000_0000
+}
+
+
+/// Methods for finding codepoint boundaries in `[u8]` and `[u16]`.
+///
+/// The slices don't need to be valid UTF-8 or UTF-16, and the boundaries are
+/// found by looking at a few elements around the index only.
+///
+/// # Invalid UTF-8
+///
+/// A continuation byte is not a boundary if it's within the range of the
+/// closest preceding byte that isn't a continuation byte,
+/// which means that
+///
+/// * `0xc0..=0xdf` cover the next byte,
+/// * `0xe0..=0xef` cover the next two bytes,
+/// * `0xf0..=0xf7` cover the next three bytes,
+/// * ASCII and `0xf8..=0xff` cover no bytes.
+///
+/// Every other index is a boundary. Overlong encodings, encoded surrogates and
+/// too high codepoints are therefore treated like valid sequences,
+/// while every unexpected continuation byte is a sequence of its own.
+///
+/// # Invalid UTF-16
+///
+/// An index is a boundary unless it's between a leading and a trailing
+/// surrogate. Unpaired surrogates are therefore sequences of their own.
+///
+/// # Indexes out of range
+///
+/// Like [`str::is_char_boundary()`](https://doc.rust-lang.org/std/primitive.str.html#method.is_char_boundary),
+/// the length of the slice is a boundary but higher indexes are not.
+/// The other methods treat higher indexes as the length of the slice.
+///
+/// # Examples
+///
+/// ```
+/// use encode_unicode::CharBoundaryExt;
+///
+/// let bytes = b"\xe2\x82\xac \xe2\x82 \x80";
+/// assert!(!bytes.is_char_boundary(1));
+/// assert_eq!(bytes.floor_char_boundary(2), 0);
+/// assert_eq!(bytes.ceil_char_boundary(5), 6);// incomplete sequences are kept together
+/// assert!(bytes.is_char_boundary(7));// an unexpected continuation byte
+/// assert_eq!(bytes.next_char_start_after(7), 8);
+///
+/// let units: &[u16] = &[0xd83d, 0xde00, 0xde00];
+/// assert!(!units.is_char_boundary(1));
+/// assert!(units.is_char_boundary(2));
+/// ```
+pub trait CharBoundaryExt {
+    /// Is `index` the start of a codepoint or sequence, or the end of the slice?
+    ///
+    /// Returns `false` if `index` is greater than the length of the slice.
+    fn is_char_boundary(&self,  index: usize) -> bool;
+
+    /// Find the closest boundary that is not after `index`.
+    ///
+    /// Returns the length of the slice if `index` is greater than it.
+    fn floor_char_boundary(&self,  index: usize) -> usize;
+
+    /// Find the closest boundary that is not before `index`.
+    ///
+    /// Returns the length of the slice if `index` is greater than it.
+    fn ceil_char_boundary(&self,  index: usize) -> usize;
+
+    /// Find the start of the next codepoint or sequence after the one `index`
+    /// is part of.
+    ///
+    /// This is useful for resuming decoding after an error at `index`,
+    /// without skipping past the start of a valid codepoint.
+    /// Returns the length of the slice if `index` is not less than it.
+    fn next_char_start_after(&self,  index: usize) -> usize;
+
+    /// Split the slice into chunks of up to `max_len` elements,
+    /// which all start and end at boundaries.
+    ///
+    /// The last chunk might be shorter, and so might chunks that would
+    /// otherwise split a codepoint.
+    /// If a single codepoint or sequence is longer than `max_len`,
+    /// it is produced as its own chunk anyway.
+    ///
+    /// # Panics
+    ///
+    /// If `max_len` is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use encode_unicode::CharBoundaryExt;
+    ///
+    /// let mut chunks = "aæ€😀".as_bytes().char_boundary_chunks(3);
+    /// assert_eq!(chunks.next(), Some("aæ".as_bytes()));
+    /// assert_eq!(chunks.next(), Some("€".as_bytes()));
+    /// assert_eq!(chunks.next(), Some("😀".as_bytes()));
+    /// assert_eq!(chunks.next(), None);
+    /// ```
+    fn char_boundary_chunks(&self,  max_len: usize) -> CharBoundaryChunks<'_,Self>;
+}
+
+impl CharBoundaryExt for [u8] {
+    fn is_char_boundary(&self,  index: usize) -> bool {
+        if index >= self.len() {
+            return index == self.len();
+        }
+        self.floor_char_boundary(index) == index
+    }
+    fn floor_char_boundary(&self,  index: usize) -> usize {
+        if index >= self.len() {
+            return self.len();
+        }
+        // The closest preceding byte that isn't a continuation byte is the
+        // only one that can cover index, so look for it once.
+        let mut start = index;
+        while is_continuation_byte(self[start]) {
+            if start == 0  ||  index-start == 3 {
+                return index;
+            }
+            start -= 1;
+        }
+        if index-start > structural_extra_bytes(self[start]) {index} else {start}
+    }
+    fn ceil_char_boundary(&self,  index: usize) -> usize {
+        if index >= self.len() {
+            return self.len();
+        }
+        let start = self.floor_char_boundary(index);
+        if start == index {
+            return start;
+        }
+        // the bytes between start and index are all continuation bytes
+        let end = start + 1 + structural_extra_bytes(self[start]);
+        let mut ceil = index + 1;
+        while ceil < end  &&  ceil < self.len()  &&  is_continuation_byte(self[ceil]) {
+            ceil += 1;
+        }
+        ceil
+    }
+    fn next_char_start_after(&self,  index: usize) -> usize {
+        if index >= self.len() {
+            return self.len();
+        }
+        self.ceil_char_boundary(index+1)
+    }
+    fn char_boundary_chunks(&self,  max_len: usize) -> CharBoundaryChunks<'_,Self> {
+        assert!(max_len != 0, "chunk length must be at least one");
+        CharBoundaryChunks { remaining: self,  max_len }
+    }
+}
+
+impl CharBoundaryExt for [u16] {
+    fn is_char_boundary(&self,  index: usize) -> bool {
+        if index == 0  ||  index >= self.len() {
+            return index <= self.len();
+        }
+        // Clear the ten content bits of both, and check that they're
+        // a leading and a trailing surrogate.
+        (self[index-1] & 0xfc00) != 0xd800  ||  (self[index] & 0xfc00) != 0xdc00
+    }
+    fn floor_char_boundary(&self,  index: usize) -> usize {
+        if index >= self.len() {
+            self.len()
+        } else if self.is_char_boundary(index) {
+            index
+        } else {
+            index-1
+        }
+    }
+    fn ceil_char_boundary(&self,  index: usize) -> usize {
+        if index >= self.len() {
+            self.len()
+        } else if self.is_char_boundary(index) {
+            index
+        } else {
+            index+1
+        }
+    }
+    fn next_char_start_after(&self,  index: usize) -> usize {
+        if index >= self.len() {
+            return self.len();
+        }
+        self.ceil_char_boundary(index+1)
+    }
+    fn char_boundary_chunks(&self,  max_len: usize) -> CharBoundaryChunks<'_,Self> {
+        assert!(max_len != 0, "chunk length must be at least one");
+        CharBoundaryChunks { remaining: self,  max_len }
+    }
+}
+
+
+/// An iterator over chunks of a `[u8]` or `[u16]` that don't split codepoints.
+///
+/// This struct is created by
+/// [`CharBoundaryExt::char_boundary_chunks()`](../trait.CharBoundaryExt.html#tymethod.char_boundary_chunks).
+/// See its documentation for more.
+pub struct CharBoundaryChunks<'a, S: ?Sized+'a> {
+    remaining: &'a S,
+    max_len: usize,
+}
+impl<'a, S: ?Sized+'a> Clone for CharBoundaryChunks<'a,S> {
+    fn clone(&self) -> Self {
+        CharBoundaryChunks { remaining: self.remaining,  max_len: self.max_len }
+    }
+}
+impl<'a, T: 'a> CharBoundaryChunks<'a,[T]> where [T]: CharBoundaryExt {
+    /// Extract the part of the slice that has not been produced yet.
+    pub fn as_slice(&self) -> &'a [T] {
+        self.remaining
+    }
+}
+impl<'a, T: 'a> Iterator for CharBoundaryChunks<'a,[T]> where [T]: CharBoundaryExt {
+    type Item = &'a [T];
+    fn next(&mut self) -> Option<&'a [T]> {
+        if self.remaining.is_empty() {
+            return None;
+        }
+        let mut end = self.remaining.floor_char_boundary(self.max_len);
+        if end == 0 {
+            end = self.remaining.next_char_start_after(0);
+        }
+        let (chunk, remaining) = self.remaining.split_at(end);
+        self.remaining = remaining;
+        Some(chunk)
+    }
+    fn size_hint(&self) -> (usize,Option<usize>) {
+        let len = self.remaining.len();
+        // Chunks are only longer than max_len if a single codepoint is,
+        // and no codepoint is longer than four elements.
+        let longest = if self.max_len < 4 {4} else {self.max_len};
+        // For len+longest to overflow, the chunks must be absurdly long,
+        // and size_hint() doesn't need to be correct.
+        (len.wrapping_add(longest-1) / longest,  Some(len))
+    }
+}
+impl<'a, T: fmt::Debug+'a> fmt::Debug for CharBoundaryChunks<'a,[T]> {
+    fn fmt(&self,  fmtr: &mut fmt::Formatter) -> fmt::Result {
+        fmtr.debug_struct("CharBoundaryChunks")
+            .field("max_len", &self.max_len)
+            .field("remaining", &self.remaining)
+            .finish()
+    }
+}
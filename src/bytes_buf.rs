@@ -0,0 +1,206 @@
+/* Copyright 2022 Torbjørn Birch Moltu
+ *
+ * Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+ * http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+ * http://opensource.org/licenses/MIT>, at your option. This file may not be
+ * copied, modified, or distributed except according to those terms.
+ */
+
+//! Extension traits for the [bytes](https://docs.rs/bytes/1/bytes/) crate's `Buf` and `BufMut`.
+
+use crate::utf8_char::Utf8Char;
+use crate::utf16_char::Utf16Char;
+use crate::traits::{U8UtfExt, U16UtfExt};
+use crate::errors::{Utf8Error, Utf8ErrorKind, Utf16ArrayError, Utf16PairError};
+use bytes::{Buf, BufMut};
+
+/// Decode UTF-8 and UTF-16 codepoints from a `Buf`,
+/// including codepoints that are split between chunks.
+///
+/// Requires the feature "bytes".
+///
+/// As `Buf` cannot look ahead past the current chunk without consuming it,
+/// the bytes of an invalid codepoint are consumed together with the error,
+/// and the end of the buffer is treated as the end of the input.
+/// When decoding text that is still arriving, only call these methods
+/// when at least four bytes remain or no more will come.
+///
+/// # Examples
+///
+/// ```
+/// use encode_unicode::{BufExt, Utf8Char};
+/// use encode_unicode::error::Utf8ErrorKind;
+/// use bytes::Buf;
+///
+/// let mut buf = (&b"\xc3"[..]).chain(&b"\xa6\xff!"[..]);
+/// assert_eq!(buf.get_utf8_char(), Ok(Utf8Char::from('æ')));
+/// assert_eq!(buf.get_utf8_char().unwrap_err().kind(), Utf8ErrorKind::NonUtf8Byte);
+/// assert_eq!(buf.get_utf8_char(), Ok(Utf8Char::from('!')));
+/// assert_eq!(buf.get_utf8_char().unwrap_err().kind(), Utf8ErrorKind::TooFewBytes);
+/// ```
+pub trait BufExt: Buf {
+    /// Decode an UTF-8 codepoint and advance past it.
+    ///
+    /// # Errors
+    ///
+    /// If the bytes are not valid UTF-8, the first byte and the continuation
+    /// bytes that belong to it are consumed, so the next call starts at
+    /// a byte that could begin a codepoint.
+    /// If the buffer is empty or ends in the middle of a codepoint,
+    /// `TooFewBytes` is returned and the rest of the buffer is consumed.
+    fn get_utf8_char(&mut self) -> Result<Utf8Char, Utf8Error> {
+        let first = match self.chunk().first() {
+            Some(&first) => first,
+            None => return Err(Utf8Error { kind: Utf8ErrorKind::TooFewBytes }),
+        };
+        let len = match first.extra_utf8_bytes() {
+            Ok(extra) => 1 + extra,
+            Err(e) => {
+                self.advance(1);
+                return Err(e);
+            }
+        };
+        if let Some(start) = self.chunk().get(..len) {
+            if let Ok((u8c, _)) = Utf8Char::from_slice_start(start) {
+                self.advance(len);
+                return Ok(u8c);
+            }
+        }
+        // the codepoint is split between chunks or invalid,
+        // so look at one byte at a time
+        let mut bytes = [first, 0, 0, 0];
+        self.advance(1);
+        for read in 1..len {
+            match self.chunk().first() {
+                Some(&next) if next & 0b1100_0000 == 0b1000_0000 => {
+                    bytes[read] = next;
+                    self.advance(1);
+                }
+                Some(&next) => {
+                    // include the byte that interrupts it to get the right error,
+                    // but leave it in the buffer
+                    bytes[read] = next;
+                    return Err(Utf8Char::from_slice_start(&bytes[..=read]).unwrap_err());
+                }
+                None => return Err(Utf8Char::from_slice_start(&bytes[..read]).unwrap_err()),
+            }
+        }
+        Utf8Char::from_slice_start(&bytes[..len]).map(|(u8c, _)| u8c )
+    }
+
+    /// Decode a big-endian UTF-16 codepoint and advance past it.
+    ///
+    /// # Errors
+    ///
+    /// A lone surrogate is consumed by itself, so a codepoint after it is
+    /// still returned by the next call, except when a leading surrogate is
+    /// followed by a little-endian unit that is split between chunks:
+    /// then that unit has to be consumed to see whether it's a trailing surrogate.
+    /// If the buffer is empty or ends in the middle of a codepoint,
+    /// `Incomplete` is returned and the rest of the buffer is consumed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use encode_unicode::{BufExt, Utf16Char};
+    /// use encode_unicode::error::Utf16PairError;
+    /// use bytes::Buf;
+    ///
+    /// let mut buf = (&b"\xd8\x3d\xdc"[..]).chain(&b"\x0b\xdc\x0b"[..]);
+    /// assert_eq!(buf.get_utf16_char(), Ok(Utf16Char::from('🐋')));
+    /// assert_eq!(buf.get_utf16_char(), Err(Utf16PairError::UnexpectedTrailingSurrogate));
+    /// assert_eq!(buf.get_utf16_char(), Err(Utf16PairError::Incomplete));
+    /// ```
+    fn get_utf16_char(&mut self) -> Result<Utf16Char, Utf16PairError> {
+        get_utf16(self, Self::get_u16, 0)
+    }
+
+    /// Decode a little-endian UTF-16 codepoint and advance past it.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`get_utf16_char()`](#method.get_utf16_char).
+    fn get_utf16le_char(&mut self) -> Result<Utf16Char, Utf16PairError> {
+        get_utf16(self, Self::get_u16_le, 1)
+    }
+}
+
+impl<B: Buf + ?Sized> BufExt for B {}
+
+/// `high_byte` is the position of the most significant byte of a unit.
+fn get_utf16<B: Buf + ?Sized>(buf: &mut B,  get_unit: fn(&mut B) -> u16,  high_byte: usize)
+-> Result<Utf16Char, Utf16PairError> {
+    if buf.remaining() < 2 {
+        buf.advance(buf.remaining());
+        return Err(Utf16PairError::Incomplete);
+    }
+    let first = get_unit(buf);
+    let second = if first.is_utf16_leading_surrogate() {
+        if buf.remaining() < 2 {
+            buf.advance(buf.remaining());
+            return Err(Utf16PairError::Incomplete);
+        }
+        // the most significant byte is enough to tell whether it's a trailing surrogate,
+        // so leave the unit in the buffer if it isn't
+        match buf.chunk().get(high_byte) {
+            Some(&high) if high & 0xfc != 0xdc => return Err(Utf16PairError::UnmatchedLeadingSurrogate),
+            _ => get_unit(buf),
+        }
+    } else {
+        0
+    };
+    Utf16Char::from_array([first, second]).map_err(|e| match e {
+        Utf16ArrayError::FirstIsTrailingSurrogate => Utf16PairError::UnexpectedTrailingSurrogate,
+        Utf16ArrayError::SecondIsNotTrailingSurrogate => Utf16PairError::UnmatchedLeadingSurrogate,
+    })
+}
+
+/// Encode UTF-8 and UTF-16 codepoints into a `BufMut`.
+///
+/// Requires the feature "bytes".
+///
+/// # Panics
+///
+/// Like `BufMut::put_slice()`, the methods panic if there isn't enough
+/// remaining capacity for the whole codepoint.
+///
+/// # Examples
+///
+/// ```
+/// use encode_unicode::BufMutExt;
+/// use bytes::BytesMut;
+///
+/// let mut buf = BytesMut::new();
+/// buf.put_utf8_char('ø');
+/// buf.put_utf16le_char('ø');
+/// buf.put_utf16_char('😀');
+/// assert_eq!(&buf[..], b"\xc3\xb8\xf8\x00\xd8\x3d\xde\x00");
+/// ```
+pub trait BufMutExt: BufMut {
+    /// Write the UTF-8 encoding of a codepoint.
+    fn put_utf8_char<C: Into<Utf8Char>>(&mut self,  c: C) {
+        self.put_slice(c.into().as_bytes());
+    }
+    /// Write the big-endian UTF-16 encoding of a codepoint.
+    fn put_utf16_char<C: Into<Utf16Char>>(&mut self,  c: C) {
+        let utf16 = c.into();
+        if self.remaining_mut() < 2*utf16.len() {
+            panic!("not enough capacity for {:?} as UTF-16", utf16);
+        }
+        for &unit in utf16.as_ref() {
+            self.put_u16(unit);
+        }
+    }
+    /// Write the little-endian UTF-16 encoding of a codepoint.
+    fn put_utf16le_char<C: Into<Utf16Char>>(&mut self,  c: C) {
+        let utf16 = c.into();
+        if self.remaining_mut() < 2*utf16.len() {
+            panic!("not enough capacity for {:?} as UTF-16", utf16);
+        }
+        for &unit in utf16.as_ref() {
+            self.put_u16_le(unit);
+        }
+    }
+}
+
+impl<B: BufMut + ?Sized> BufMutExt for B {}
@@ -0,0 +1,194 @@
+/* Copyright 2022 Torbjørn Birch Moltu
+ *
+ * Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+ * http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+ * http://opensource.org/licenses/MIT>, at your option. This file may not be
+ * copied, modified, or distributed except according to those terms.
+ */
+
+//! [tokio-util](https://docs.rs/tokio-util/0.7/tokio_util/codec/index.html)
+//! codecs for UTF-8 streams.
+//!
+//! Requires the feature "tokio-codec".
+//!
+//! The decoders return invalid UTF-8 as an `io::Error` of kind `InvalidData`
+//! which wraps a [`PositionedError<Utf8Error>`](../error/struct.PositionedError.html)
+//! with the offset of the invalid sequence in the whole stream.
+//! The invalid sequence is skipped one byte at a time like
+//! [`Utf8CharDecoder`](../iterator/struct.Utf8CharDecoder.html) does,
+//! so decoding can continue after an error.
+//! A codepoint that is incomplete at the end of the stream is reported as
+//! [`TooFewBytes`](../error/enum.Utf8ErrorKind.html#variant.TooFewBytes).
+//!
+//! # Examples
+//!
+//! ```
+//! use encode_unicode::codec::Utf8ChunkCodec;
+//! use encode_unicode::error::{PositionedError, Utf8Error, Utf8ErrorKind};
+//! use tokio_util::codec::Decoder;
+//! use bytes::BytesMut;
+//!
+//! let mut codec = Utf8ChunkCodec::new();
+//! let mut buf = BytesMut::from(&b"r\xc3\xa6v\xc3"[..]);
+//! assert_eq!(codec.decode(&mut buf).unwrap(), Some("ræv".to_string()));
+//! assert_eq!(codec.decode(&mut buf).unwrap(), None);
+//! buf.extend_from_slice(b"\xa6\xff");
+//! assert_eq!(codec.decode(&mut buf).unwrap(), Some("æ".to_string()));
+//! let error = codec.decode(&mut buf).unwrap_err();
+//! let positioned = error.get_ref().unwrap().downcast_ref::<PositionedError<Utf8Error>>().unwrap();
+//! assert_eq!((positioned.offset(), positioned.error().kind()), (6, Utf8ErrorKind::NonUtf8Byte));
+//! ```
+
+use crate::utf8_char::Utf8Char;
+use crate::errors::{PositionedError, Utf8Error, Utf8ErrorKind};
+use crate::lengths::utf8_codepoint_count;
+use std::io;
+use std::string::String;
+use bytes::{Buf, BufMut, BytesMut};
+use tokio_util::codec::{Decoder, Encoder};
+
+fn invalid_data(offset: usize,  error: Utf8Error) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, PositionedError { offset,  error })
+}
+
+/// Decode a UTF-8 stream into `Utf8Char`s, and encode `Utf8Char`s or `char`s.
+///
+/// # Examples
+///
+/// ```
+/// use encode_unicode::codec::Utf8CharCodec;
+/// use tokio_util::codec::Decoder;
+/// use bytes::BytesMut;
+///
+/// let mut codec = Utf8CharCodec::new();
+/// let mut buf = BytesMut::from(&b"\xe2\x82\xaca\xf0\x9f"[..]);
+/// assert_eq!(codec.decode(&mut buf).unwrap(), Some('€'.into()));
+/// assert_eq!(codec.decode(&mut buf).unwrap(), Some('a'.into()));
+/// assert_eq!(codec.decode(&mut buf).unwrap(), None);
+/// assert_eq!(codec.offset(), 4);
+/// assert!(codec.decode_eof(&mut buf).is_err());
+/// ```
+#[derive(Clone,Copy, Debug, Default, PartialEq,Eq)]
+pub struct Utf8CharCodec {
+    offset: usize,
+}
+impl Utf8CharCodec {
+    /// Create a codec for a new stream.
+    pub const fn new() -> Self {
+        Utf8CharCodec { offset: 0 }
+    }
+    /// The number of bytes decoded or skipped so far,
+    /// which is the offset of the next byte in the stream.
+    pub const fn offset(&self) -> usize {
+        self.offset
+    }
+}
+impl Decoder for Utf8CharCodec {
+    type Item = Utf8Char;
+    type Error = io::Error;
+    fn decode(&mut self,  src: &mut BytesMut) -> Result<Option<Utf8Char>, io::Error> {
+        if src.is_empty() {
+            return Ok(None);
+        }
+        match Utf8Char::from_slice_start(src) {
+            Ok((u8c, len)) => {
+                src.advance(len);
+                self.offset += len;
+                Ok(Some(u8c))
+            }
+            Err(e) if e.kind() == Utf8ErrorKind::TooFewBytes => Ok(None),
+            Err(e) => {
+                let offset = self.offset;
+                src.advance(1);
+                self.offset += 1;
+                Err(invalid_data(offset, e))
+            }
+        }
+    }
+    fn decode_eof(&mut self,  src: &mut BytesMut) -> Result<Option<Utf8Char>, io::Error> {
+        match self.decode(src)? {
+            None if !src.is_empty() => {
+                let error = Utf8Char::from_slice_start(src).unwrap_err();
+                let offset = self.offset;
+                self.offset += src.len();
+                src.clear();
+                Err(invalid_data(offset, error))
+            }
+            decoded => Ok(decoded),
+        }
+    }
+}
+impl<C: Into<Utf8Char>> Encoder<C> for Utf8CharCodec {
+    type Error = io::Error;
+    fn encode(&mut self,  c: C,  dst: &mut BytesMut) -> Result<(), io::Error> {
+        dst.put_slice(c.into().as_bytes());
+        Ok(())
+    }
+}
+
+/// Decode a UTF-8 stream into `String`s of all the complete codepoints
+/// received so far, and encode string slices.
+///
+/// The chunks are split only at codepoint boundaries, and otherwise wherever
+/// the reads from the underlying stream happened to end.
+/// If there is an invalid sequence, the valid text before it is returned first.
+#[derive(Clone,Copy, Debug, Default, PartialEq,Eq)]
+pub struct Utf8ChunkCodec {
+    offset: usize,
+}
+impl Utf8ChunkCodec {
+    /// Create a codec for a new stream.
+    pub const fn new() -> Self {
+        Utf8ChunkCodec { offset: 0 }
+    }
+    /// The number of bytes decoded or skipped so far,
+    /// which is the offset of the next byte in the stream.
+    pub const fn offset(&self) -> usize {
+        self.offset
+    }
+    fn take(&mut self,  src: &mut BytesMut,  len: usize) -> String {
+        let chunk = src.split_to(len);
+        self.offset += len;
+        // SAFETY: validated by the caller
+        unsafe { String::from_utf8_unchecked(chunk.to_vec()) }
+    }
+}
+impl Decoder for Utf8ChunkCodec {
+    type Item = String;
+    type Error = io::Error;
+    fn decode(&mut self,  src: &mut BytesMut) -> Result<Option<String>, io::Error> {
+        if src.is_empty() {
+            return Ok(None);
+        }
+        match utf8_codepoint_count(src) {
+            Ok(_) => Ok(Some(self.take(src, src.len()))),
+            Err(e) if e.offset() > 0 => Ok(Some(self.take(src, e.offset()))),
+            Err(e) if e.error().kind() == Utf8ErrorKind::TooFewBytes => Ok(None),
+            Err(e) => {
+                let offset = self.offset;
+                src.advance(1);
+                self.offset += 1;
+                Err(invalid_data(offset, *e.error()))
+            }
+        }
+    }
+    fn decode_eof(&mut self,  src: &mut BytesMut) -> Result<Option<String>, io::Error> {
+        match self.decode(src)? {
+            None if !src.is_empty() => {
+                let error = Utf8Char::from_slice_start(src).unwrap_err();
+                let offset = self.offset;
+                self.offset += src.len();
+                src.clear();
+                Err(invalid_data(offset, error))
+            }
+            decoded => Ok(decoded),
+        }
+    }
+}
+impl<S: AsRef<str>> Encoder<S> for Utf8ChunkCodec {
+    type Error = io::Error;
+    fn encode(&mut self,  s: S,  dst: &mut BytesMut) -> Result<(), io::Error> {
+        dst.put_slice(s.as_ref().as_bytes());
+        Ok(())
+    }
+}
@@ -0,0 +1,244 @@
+/* Copyright 2022 Torbjørn Birch Moltu
+ *
+ * Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+ * http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+ * http://opensource.org/licenses/MIT>, at your option. This file may not be
+ * copied, modified, or distributed except according to those terms.
+ */
+
+//! Functions for corrupting valid UTF-8 and UTF-16 in controlled ways,
+//! for testing how parsers handle invalid input.
+//!
+//! Together with the corrupted text they return the errors that
+//! [`Utf8CharDecoder`](../iterator/struct.Utf8CharDecoder.html) and
+//! [`Utf16CharDecoder`](../iterator/struct.Utf16CharDecoder.html)
+//! will report for it, and at which offsets.
+//!
+//! Requires the feature "alloc".
+
+use crate::errors::{Utf8ErrorKind, Utf16PairError};
+use crate::traits::U16UtfExt;
+use alloc::vec::Vec;
+
+/// A way to make UTF-8 invalid, applied to a single codepoint.
+#[derive(Clone,Copy, Debug, PartialEq,Eq)]
+pub enum Utf8Corruption {
+    /// Replace the codepoint with an encoding that is one byte longer than
+    /// necessary, or two bytes for ASCII characters.
+    ///
+    /// Produces `OverlongEncoding` followed by `UnexpectedContinuationByte`
+    /// for every byte after the first.
+    /// Codepoints outside the basic multilingual plane cannot be made
+    /// overlong, as they already need four bytes.
+    Overlong,
+    /// Remove the last byte of a multi-byte codepoint.
+    ///
+    /// Produces `InterruptedSequence`, or `TooFewBytes` at the end of the
+    /// text, followed by `UnexpectedContinuationByte` for every remaining
+    /// byte after the first.
+    Split,
+    /// Insert a continuation byte after the codepoint.
+    ///
+    /// Produces `UnexpectedContinuationByte`.
+    StrayContinuationByte,
+    /// Insert the three-byte encoding of an UTF-16 surrogate after the codepoint.
+    ///
+    /// Produces `Utf16ReservedCodepoint` followed by two
+    /// `UnexpectedContinuationByte`s.
+    EncodedSurrogate(u16),
+}
+
+/// A way to make UTF-16 invalid, applied to a single codepoint.
+#[derive(Clone,Copy, Debug, PartialEq,Eq)]
+pub enum Utf16Corruption {
+    /// Insert a leading surrogate that is not followed by a trailing one
+    /// after the codepoint.
+    ///
+    /// Produces `UnmatchedLeadingSurrogate`, or `Incomplete` at the end of the text.
+    LoneLeadingSurrogate,
+    /// Insert a trailing surrogate after the codepoint.
+    ///
+    /// Produces `UnexpectedTrailingSurrogate`.
+    LoneTrailingSurrogate,
+    /// Remove the trailing surrogate of a codepoint outside the basic multilingual plane.
+    ///
+    /// Produces `UnmatchedLeadingSurrogate`, or `Incomplete` at the end of the text.
+    Split,
+}
+
+/// Corrupt UTF-8 text and return the errors it will produce.
+///
+/// Each corruption is applied to the codepoint starting at the given byte offset
+/// in `s`, and the offsets must be increasing.
+/// The returned errors are those that decoding the corrupted bytes with
+/// [`SliceExt::utf8char_indices()`](../trait.SliceExt.html#tymethod.utf8char_indices)
+/// produces, together with their offsets in the corrupted bytes.
+///
+/// # Panics
+///
+/// If an offset is not at the start of a codepoint, or not after the previous offset,
+/// or the corruption cannot be applied to the codepoint at it.
+///
+/// # Examples
+///
+/// ```
+/// use encode_unicode::corrupt::{corrupt_utf8, Utf8Corruption};
+/// use encode_unicode::error::Utf8ErrorKind::*;
+/// use encode_unicode::SliceExt;
+///
+/// let corruptions = [(0, Utf8Corruption::Overlong), (4, Utf8Corruption::Split)];
+/// let (bytes, errors) = corrupt_utf8("hæ ‽", &corruptions);
+/// assert_eq!(bytes, b"\xe0\x81\xa8\xc3\xa6 \xe2\x80");
+/// assert_eq!(errors, [
+///     (0, OverlongEncoding),
+///     (1, UnexpectedContinuationByte),
+///     (2, UnexpectedContinuationByte),
+///     (6, TooFewBytes),
+///     (7, UnexpectedContinuationByte),
+/// ]);
+///
+/// let decoded = bytes.utf8char_indices()
+///     .filter_map(|(offset, result, _)| Some((offset, result.err()?.kind())) )
+///     .collect::<Vec<_>>();
+/// assert_eq!(decoded, errors);
+/// ```
+pub fn corrupt_utf8(s: &str,  corruptions: &[(usize, Utf8Corruption)])
+-> (Vec<u8>, Vec<(usize, Utf8ErrorKind)>) {
+    let mut bytes = Vec::with_capacity(s.len() + 4*corruptions.len());
+    let mut errors = Vec::new();
+    // an InterruptedSequence that becomes TooFewBytes if nothing comes after it
+    let mut split_end = None;
+    let mut copied = 0;
+    for &(offset, corruption) in corruptions {
+        assert!(offset >= copied, "corruption offsets must be increasing");
+        assert!(s.is_char_boundary(offset), "offset {} is not at the start of a codepoint", offset);
+        let c = match s[offset..].chars().next() {
+            Some(c) => c,
+            None => panic!("offset {} is at the end of the string", offset),
+        };
+        bytes.extend_from_slice(&s.as_bytes()[copied..offset]);
+        copied = offset + c.len_utf8();
+        let start = bytes.len();
+        let codepoint = c as u32;
+        match corruption {
+            Utf8Corruption::Overlong => {
+                if codepoint < 0x800 {
+                    bytes.push(0xe0);
+                } else if codepoint < 0x1_00_00 {
+                    bytes.push(0xf0);
+                    bytes.push(0x80 | (codepoint >> 12) as u8);
+                } else {
+                    panic!("{:?} is too high to be made overlong", c);
+                }
+                bytes.push(0x80 | (codepoint >> 6 & 0x3f) as u8);
+                bytes.push(0x80 | (codepoint & 0x3f) as u8);
+                errors.push((start, Utf8ErrorKind::OverlongEncoding));
+            }
+            Utf8Corruption::Split => {
+                assert!(c.len_utf8() > 1, "{:?} is a single byte and cannot be split", c);
+                bytes.extend_from_slice(&s.as_bytes()[offset..copied-1]);
+                split_end = Some((errors.len(), bytes.len()));
+                errors.push((start, Utf8ErrorKind::InterruptedSequence));
+            }
+            Utf8Corruption::StrayContinuationByte => {
+                bytes.extend_from_slice(&s.as_bytes()[offset..copied]);
+                errors.push((bytes.len(), Utf8ErrorKind::UnexpectedContinuationByte));
+                bytes.push(0x80);
+            }
+            Utf8Corruption::EncodedSurrogate(surrogate) => {
+                assert!(surrogate & 0xf8_00 == 0xd8_00, "{:#x} is not a surrogate", surrogate);
+                bytes.extend_from_slice(&s.as_bytes()[offset..copied]);
+                errors.push((bytes.len(), Utf8ErrorKind::Utf16ReservedCodepoint));
+                bytes.push(0xed);
+                bytes.push(0x80 | (surrogate >> 6 & 0x3f) as u8);
+                bytes.push(0x80 | (surrogate & 0x3f) as u8);
+            }
+        }
+        // the decoders skip one byte after an error, and report the
+        // remaining continuation bytes of the sequence individually.
+        let first_error = errors.last().unwrap().0;
+        for continuation in first_error+1..bytes.len() {
+            errors.push((continuation, Utf8ErrorKind::UnexpectedContinuationByte));
+        }
+    }
+    bytes.extend_from_slice(&s.as_bytes()[copied..]);
+    if let Some((error, end)) = split_end {
+        if end == bytes.len() {
+            errors[error].1 = Utf8ErrorKind::TooFewBytes;
+        }
+    }
+    (bytes, errors)
+}
+
+/// Corrupt UTF-16 text and return the errors it will produce.
+///
+/// Each corruption is applied to the codepoint starting at the given unit offset
+/// in `units`, which must be valid UTF-16, and the offsets must be increasing.
+/// The returned errors are those that decoding the corrupted units with
+/// [`SliceExt::utf16char_indices()`](../trait.SliceExt.html#tymethod.utf16char_indices)
+/// produces, together with their offsets in the corrupted units.
+///
+/// # Panics
+///
+/// If an offset is not at the start of a codepoint, or not after the previous offset,
+/// or the corruption cannot be applied to the codepoint at it.
+///
+/// # Examples
+///
+/// ```
+/// use encode_unicode::corrupt::{corrupt_utf16, Utf16Corruption};
+/// use encode_unicode::error::Utf16PairError::*;
+///
+/// let units = "a😀b".encode_utf16().collect::<Vec<u16>>();
+/// let corruptions = [(0, Utf16Corruption::LoneTrailingSurrogate), (1, Utf16Corruption::Split)];
+/// let (units, errors) = corrupt_utf16(&units, &corruptions);
+/// assert_eq!(units, [0x61, 0xdc00, 0xd83d, 0x62]);
+/// assert_eq!(errors, [(1, UnexpectedTrailingSurrogate), (2, UnmatchedLeadingSurrogate)]);
+/// ```
+pub fn corrupt_utf16(units: &[u16],  corruptions: &[(usize, Utf16Corruption)])
+-> (Vec<u16>, Vec<(usize, Utf16PairError)>) {
+    let mut corrupted = Vec::with_capacity(units.len() + corruptions.len());
+    let mut errors = Vec::new();
+    // an UnmatchedLeadingSurrogate that becomes Incomplete if nothing comes after it
+    let mut unmatched_end = None;
+    let mut copied = 0;
+    for &(offset, corruption) in corruptions {
+        assert!(offset >= copied, "corruption offsets must be increasing");
+        let first = match units.get(offset) {
+            Some(&first) => first,
+            None => panic!("offset {} is at the end of the slice", offset),
+        };
+        let len = match first.utf16_needs_extra_unit() {
+            Ok(needs_extra) => 1 + needs_extra as usize,
+            Err(_) => panic!("offset {} is not at the start of a codepoint", offset),
+        };
+        corrupted.extend_from_slice(&units[copied..offset]);
+        copied = offset + len;
+        match corruption {
+            Utf16Corruption::LoneLeadingSurrogate => {
+                corrupted.extend_from_slice(&units[offset..copied]);
+                errors.push((corrupted.len(), Utf16PairError::UnmatchedLeadingSurrogate));
+                corrupted.push(0xd800);
+                unmatched_end = Some((errors.len()-1, corrupted.len()));
+            }
+            Utf16Corruption::LoneTrailingSurrogate => {
+                corrupted.extend_from_slice(&units[offset..copied]);
+                errors.push((corrupted.len(), Utf16PairError::UnexpectedTrailingSurrogate));
+                corrupted.push(0xdc00);
+            }
+            Utf16Corruption::Split => {
+                assert!(len == 2, "{:#x} is a single unit and cannot be split", first);
+                errors.push((corrupted.len(), Utf16PairError::UnmatchedLeadingSurrogate));
+                corrupted.push(first);
+                unmatched_end = Some((errors.len()-1, corrupted.len()));
+            }
+        }
+    }
+    corrupted.extend_from_slice(&units[copied..]);
+    if let Some((error, end)) = unmatched_end {
+        if end == corrupted.len() {
+            errors[error].1 = Utf16PairError::Incomplete;
+        }
+    }
+    (corrupted, errors)
+}
@@ -20,6 +20,10 @@ use crate::errors::Utf16PairError::*;
 use crate::errors::Utf8ErrorKind::*;
 use crate::utf8_char::Utf8Char;
 use crate::utf16_char::Utf16Char;
+use crate::utf8_validator::Utf8Validator;
+use crate::lengths::ascii_prefix_len;
+use crate::validation::validate_utf16;
+use crate::utf8_iterators::{CharPosition, ColumnUnit};
 use crate::traits::U16UtfExt;
 extern crate core;
 use core::borrow::Borrow;
@@ -94,35 +98,6 @@ impl<B:Borrow<u8>, I:Iterator<Item=B>> Utf8CharMerger<B,I> {
             self.after_err_leftover += 1;
         }
     }
-    /// Reads len-1 bytes into bytes[1..]
-    fn extra(&mut self,  bytes: &mut[u8;4],  len: usize) -> Result<(),Utf8Error> {
-        // This is the only function that pushes onto after_err_stack,
-        // and it checks that all bytes are continuation bytes before fetching the next one.
-        // Therefore only the last byte retrieved can be a non-continuation byte.
-        // That last byte is also the last to be retrieved from after_err.
-        //
-        // Before this function is called, there has been retrieved at least one byte.
-        // If that byte was a continuation byte, next() produces an error
-        // and won't call this function.
-        // Therefore, we know that after_err is empty at this point.
-        // This means that we can use self.iter directly, and knows where to start pushing
-        debug_assert_eq!(self.after_err_leftover, 0, "first: {:#02x}, stack: {:?}", bytes[0], self.after_err_stack);
-        for i in 1..len {
-            if let Some(extra) = self.iter.next() {
-                let extra = *extra.borrow();
-                bytes[i] = extra;
-                if extra & 0b1100_0000 != 0b1000_0000 {
-                    // not a continuation byte
-                    self.save(bytes, i+1);
-                    return Err(Utf8Error{ kind: InterruptedSequence })
-                }
-            } else {
-                self.save(bytes, i);
-                return Err(Utf8Error{ kind: TooFewBytes });
-            }
-        }
-        Ok(())
-    }
 }
 impl<B:Borrow<u8>, I:Iterator<Item=B>> Iterator for Utf8CharMerger<B,I> {
     type Item = Result<Utf8Char,Utf8Error>;
@@ -137,53 +112,39 @@ impl<B:Borrow<u8>, I:Iterator<Item=B>> Iterator for Utf8CharMerger<B,I> {
             return None;
         }
 
-        unsafe {
-            let mut bytes = [first, 0, 0, 0];
-            let ok = match first {
-                0b0000_0000..=0b0111_1111 => {/*1 and */Ok(())},
-                0b1100_0010..=0b1101_1111 => {//2 and not overlong
-                    self.extra(&mut bytes, 2) // no extra validation required
-                },
-                0b1110_0000..=0b1110_1111 => {//3
-                    if let Err(e) = self.extra(&mut bytes, 3) {
-                        Err(e)
-                    } else if bytes[0] == 0b1110_0000  &&  bytes[1] <= 0b10_011111 {
-                        self.save(&bytes, 3);
-                        Err(Utf8Error{ kind: OverlongEncoding })
-                    } else if bytes[0] == 0b1110_1101  &&  bytes[1] & 0b11_100000 == 0b10_100000 {
-                        self.save(&bytes, 3);
-                        Err(Utf8Error{ kind: Utf16ReservedCodepoint })
-                    } else {
-                        Ok(())
-                    }
-                },
-                0b1111_0000..=0b1111_0100 => {//4
-                    if let Err(e) = self.extra(&mut bytes, 4) {
-                        Err(e)
-                    } else if bytes[0] == 0b11110_000  &&  bytes[1] <= 0b10_001111 {
-                        self.save(&bytes, 4);
-                        Err(Utf8Error{ kind: OverlongEncoding })
-                    } else if bytes[0] == 0b11110_100  &&  bytes[1] > 0b10_001111 {
-                        self.save(&bytes, 4);
-                        Err(Utf8Error{ kind: TooHighCodepoint })
-                    } else {
-                        Ok(())
-                    }
-                },
-                0b1000_0000..=0b1011_1111 => {// continuation byte
-                    Err(Utf8Error{ kind: UnexpectedContinuationByte })
-                },
-                0b1100_0000..=0b1100_0001 => {// 2 and overlong
-                    Err(Utf8Error{ kind: NonUtf8Byte })
-                },
-                0b1111_0101..=0b1111_0111 => {// 4 and too high codepoint
-                    Err(Utf8Error{ kind: NonUtf8Byte })
-                },
-                0b1111_1000..=0b1111_1111 => {
-                    Err(Utf8Error{ kind: NonUtf8Byte })
+        if first.is_ascii() {
+            // SAFETY: just checked
+            return Some(Ok(unsafe { Utf8Char::from_ascii_unchecked(first) }));
+        }
+
+        // Only the last byte read can be a non-continuation byte,
+        // and it is the last to be popped from after_err_stack.
+        // Therefore the stack is empty when the first byte starts a sequence,
+        // and self.iter can be used directly.
+        let mut bytes = [first, 0, 0, 0];
+        let mut len = 1;
+        let mut validator = Utf8Validator::new().step(first);
+        while validator.is_in_sequence() {
+            debug_assert_eq!(self.after_err_leftover, 0, "first: {:#04x}, stack: {:?}", first, self.after_err_stack);
+            match self.iter.next() {
+                Some(extra) => {
+                    bytes[len] = *extra.borrow();
+                    validator = validator.step(bytes[len]);
+                    len += 1;
                 },
-            };
-            Some(ok.map(|()| Utf8Char::from_array_unchecked(bytes) ))
+                None => {
+                    self.save(&bytes, len);
+                    return Some(Err(Utf8Error{ kind: validator.incomplete_error() }));
+                }
+            }
+        }
+        match validator.error() {
+            Some(kind) => {
+                self.save(&bytes, len);
+                Some(Err(Utf8Error{ kind }))
+            },
+            // SAFETY: the validator accepted the sequence
+            None => Some(Ok(unsafe { Utf8Char::from_array_unchecked(bytes) })),
         }
     }
     fn size_hint(&self) -> (usize,Option<usize>) {
@@ -219,12 +180,17 @@ impl<B:Borrow<u8>, I:Iterator<Item=B>+Debug> Debug for Utf8CharMerger<B,I> {
 /// for examples and error handling.
 #[derive(Clone, Default)]
 pub struct Utf8CharDecoder<'a> {
+    /// The whole slice, because errors can depend on bytes which have
+    /// already been returned by `next_back()`.
     slice: &'a[u8],
     index: usize,
+    end: usize,
+    /// End of the run of ASCII that `index` is in, if it has been found.
+    ascii_end: usize,
 }
 impl<'a> From<&'a[u8]> for Utf8CharDecoder<'a> {
     fn from(s: &[u8]) -> Utf8CharDecoder {
-        Utf8CharDecoder { slice: s, index: 0 }
+        Utf8CharDecoder { slice: s, index: 0, end: s.len(), ascii_end: 0 }
     }
 }
 impl<'a> Utf8CharDecoder<'a> {
@@ -241,28 +207,40 @@ impl<'a> Utf8CharDecoder<'a> {
     /// assert_eq!(iter.as_slice(), b"\xa1\xb2FS");
     /// ```
     pub fn as_slice(&self) -> &'a[u8] {
-        &self.slice[self.index..]
+        &self.slice[self.index..self.end]
+    }
+    /// Decode the codepoint or error starting at `start`.
+    fn decode(&self,  start: usize) -> (usize, Result<Utf8Char,Utf8Error>, usize) {
+        match Utf8Char::from_slice_start(&self.slice[start..]) {
+            Ok((u8c, len)) => (start, Ok(u8c), len),
+            Err(e) => (start, Err(e), 1),
+        }
     }
 }
 impl<'a> Iterator for Utf8CharDecoder<'a> {
     type Item = (usize, Result<Utf8Char,Utf8Error>, usize);
     fn next(&mut self) -> Option<Self::Item> {
-        let start = self.index;
-        match Utf8Char::from_slice_start(&self.slice[self.index..]) {
-            Ok((u8c, len)) => {
-                self.index += len;
-                Some((start, Ok(u8c), len))
-            },
-            Err(_) if self.slice.len() <= self.index => None,
-            Err(e) => {
-                self.index += 1;
-                Some((start, Err(e), 1))
-            }
+        if self.index >= self.end {
+            return None;
         }
+        if self.index >= self.ascii_end  &&  self.slice[self.index].is_ascii() {
+            self.ascii_end = self.index + ascii_prefix_len(&self.slice[self.index..self.end]);
+        }
+        if self.index < self.ascii_end {
+            // SAFETY: checked by ascii_prefix_len()
+            let u8c = unsafe { Utf8Char::from_ascii_unchecked(self.slice[self.index]) };
+            self.index += 1;
+            return Some((self.index-1, Ok(u8c), 1));
+        }
+        // A codepoint that starts before self.end also ends before it,
+        // because next_back() only stops at the start of codepoints or errors.
+        let item = self.decode(self.index);
+        self.index += item.2;
+        Some(item)
     }
     #[inline]
     fn size_hint(&self) -> (usize,Option<usize>) {
-        let bytes = self.slice.len() - self.index;
+        let bytes = self.end - self.index;
         // Cannot be exact, so KISS and don't bother rounding up.
         // The slice is unlikely be full of 4-byte codepoints, so buffers
         // allocated with the lower bound will have to be grown anyway.
@@ -271,32 +249,31 @@ impl<'a> Iterator for Utf8CharDecoder<'a> {
 }
 impl<'a> DoubleEndedIterator for Utf8CharDecoder<'a> {
     fn next_back(&mut self) -> Option<Self::Item> {
-        if self.index < self.slice.len() {
-            let extras = self.slice.iter()
-                .rev()
-                .take_while(|&b| b & 0b1100_0000 == 0b1000_0000 )
-                .count();
-            let starts = self.slice.len() - (extras+1);
-            match Utf8Char::from_slice_start(&self.slice[starts..]) {
-                Ok((u8c,len)) if len == 1+extras => {
-                    self.slice = &self.slice[..starts];
-                    Some((starts, Ok(u8c), len))
-                },
-                // This enures errors for every byte in both directions,
-                // but means overlong and codepoint errors will be turned into
-                // tooshort errors.
-                Err(e) if extras == 0 => {
-                    self.slice = &self.slice[..self.slice.len()-1];
-                    Some((self.slice.len()-1, Err(e), 1))
-                },
-                _ => {
-                    self.slice = &self.slice[..self.slice.len()-1];
-                    Some((self.slice.len()-1, Err(Utf8Error{ kind: UnexpectedContinuationByte }), 1))
-                },
+        // Produces the same items as next() would, in reverse order:
+        // A non-continuation byte is never inside a codepoint that next()
+        // would produce, so if it starts a valid sequence that ends here,
+        // that's the item.
+        // If it doesn't, and it's the last byte, next() would report the
+        // same error for it, and otherwise the last byte is a lone
+        // continuation byte.
+        if self.index >= self.end {
+            return None;
+        }
+        let last = self.end - 1;
+        let earliest = self.index.max(self.end.saturating_sub(4));
+        let start = (earliest..=last).rev()
+            .find(|&i| self.slice[i] & 0b1100_0000 != 0b1000_0000 );
+        if let Some(start) = start {
+            let item = self.decode(start);
+            if (item.1.is_ok()  &&  start + item.2 == self.end)  ||  start == last {
+                self.end = start;
+                self.ascii_end = self.ascii_end.min(start);
+                return Some(item);
             }
-        } else {
-            None
         }
+        self.end = last;
+        self.ascii_end = self.ascii_end.min(last);
+        Some((last, Err(Utf8Error{ kind: UnexpectedContinuationByte }), 1))
     }
 }
 impl<'a> Debug for Utf8CharDecoder<'a> {
@@ -306,6 +283,82 @@ impl<'a> Debug for Utf8CharDecoder<'a> {
 }
 
 
+/// An [`Utf8CharDecoder`](struct.Utf8CharDecoder.html) that produces
+/// UTF-16 and codepoint offsets and line and column numbers in addition to
+/// byte offsets.
+///
+/// See [`SliceExt::utf8char_positions()`](../trait.SliceExt.html#tymethod.utf8char_positions)
+/// for examples and how errors are counted.
+#[derive(Clone)]
+pub struct Utf8CharPositionDecoder<'a> {
+    slice: &'a[u8],
+    next: CharPosition,
+    unit: ColumnUnit,
+}
+impl<'a> From<&'a[u8]> for Utf8CharPositionDecoder<'a> {
+    /// Creates an iterator which counts columns in codepoints.
+    fn from(s: &[u8]) -> Utf8CharPositionDecoder<'_> {
+        Utf8CharPositionDecoder {
+            slice: s,
+            next: CharPosition::default(),
+            unit: ColumnUnit::Codepoints,
+        }
+    }
+}
+impl<'a> Utf8CharPositionDecoder<'a> {
+    /// Change which unit columns are counted in.
+    ///
+    /// Changing the unit in the middle of a line only affects characters
+    /// after the current column.
+    pub fn with_column_unit(mut self,  unit: ColumnUnit) -> Self {
+        self.unit = unit;
+        self
+    }
+    /// Get the position of the next codepoint or error,
+    /// or of the end of the slice if there are none left.
+    pub fn position(&self) -> CharPosition {
+        self.next
+    }
+    /// Extract the remainder of the source slice.
+    pub fn as_slice(&self) -> &'a[u8] {
+        &self.slice[self.next.byte..]
+    }
+}
+impl<'a> Iterator for Utf8CharPositionDecoder<'a> {
+    type Item = (CharPosition, Result<Utf8Char,Utf8Error>, usize);
+    fn next(&mut self) -> Option<Self::Item> {
+        let position = self.next;
+        match Utf8Char::from_slice_start(self.as_slice()) {
+            Ok((u8c, len)) => {
+                let utf16_len = if len == 4 {2} else {1};
+                self.next.advance(len, utf16_len, u8c == b'\n', self.unit);
+                Some((position, Ok(u8c), len))
+            },
+            Err(_) if self.slice.len() <= self.next.byte => None,
+            Err(e) => {
+                // counted as one U+FFFD, except in bytes
+                self.next.advance(1, 1, false, self.unit);
+                Some((position, Err(e), 1))
+            }
+        }
+    }
+    #[inline]
+    fn size_hint(&self) -> (usize,Option<usize>) {
+        let bytes = self.slice.len() - self.next.byte;
+        (bytes/4, Some(bytes))
+    }
+}
+impl<'a> Debug for Utf8CharPositionDecoder<'a> {
+    fn fmt(&self,  fmtr: &mut fmt::Formatter) -> fmt::Result {
+        fmtr.debug_struct("Utf8CharPositionDecoder")
+            .field("position", &self.next)
+            .field("unit", &self.unit)
+            .field("remaining", &self.as_slice())
+            .finish()
+    }
+}
+
+
 
 /// Decodes UTF-16 characters from a `u16` iterator into `Utf16Char`s.
 ///
@@ -488,3 +541,130 @@ impl<'a> Debug for Utf16CharDecoder<'a> {
         write!(fmtr, "Utf16CharDecoder {{ units[{}..]: {:?} }}", self.index, self.as_slice())
     }
 }
+
+
+/// An iterator over the valid UTF-8 runs of a byte slice and the invalid
+/// sequences between them.
+///
+/// See [`SliceExt::utf8char_chunks()`](../trait.SliceExt.html#tymethod.utf8char_chunks)
+/// for examples and how invalid sequences are split.
+#[derive(Clone, Default)]
+pub struct Utf8Chunks<'a> {
+    remaining: &'a[u8],
+}
+impl<'a> From<&'a[u8]> for Utf8Chunks<'a> {
+    fn from(s: &'a[u8]) -> Self {
+        Utf8Chunks { remaining: s }
+    }
+}
+impl<'a> Utf8Chunks<'a> {
+    /// Extract the remainder of the source slice.
+    pub fn as_slice(&self) -> &'a[u8] {
+        self.remaining
+    }
+}
+impl<'a> Iterator for Utf8Chunks<'a> {
+    type Item = (&'a str, Option<(&'a[u8], Utf8Error)>);
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining.is_empty() {
+            return None;
+        }
+        let bytes = self.remaining;
+        // find where the first sequence that can't become valid starts
+        let mut start = ascii_prefix_len(bytes);
+        let mut end = start;
+        let mut validator = Utf8Validator::new();
+        while end < bytes.len() {
+            validator = validator.step(bytes[end]);
+            if !validator.can_become_valid() {
+                break;
+            }
+            end += 1;
+            if validator.is_at_boundary() {
+                start = end;
+            }
+        }
+        if start == bytes.len() {
+            self.remaining = &[];
+            // SAFETY: the validator accepted all of it
+            return Some((unsafe { core::str::from_utf8_unchecked(bytes) }, None));
+        }
+        // the invalid part is the longest start of a valid sequence,
+        // but at least one byte.
+        let invalid_len = if end == start {1} else {end - start};
+        // keep going to get the same error as utf8char_indices() produces
+        // for the first byte, which might only be reported once the sequence is complete
+        while end+1 < bytes.len()  &&  validator.is_in_sequence() {
+            end += 1;
+            validator = validator.step(bytes[end]);
+        }
+        let error = validator.finish().unwrap_err();
+        // SAFETY: the validator accepted everything before start
+        let valid = unsafe { core::str::from_utf8_unchecked(&bytes[..start]) };
+        let (invalid, rest) = bytes[start..].split_at(invalid_len);
+        self.remaining = rest;
+        Some((valid, Some((invalid, error))))
+    }
+    #[inline]
+    fn size_hint(&self) -> (usize,Option<usize>) {
+        let bytes = self.remaining.len();
+        ((bytes != 0) as usize, Some(bytes))
+    }
+}
+impl<'a> Debug for Utf8Chunks<'a> {
+    fn fmt(&self,  fmtr: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmtr, "Utf8Chunks {{ remaining: {:?} }}", self.remaining)
+    }
+}
+
+
+/// An iterator over the well-formed UTF-16 runs of a slice and the invalid
+/// units between them.
+///
+/// See [`SliceExt::utf16char_chunks()`](../trait.SliceExt.html#tymethod.utf16char_chunks)
+/// for examples.
+#[derive(Clone, Default)]
+pub struct Utf16Chunks<'a> {
+    remaining: &'a[u16],
+}
+impl<'a> From<&'a[u16]> for Utf16Chunks<'a> {
+    fn from(s: &'a[u16]) -> Self {
+        Utf16Chunks { remaining: s }
+    }
+}
+impl<'a> Utf16Chunks<'a> {
+    /// Extract the remainder of the source slice.
+    pub fn as_slice(&self) -> &'a[u16] {
+        self.remaining
+    }
+}
+impl<'a> Iterator for Utf16Chunks<'a> {
+    type Item = (&'a[u16], Option<(u16, Utf16PairError)>);
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining.is_empty() {
+            return None;
+        }
+        match validate_utf16(self.remaining) {
+            Ok(()) => {
+                let valid = self.remaining;
+                self.remaining = &[];
+                Some((valid, None))
+            },
+            Err(e) => {
+                let (valid, rest) = self.remaining.split_at(e.offset());
+                self.remaining = &rest[1..];
+                Some((valid, Some((rest[0], *e.error()))))
+            },
+        }
+    }
+    #[inline]
+    fn size_hint(&self) -> (usize,Option<usize>) {
+        let units = self.remaining.len();
+        ((units != 0) as usize, Some(units))
+    }
+}
+impl<'a> Debug for Utf16Chunks<'a> {
+    fn fmt(&self,  fmtr: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmtr, "Utf16Chunks {{ remaining: {:?} }}", self.remaining)
+    }
+}
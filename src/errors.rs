@@ -17,6 +17,12 @@ use core::fmt::{self,Display,Formatter};
 use core::ops::RangeInclusive;
 #[cfg(feature="std")]
 use std::error::Error;
+#[cfg(all(feature="core_error", not(feature="std")))]
+use core::error::Error;
+#[cfg(feature="serde")]
+use serde::{Serialize, Serializer};
+#[cfg(feature="serde")]
+use serde::ser::SerializeStruct;
 
 
 macro_rules! description {($err:ty, $desc:expr) => {
@@ -27,7 +33,7 @@ macro_rules! description {($err:ty, $desc:expr) => {
             ($desc)(self)
         }
     }
-    #[cfg(feature="std")]
+    #[cfg(any(feature="std", feature="core_error"))]
     impl Error for $err {
         fn description(&self) -> &'static str {
             ($desc)(self)
@@ -47,6 +53,13 @@ macro_rules! single_cause {($(#[$doc:meta])* $err:ident => $desc:expr) => {
     #[derive(Clone,Copy, Debug, PartialEq,Eq)]
     pub struct $err;
     description!{$err, |_| $desc }
+    #[cfg(feature="serde")]
+    /// Requires the feature "serde".
+    impl Serialize for $err {
+        fn serialize<S: Serializer>(&self,  serializer: S) -> Result<S::Ok, S::Error> {
+            serializer.serialize_unit_struct(stringify!($err))
+        }
+    }
 }}
 
 
@@ -75,6 +88,21 @@ single_cause!{
     EmptyStrError => "is empty"
 }
 
+single_cause!{
+    /// Error returned by [`TryExtend`](../trait.TryExtend.html) and the methods of
+    /// [`Utf8ArrayString`](../struct.Utf8ArrayString.html) and
+    /// [`Utf16ArrayString`](../struct.Utf16ArrayString.html)
+    /// when there is not enough room left for a character or string.
+    CapacityError => "not enough capacity"
+}
+
+single_cause!{
+    /// Error returned by the functions in the [`wide`](../wide/index.html) module
+    /// and the conversions to `widestring`'s C strings
+    /// when the text contains a NUL character, which would end it early in C.
+    InteriorNulError => "contains an interior NUL character"
+}
+
 
 
 macro_rules! simple {($(#[$tydoc:meta])* $err:ident {
@@ -86,6 +114,14 @@ macro_rules! simple {($(#[$tydoc:meta])* $err:ident {
         $( $(#[$vardoc])* $variant, )*
     }
     description!{$err, |e: &$err| match *e {$($err::$variant => $string),*} }
+    #[cfg(feature="serde")]
+    /// Requires the feature "serde".
+    impl Serialize for $err {
+        fn serialize<S: Serializer>(&self,  serializer: S) -> Result<S::Ok, S::Error> {
+            let variant = match *self {$($err::$variant => stringify!($variant)),*};
+            serializer.serialize_unit_variant(stringify!($err), *self as u32, variant)
+        }
+    }
 }}
 
 
@@ -171,6 +207,19 @@ simple!{
     }
 }
 
+simple!{
+    /// Error returned by [`Utf8Char::from_packed_u32()`](../struct.Utf8Char.html#method.from_packed_u32)
+    /// and [`Utf16Char::from_packed_u32()`](../struct.Utf16Char.html#method.from_packed_u32)
+    /// for values that `to_packed_u32()` never returns.
+    PackedCharError {
+        /// The value doesn't start with a valid UTF-8 or UTF-16 codepoint.
+        InvalidCodepoint => "doesn't start with a valid codepoint",
+        /// The codepoint is valid, but the unused bytes or unit is not zero,
+        /// or doesn't have the value `'\0'` is packed with.
+        InvalidPadding => "the bytes or unit after the codepoint are not zero",
+    }
+}
+
 
 
 /// Error returned when an invalid UTF-8 sequence is encountered.
@@ -193,12 +242,21 @@ impl Utf8Error {
         utf8_error_description(self.kind)
     }
 }
-#[cfg(feature="std")]
+#[cfg(any(feature="std", feature="core_error"))]
 impl Error for Utf8Error {
     fn description(&self) -> &'static str {
         utf8_error_description(self.kind)
     }
 }
+#[cfg(feature="serde")]
+/// Requires the feature "serde".
+impl Serialize for Utf8Error {
+    fn serialize<S: Serializer>(&self,  serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("Utf8Error", 1)?;
+        state.serialize_field("kind", &self.kind)?;
+        state.end()
+    }
+}
 impl Display for Utf8Error {
     fn fmt(&self,  fmtr: &mut Formatter) -> fmt::Result {
         fmtr.write_str(utf8_error_description(self.kind))
@@ -225,6 +283,8 @@ pub enum Utf8ErrorKind {
     ///
     /// This can happen when a slice is empty or too short, or an iterator
     /// returned `None` while in the middle of a codepoint.  
+    /// It is only returned if the bytes that are there could start a valid
+    /// codepoint; For example `b"\xe0\x80"` is reported as an overlong encoding.  
     /// This error is never produced by functions accepting fixed-size
     /// `[u8; 4]` arrays.
     ///
@@ -259,8 +319,10 @@ pub enum Utf8ErrorKind {
     /// The byte at index 1..=3 should be a continuation byte,
     /// but doesn't fit the pattern `0b10xx_xxxx`.
     ///
-    /// When the input slice or iterator has too few bytes,
-    /// [`TooFewBytes`](#Incomplete) is returned instead.
+    /// This error is returned as soon as such a byte is encountered,
+    /// even if the input slice or iterator then ends before the sequence would.
+    /// (while [`TooFewBytes`](#variant.TooFewBytes) is returned if all the bytes
+    /// that are present are continuation bytes.)
     InterruptedSequence,
     /// The encoding of the codepoint has so many leading zeroes that it
     /// could be a byte shorter.
@@ -286,6 +348,22 @@ pub enum Utf8ErrorKind {
     /// unicode permits.
     TooHighCodepoint,
 }
+#[cfg(feature="serde")]
+/// Requires the feature "serde".
+impl Serialize for Utf8ErrorKind {
+    fn serialize<S: Serializer>(&self,  serializer: S) -> Result<S::Ok, S::Error> {
+        let variant = match *self {
+            Utf8ErrorKind::TooFewBytes => "TooFewBytes",
+            Utf8ErrorKind::NonUtf8Byte => "NonUtf8Byte",
+            Utf8ErrorKind::UnexpectedContinuationByte => "UnexpectedContinuationByte",
+            Utf8ErrorKind::InterruptedSequence => "InterruptedSequence",
+            Utf8ErrorKind::OverlongEncoding => "OverlongEncoding",
+            Utf8ErrorKind::Utf16ReservedCodepoint => "Utf16ReservedCodepoint",
+            Utf8ErrorKind::TooHighCodepoint => "TooHighCodepoint",
+        };
+        serializer.serialize_unit_variant("Utf8ErrorKind", *self as u32, variant)
+    }
+}
 const fn utf8_error_description(kind: Utf8ErrorKind) -> &'static str {
     match kind {
         Utf8ErrorKind::TooFewBytes => "too few bytes",
@@ -307,3 +385,116 @@ impl PartialEq<Utf8Error> for Utf8ErrorKind {
         *self == error.kind
     }
 }
+
+
+
+/// An error together with the offset in the slice it was encountered at.
+///
+/// Returned by functions in the [`slice`](../slice/index.html) module that
+/// look at a whole slice, where knowing where the invalid sequence starts
+/// is as important as knowing what's wrong with it.
+#[derive(Clone,Copy, Debug, PartialEq,Eq)]
+pub struct PositionedError<E> {
+    pub(crate) offset: usize,
+    pub(crate) error: E,
+}
+impl<E> PositionedError<E> {
+    /// Get the index of the first byte or unit of the invalid sequence.
+    pub const fn offset(&self) -> usize {
+        self.offset
+    }
+    /// Get what's wrong with the sequence.
+    pub const fn error(&self) -> &E {
+        &self.error
+    }
+}
+#[cfg(any(feature="std", feature="core_error"))]
+impl<E: Error+'static> Error for PositionedError<E> {
+    fn description(&self) -> &str {
+        "invalid text"
+    }
+    fn source(&self) -> Option<&(dyn Error+'static)> {
+        Some(&self.error)
+    }
+}
+#[cfg(feature="serde")]
+/// Requires the feature "serde".
+impl<E: Serialize> Serialize for PositionedError<E> {
+    fn serialize<S: Serializer>(&self,  serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("PositionedError", 2)?;
+        state.serialize_field("offset", &self.offset)?;
+        state.serialize_field("error", &self.error)?;
+        state.end()
+    }
+}
+/// Only mentions the offset; the wrapped error is available through `source()`
+/// or [`error()`](#method.error).
+impl<E> Display for PositionedError<E> {
+    fn fmt(&self,  fmtr: &mut Formatter) -> fmt::Result {
+        write!(fmtr, "invalid text at offset {}", self.offset)
+    }
+}
+
+
+
+/// Reason why [`transcode_utf8_to_utf16()`](../slice/fn.transcode_utf8_to_utf16.html)
+/// or [`transcode_utf16_to_utf8()`](../slice/fn.transcode_utf16_to_utf8.html)
+/// stopped before the end of the source.
+///
+/// In both cases the next codepoint starts at the returned number of
+/// elements read, and can be retried from there.
+#[derive(Clone,Copy, Debug, PartialEq,Eq)]
+pub enum TranscodeError<E> {
+    /// The next sequence in the source is invalid,
+    /// or the source ends in the middle of a codepoint.
+    Invalid(E),
+    /// The destination doesn't have room for the next codepoint.
+    OutputFull {
+        /// How many elements of the destination the next codepoint needs.
+        needed: usize,
+    },
+}
+#[cfg(any(feature="std", feature="core_error"))]
+impl<E: Error+'static> Error for TranscodeError<E> {
+    fn description(&self) -> &str {
+        match *self {
+            TranscodeError::Invalid(_) => "invalid text",
+            TranscodeError::OutputFull{..} => "the output buffer is full",
+        }
+    }
+    fn source(&self) -> Option<&(dyn Error+'static)> {
+        match *self {
+            TranscodeError::Invalid(ref error) => Some(error),
+            TranscodeError::OutputFull{..} => None,
+        }
+    }
+}
+#[cfg(feature="serde")]
+/// Requires the feature "serde".
+impl<E: Serialize> Serialize for TranscodeError<E> {
+    fn serialize<S: Serializer>(&self,  serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStructVariant;
+        match *self {
+            TranscodeError::Invalid(ref error) => {
+                serializer.serialize_newtype_variant("TranscodeError", 0, "Invalid", error)
+            }
+            TranscodeError::OutputFull{needed} => {
+                let mut state = serializer.serialize_struct_variant("TranscodeError", 1, "OutputFull", 1)?;
+                state.serialize_field("needed", &needed)?;
+                state.end()
+            }
+        }
+    }
+}
+/// For `Invalid` the wrapped error is only available through `source()`
+/// or by matching.
+impl<E> Display for TranscodeError<E> {
+    fn fmt(&self,  fmtr: &mut Formatter) -> fmt::Result {
+        match *self {
+            TranscodeError::Invalid(_) => write!(fmtr, "invalid text"),
+            TranscodeError::OutputFull{needed} => {
+                write!(fmtr, "the output buffer doesn't have room for {} more elements", needed)
+            }
+        }
+    }
+}
@@ -0,0 +1,289 @@
+/* Copyright 2022 Torbjørn Birch Moltu
+ *
+ * Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+ * http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+ * http://opensource.org/licenses/MIT>, at your option. This file may not be
+ * copied, modified, or distributed except according to those terms.
+ */
+
+//! Computing the length of text in another encoding, and counting codepoints.
+//!
+//! The counting is done a `usize` at a time (SWAR):
+//! Each byte or `u16` of a word is a lane, and the lanes are tested with
+//! bitwise operations and summed with additions that never carry into the
+//! next lane. The lanes are only added together after a block of words.
+
+use crate::errors::{PositionedError, Utf8Error, Utf16PairError};
+use crate::validation::validate_utf16;
+use crate::traits::CharExt;
+extern crate core;
+use core::mem::size_of;
+
+const WORD: usize = size_of::<usize>();
+/// Number of words summed into lanes before the lanes are added together.
+/// Each lane of a sum can at most become 255 times two, so it never overflows.
+const WORDS_PER_SUM: usize = 255;
+/// `0x0101...01`
+const ONES_U8: usize = usize::MAX / 0xff;
+/// `0x0001...0001`
+const ONES_U16: usize = usize::MAX / 0xffff;
+
+#[inline]
+fn read_word(bytes: &[u8]) -> usize {
+    let mut word = [0; WORD];
+    word.copy_from_slice(bytes);
+    usize::from_ne_bytes(word)
+}
+
+/// Add together the byte lanes of `lanes`.
+#[inline]
+fn sum_u8_lanes(lanes: usize) -> usize {
+    let u16_lanes = (lanes & (ONES_U16 * 0xff))  +  ((lanes >> 8) & (ONES_U16 * 0xff));
+    sum_u16_lanes(u16_lanes)
+}
+
+/// Add together the `u16` lanes of `lanes`, which must sum to less than 2^16.
+#[inline]
+fn sum_u16_lanes(lanes: usize) -> usize {
+    lanes.wrapping_mul(ONES_U16) >> (usize::BITS - 16)
+}
+
+/// Set the top bit of every `u16` lane that is not zero, and clear all other bits.
+#[inline]
+pub(crate) fn nonzero_u16_lanes(lanes: usize) -> usize {
+    const LOW: usize = ONES_U16 * 0x7fff;
+    (((lanes & LOW) + LOW) | lanes)  &  !LOW
+}
+
+/// Get the number of ASCII bytes at the start of `bytes`.
+pub(crate) fn ascii_prefix_len(bytes: &[u8]) -> usize {
+    const HIGH: usize = ONES_U8 * 0x80;
+    let mut words = bytes.chunks_exact(WORD);
+    let mut ascii = 0;
+    for word in &mut words {
+        let mut le = [0; WORD];
+        le.copy_from_slice(word);
+        // in little-endian order the first non-ASCII byte has the lowest set bit
+        let non_ascii = usize::from_le_bytes(le) & HIGH;
+        if non_ascii != 0 {
+            return ascii + non_ascii.trailing_zeros() as usize / 8;
+        }
+        ascii += WORD;
+    }
+    ascii + words.remainder().iter().take_while(|b| b.is_ascii() ).count()
+}
+
+/// Count bytes that start a codepoint and bytes that start four-byte sequences.
+fn count_utf8_starts(bytes: &[u8]) -> (usize, usize) {
+    const HIGH: usize = ONES_U8 * 0x80;
+    let (mut continuation, mut four_byte_starts) = (0, 0);
+    let mut blocks = bytes.chunks_exact(WORD*WORDS_PER_SUM);
+    for block in &mut blocks {
+        let (mut continuation_lanes, mut four_byte_lanes) = (0, 0);
+        for word in block.chunks_exact(WORD) {
+            let word = read_word(word);
+            // shifting left moves lower bits of a byte to its top bit
+            continuation_lanes += (word & !(word << 1) & HIGH) >> 7;
+            four_byte_lanes += (word & (word << 1) & (word << 2) & (word << 3) & HIGH) >> 7;
+        }
+        continuation += sum_u8_lanes(continuation_lanes);
+        four_byte_starts += sum_u8_lanes(four_byte_lanes);
+    }
+    for &b in blocks.remainder() {
+        continuation += (b & 0xc0 == 0x80) as usize;
+        four_byte_starts += (b >= 0xf0) as usize;
+    }
+    (bytes.len() - continuation, four_byte_starts)
+}
+
+/// Count the number of extra bytes the units need as UTF-8,
+/// and the number of surrogates.
+fn count_utf16_extra(units: &[u16]) -> (usize, usize) {
+    const UNITS_PER_WORD: usize = WORD / 2;
+    let (mut extra, mut surrogates) = (0, 0);
+    let mut blocks = units.chunks_exact(UNITS_PER_WORD*WORDS_PER_SUM);
+    for block in &mut blocks {
+        let (mut extra_lanes, mut surrogate_lanes) = (0, 0);
+        for word in block.chunks_exact(UNITS_PER_WORD) {
+            let mut lanes = 0;
+            for &unit in word.iter().rev() {
+                lanes = (lanes << 16) | unit as usize;
+            }
+            let not_ascii = nonzero_u16_lanes(lanes & (ONES_U16 * 0xff80));
+            let three_bytes = nonzero_u16_lanes(lanes & (ONES_U16 * 0xf800));
+            let surrogate = !nonzero_u16_lanes((lanes & (ONES_U16 * 0xf800)) ^ (ONES_U16 * 0xd800));
+            extra_lanes += (not_ascii >> 15) + (three_bytes >> 15);
+            surrogate_lanes += (surrogate & (ONES_U16 * 0x8000)) >> 15;
+        }
+        extra += sum_u16_lanes(extra_lanes);
+        surrogates += sum_u16_lanes(surrogate_lanes);
+    }
+    for &u in blocks.remainder() {
+        extra += (u >= 0x80) as usize + (u >= 0x800) as usize;
+        surrogates += (u & 0xf800 == 0xd800) as usize;
+    }
+    (extra, surrogates)
+}
+
+/// Number of bytes validated at a time by `count_checked_utf8()`,
+/// small enough that they're still in cache when counted.
+const VALIDATION_BLOCK: usize = 4096;
+
+/// Validate and count in blocks, so that the input is only read from memory once.
+fn count_checked_utf8(bytes: &[u8]) -> Result<(usize, usize), PositionedError<Utf8Error>> {
+    let (mut starts, mut four_byte_starts) = (0, 0);
+    let mut offset = 0;
+    while offset < bytes.len() {
+        let end = bytes.len().min(offset + VALIDATION_BLOCK);
+        let valid = match core::str::from_utf8(&bytes[offset..end]) {
+            Ok(_) => end - offset,
+            Err(e) => e.valid_up_to(),
+        };
+        let (block_starts, block_four_byte_starts) = count_utf8_starts(&bytes[offset..offset+valid]);
+        starts += block_starts;
+        four_byte_starts += block_four_byte_starts;
+        offset += valid;
+        if offset < end {
+            // Either a codepoint crosses the end of the block, or there's an error.
+            // Decode it to get the length or the type of error.
+            match char::from_utf8_slice_start(&bytes[offset..]) {
+                Ok((_, len)) => {
+                    starts += 1;
+                    four_byte_starts += (len == 4) as usize;
+                    offset += len;
+                },
+                Err(error) => return Err(PositionedError { offset,  error }),
+            }
+        }
+    }
+    Ok((starts, four_byte_starts))
+}
+
+
+/// Get the number of codepoints in a `str`.
+///
+/// This is the same as `s.chars().count()`.
+///
+/// # Examples
+///
+/// ```
+/// use encode_unicode::slice::codepoint_count;
+///
+/// assert_eq!(codepoint_count("æøå😀"), 4);
+/// ```
+pub fn codepoint_count(s: &str) -> usize {
+    count_utf8_starts(s.as_bytes()).0
+}
+
+/// Get the number of units a `str` would need if encoded as UTF-16.
+///
+/// This is the same as `s.encode_utf16().count()`, but faster.
+///
+/// # Examples
+///
+/// ```
+/// use encode_unicode::slice::utf16_len;
+///
+/// assert_eq!(utf16_len("æøå😀"), 5);
+/// assert_eq!(utf16_len(""), 0);
+/// ```
+pub fn utf16_len(s: &str) -> usize {
+    let (starts, four_byte_starts) = count_utf8_starts(s.as_bytes());
+    starts + four_byte_starts
+}
+
+/// Get the number of codepoints in a slice that should be UTF-8.
+///
+/// # Errors
+///
+/// Returns the position and type of the first invalid sequence.
+///
+/// # Examples
+///
+/// ```
+/// use encode_unicode::slice::utf8_codepoint_count;
+/// use encode_unicode::error::Utf8ErrorKind;
+///
+/// assert_eq!(utf8_codepoint_count("æøå😀".as_bytes()), Ok(4));
+/// let error = utf8_codepoint_count(b"ab\xe0\x80\x80").unwrap_err();
+/// assert_eq!(error.offset(), 2);
+/// assert_eq!(error.error().kind(), Utf8ErrorKind::OverlongEncoding);
+/// ```
+pub fn utf8_codepoint_count(bytes: &[u8]) -> Result<usize, PositionedError<Utf8Error>> {
+    count_checked_utf8(bytes).map(|(starts, _)| starts )
+}
+
+/// Get the number of units a slice that should be UTF-8 would need if
+/// encoded as UTF-16.
+///
+/// # Errors
+///
+/// Returns the position and type of the first invalid sequence.
+///
+/// # Examples
+///
+/// ```
+/// use encode_unicode::slice::utf16_len_of_utf8;
+/// use encode_unicode::error::Utf8ErrorKind;
+///
+/// assert_eq!(utf16_len_of_utf8("æøå😀".as_bytes()), Ok(5));
+/// let error = utf16_len_of_utf8(b"ab\xf0\x9f\x98").unwrap_err();
+/// assert_eq!(error.offset(), 2);
+/// assert_eq!(error.error().kind(), Utf8ErrorKind::TooFewBytes);
+/// ```
+pub fn utf16_len_of_utf8(bytes: &[u8]) -> Result<usize, PositionedError<Utf8Error>> {
+    count_checked_utf8(bytes).map(|(starts, four_byte_starts)| starts + four_byte_starts )
+}
+
+/// Get the number of bytes a slice of UTF-16 would need if encoded as UTF-8.
+///
+/// # Errors
+///
+/// Returns the position and type of the first unpaired surrogate.
+///
+/// # Examples
+///
+/// ```
+/// use encode_unicode::slice::utf8_len;
+/// use encode_unicode::error::Utf16PairError;
+///
+/// let units = "aæ€😀".encode_utf16().collect::<Vec<u16>>();
+/// assert_eq!(utf8_len(&units), Ok(1+2+3+4));
+/// let error = utf8_len(&[0x41, 0xd83d, 0x41]).unwrap_err();
+/// assert_eq!(error.offset(), 1);
+/// assert_eq!(*error.error(), Utf16PairError::UnmatchedLeadingSurrogate);
+/// ```
+pub fn utf8_len(units: &[u16]) -> Result<usize, PositionedError<Utf16PairError>> {
+    let (extra, surrogates) = count_utf16_extra(units);
+    if surrogates != 0 {
+        validate_utf16(units)?;
+    }
+    // Surrogates have been counted as needing three bytes each,
+    // but a pair needs four.
+    Ok(units.len() + extra - surrogates)
+}
+
+/// Get the number of codepoints in a slice of UTF-16.
+///
+/// # Errors
+///
+/// Returns the position and type of the first unpaired surrogate.
+///
+/// # Examples
+///
+/// ```
+/// use encode_unicode::slice::utf16_codepoint_count;
+/// use encode_unicode::error::Utf16PairError;
+///
+/// let units = "aæ€😀".encode_utf16().collect::<Vec<u16>>();
+/// assert_eq!(utf16_codepoint_count(&units), Ok(4));
+/// let error = utf16_codepoint_count(&units[..4]).unwrap_err();
+/// assert_eq!((error.offset(), *error.error()), (3, Utf16PairError::Incomplete));
+/// ```
+pub fn utf16_codepoint_count(units: &[u16]) -> Result<usize, PositionedError<Utf16PairError>> {
+    let (_, surrogates) = count_utf16_extra(units);
+    if surrogates != 0 {
+        validate_utf16(units)?;
+    }
+    Ok(units.len() - surrogates/2)
+}
@@ -13,15 +13,81 @@ Miscellaneous UTF-8 and UTF-16 types and methods.
 
 # Optional features:
 * `#![no_std]`-mode: There are a few differences:
-  * `Error` doesn't exist, but `description()` is made available as an inherent impl.
-  * `Extend`/`FromIterator`-implementations for `String`/`Vec<u8>`/`Vec<u16>` are missing.
+  * `Error` isn't implemented unless the `core_error` feature is enabled,
+    but `description()` is made available as an inherent impl.
+  * `Extend`/`FromIterator`-implementations for `String`/`Vec<u8>`/`Vec<u16>` are missing
+    unless the `alloc` feature is enabled.
   * There is no `io`, so `Utf8Iterator` and `Utf8CharSplitter` doesn't implement `Read`.
 
   This feature is enabled by setting `default-features=false` in `Cargo.toml`:
   `encode_unicode = {version="0.3.4", default-features=false}`
+* `alloc`: Implement `Extend` and `FromIterator` for `String`, `Vec<u8>` and `Vec<u16>`
+  without requiring `std`, for targets that have an allocator,
+  and add the [`corrupt`](corrupt/index.html) module.  
+  It is enabled by the default `std` feature:
+  `encode_unicode = {version="1.1.0", default-features=false, features=["alloc"]}`
+* `core_error`: Implement `core::error::Error` for the error types without requiring `std`.  
+  This requires Rust 1.81, and has no effect when `std` is enabled.
+* `neon`: Use NEON instructions in [`slice::validate_utf16()`](slice/fn.validate_utf16.html) on aarch64,
+  detected at runtime if `std` is also enabled.  
+  This requires Rust 1.61, and has no effect on other architectures.
 * Integration with the [ascii](https://tomprogrammer.github.io/rust-ascii/ascii/index.html) crate:  
   Convert `Utf8Char` and `Utf16Char` to and from
   [`ascii::AsciiChar`](https://tomprogrammer.github.io/rust-ascii/ascii/enum.AsciiChar.html).
+* `serde`: Implement `Serialize` and `Deserialize` for `Utf8Char` and `Utf16Char`,
+  and `Serialize` for the error types.  
+  The characters are strings in human-readable formats, and UTF-8 bytes or
+  a sequence of UTF-16 units in binary formats.
+* `arbitrary`: Implement [`Arbitrary`](https://docs.rs/arbitrary/1/arbitrary/trait.Arbitrary.html)
+  for `Utf8Char` and `Utf16Char`, for use in fuzz targets.
+* `proptest`: Implement proptest's `Arbitrary` for `Utf8Char` and `Utf16Char`,
+  and add the [`strategies`](strategies/index.html) module with strategies for valid
+  UTF-8 and UTF-16, and for invalid sequences that produce a chosen error.
+* `nom`: Add the [`nom_parsers`](nom_parsers/index.html) module with streaming
+  [nom](https://docs.rs/nom/) parsers for UTF-8 and UTF-16 characters and strings in byte slices.  
+  Both nom 7 and 8 are supported, but nom 8 requires Rust 1.65.
+* `tokio-codec`: Add the [`codec`](codec/index.html) module with
+  [tokio-util](https://docs.rs/tokio-util/0.7/tokio_util/codec/index.html) codecs that decode
+  UTF-8 streams into `Utf8Char`s or into `String`s split at codepoint boundaries.
+* `tokio-io` and `futures`: Add the [`async_io`](async_io/index.html) module with readers that
+  validate UTF-8 or transcode between UTF-8 and UTF-16, and implement `AsyncRead` and `AsyncBufRead`
+  from tokio or [futures-io](https://docs.rs/futures-io/0.3/futures_io/).
+* `bytes`: Add the [`BufExt`](trait.BufExt.html) and [`BufMutExt`](trait.BufMutExt.html)
+  extension traits for reading and writing UTF-8 and UTF-16 codepoints with
+  [bytes](https://docs.rs/bytes/1/bytes/)' `Buf` and `BufMut`, including codepoints
+  split between the chunks of a `Buf`.
+* `rayon`: Add the [`parallel`](parallel/index.html) module, which validates and transcodes
+  large slices in parallel with [rayon](https://docs.rs/rayon/1/rayon/), with the same
+  results as doing it sequentially.
+* `widestring`: Add the [`wide`](wide/index.html) module, which encodes and decodes
+  NUL-terminated UTF-16 or UTF-32 for C functions taking `wchar_t` strings,
+  and implement conversions between `Utf16Char` and
+  [widestring](https://docs.rs/widestring/1/widestring/)'s `Utf16String`, `U16String`, `U16CString` and `U32CString`.
+  This requires Rust 1.58.
+* `heapless` and `arrayvec`: Implement [`TryExtend`](trait.TryExtend.html) for
+  [heapless](https://docs.rs/heapless/)' `String<N>`, `Vec<u8, N>` and `Vec<u16, N>` and for
+  [arrayvec](https://docs.rs/arrayvec/0.7/arrayvec/)'s `ArrayString<N>`, `ArrayVec<u8, N>` and `ArrayVec<u16, N>`,
+  for filling them from iterators of `Utf8Char` or `Utf16Char` without panicking when full.  
+  heapless 0.8 and 0.9 are supported, and `heapless` requires Rust 1.60.
+  ([`Utf8ArrayString`](struct.Utf8ArrayString.html) and [`Utf16ArrayString`](struct.Utf16ArrayString.html)
+  provide the same without any dependency.)
+* `memchr`: Use [memchr](https://docs.rs/memchr/2/memchr/) to scan for the needle in
+  [`slice::find_utf8char()`](slice/fn.find_utf8char.html) and the other search functions,
+  which makes them use SIMD where available.
+* `bytemuck`: Implement [`NoUninit`](https://docs.rs/bytemuck/1/bytemuck/trait.NoUninit.html),
+  [`CheckedBitPattern`](https://docs.rs/bytemuck/1/bytemuck/trait.CheckedBitPattern.html)
+  and [`ZeroableInOption`](https://docs.rs/bytemuck/1/bytemuck/trait.ZeroableInOption.html)
+  for `Utf8Char` and `Utf16Char`, so that slices of them can be cast to and from bytes or units.
+  Casting from bytes or units goes through
+  [`bytemuck::checked`](https://docs.rs/bytemuck/1/bytemuck/checked/index.html), which validates them.  
+  `Zeroable` is only implemented for `Option<Utf8Char>` and `Option<Utf16Char>`,
+  as all zeros is what represents `None`.
+* `zerocopy`: Implement `IntoBytes`, `Immutable` and `KnownLayout` from
+  [zerocopy](https://docs.rs/zerocopy/0.8/zerocopy/) for `Utf8Char` and `Utf16Char`,
+  so they can be cast to bytes.  
+  `TryFromBytes` (and therefore `FromZeros`) is not implemented, because zerocopy
+  only allows deriving it, and the derive can't check that the bytes are valid UTF-8.
+  Use the `bytemuck` feature to cast in that direction.
 
 # Minimum supported Rust version
 
@@ -32,6 +98,10 @@ For example this means that if the current stable Rust version is 1.66 when
 `encode_unicode` 1.1.0 is released, then `encode_unicode` 1.1.\* will
 not require a newer Rust version than 1.63.
 
+Some optional features require a newer Rust version, which is noted in their description below.
+The others work with the oldest supported minor version of each dependency,
+but newer versions of those might require a newer Rust version.
+
 [crates.io page](https://crates.io/crates/encode_unicode)  
 [github repository](https://github.com/tormol/encode_unicode)
 
@@ -59,31 +129,88 @@ not require a newer Rust version than 1.63.
 // opt-in lints that might be interesting to recheck once in a while:
 //#![warn(clippy::unwrap_used)]
 
+#[cfg(any(feature="alloc", feature="proptest", feature="rayon", feature="widestring"))]
+extern crate alloc;
+
 mod errors;
 mod traits;
 mod utf8_char;
+mod utf8_validator;
 mod utf8_iterators;
 mod utf16_char;
 mod utf16_iterators;
 mod decoding_iterators;
+mod boundaries;
+mod truncate;
+mod lengths;
+mod transcoding;
+mod validation;
+mod search;
+mod array_strings;
+mod macros;
+#[cfg(feature="alloc")]
+pub mod corrupt;
+#[cfg(feature="proptest")]
+pub mod strategies;
+#[cfg(feature="nom")]
+pub mod nom_parsers;
+#[cfg(feature="tokio-codec")]
+pub mod codec;
+#[cfg(any(feature="tokio-io", feature="futures"))]
+pub mod async_io;
+#[cfg(feature="bytes")]
+mod bytes_buf;
+#[cfg(feature="rayon")]
+pub mod parallel;
+#[cfg(feature="widestring")]
+pub mod wide;
 
 pub use traits::{CharExt, U8UtfExt, U16UtfExt, StrExt, IterExt, SliceExt};
+pub use boundaries::CharBoundaryExt;
 pub use utf8_char::Utf8Char;
 pub use utf16_char::Utf16Char;
+pub use utf8_validator::Utf8Validator;
+pub use array_strings::{TryExtend, Utf8ArrayString, Utf16ArrayString};
+#[cfg(feature="bytes")]
+pub use bytes_buf::{BufExt, BufMutExt};
+#[doc(hidden)]// used by macros
+pub use macros::{utf16_macro_len as __utf16_macro_len, utf16_macro_encode as __utf16_macro_encode};
+#[doc(hidden)]// used by macros
+pub use macros::{utf8char_macro as __utf8char_macro, utf16char_macro as __utf16char_macro};
 
 pub mod error {// keeping the public interface in one file
     //! Errors returned by various conversion methods in this crate.
-    pub use crate::errors::{FromStrError, EmptyStrError};
+    pub use crate::errors::{FromStrError, EmptyStrError, PackedCharError};
     pub use crate::errors::{CodepointError, NonAsciiError, NonBmpError};
     pub use crate::errors::{Utf8Error, Utf8ErrorKind};
     pub use crate::errors::{Utf16SliceError, Utf16ArrayError, Utf16TupleError};
     pub use crate::errors::{Utf16FirstUnitError, Utf16PairError};
+    pub use crate::errors::{PositionedError, TranscodeError};
+    pub use crate::errors::{CapacityError, InteriorNulError};
 }
 
 pub mod iterator {
     //! Iterator types that you should rarely need to name
     pub use crate::utf8_iterators::{Utf8Iterator, Utf8CharSplitter, Utf8Chars, Utf8CharIndices};
+    pub use crate::utf8_iterators::{Utf8CharPositions, CharPosition, ColumnUnit};
+    pub use crate::utf8_iterators::{AsciiRuns, AsciiRunOrChar};
     pub use crate::utf16_iterators::{Utf16Iterator, Utf16CharSplitter, Utf16Chars, Utf16CharIndices};
-    pub use crate::decoding_iterators::{Utf8CharMerger, Utf8CharDecoder};
+    pub use crate::decoding_iterators::{Utf8CharMerger, Utf8CharDecoder, Utf8CharPositionDecoder};
     pub use crate::decoding_iterators::{Utf16CharMerger, Utf16CharDecoder};
+    pub use crate::decoding_iterators::{Utf8Chunks, Utf16Chunks};
+    pub use crate::boundaries::CharBoundaryChunks;
+    pub use crate::search::{SplitOnUtf8Char, SplitOnUtf16Char};
+}
+
+pub mod slice {
+    //! Functions that operate on whole `str`s, `[u8]`s or `[u16]`s.
+    pub use crate::truncate::{truncate_str, truncate_str_to_utf16_len};
+    pub use crate::truncate::{truncate_utf8, truncate_utf8_to_utf16_len};
+    pub use crate::truncate::{truncate_utf16, truncate_utf16_to_utf8_len};
+    pub use crate::lengths::{codepoint_count, utf8_codepoint_count, utf16_codepoint_count};
+    pub use crate::lengths::{utf16_len, utf16_len_of_utf8, utf8_len};
+    pub use crate::transcoding::{transcode_utf8_to_utf16, transcode_utf16_to_utf8};
+    pub use crate::validation::validate_utf16;
+    pub use crate::search::{find_utf8char, rfind_utf8char, count_utf8char, split_on_utf8char};
+    pub use crate::search::{find_utf16char, rfind_utf16char, count_utf16char, split_on_utf16char};
 }
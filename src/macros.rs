@@ -0,0 +1,205 @@
+/* Copyright 2022 Torbjørn Birch Moltu
+ *
+ * Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+ * http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+ * http://opensource.org/licenses/MIT>, at your option. This file may not be
+ * copied, modified, or distributed except according to those terms.
+ */
+
+//! Macros for creating constants, and the `const fn`s they expand to.
+//!
+//! The functions are public because the macros are expanded in other crates,
+//! but they are hidden from the documentation and not part of the API.
+//!
+//! Compile-time errors are produced by indexing out of bounds,
+//! because panicking in `const fn`s requires Rust 1.57.
+
+use crate::utf8_char::Utf8Char;
+use crate::utf16_char::Utf16Char;
+use crate::errors::EmptyStrError;
+use core::str;
+
+/// Skip the first `bytes` bytes of a `str`, which must end at a codepoint boundary.
+///
+/// Slicing with ranges is not possible in `const fn`s, but slice patterns are.
+const fn skip(s: &str,  bytes: usize) -> &str {
+    let mut rest = s.as_bytes();
+    let mut bytes = bytes;
+    while bytes > 0 {
+        if let [_, after @ ..] = rest {
+            rest = after;
+        }
+        bytes -= 1;
+    }
+    unsafe { str::from_utf8_unchecked(rest) }
+}
+
+/// The number of units `utf16!()` or `utf16z!()` encodes the string as.
+#[doc(hidden)]
+pub const fn utf16_macro_len(s: &str,  nul_terminated: bool) -> usize {
+    let mut rest = s;
+    let mut len = 0;
+    while let Ok((u16c, utf8_len)) = Utf16Char::from_str_start(rest) {
+        let is_nul = u16c.to_array()[0] == 0;
+        len += [/* utf16z!() strings cannot contain '\0' */u16c.len()][(nul_terminated && is_nul) as usize];
+        rest = skip(rest, utf8_len);
+    }
+    len + nul_terminated as usize
+}
+
+/// Encode the string as UTF-16 into an array of a length returned by
+/// `utf16_macro_len()`.
+///
+/// Any units after the string are left zero.
+#[doc(hidden)]
+pub const fn utf16_macro_encode<const N: usize>(s: &str) -> [u16; N] {
+    let mut units = [0; N];
+    let mut rest = s;
+    let mut i = 0;
+    while let Ok((u16c, utf8_len)) = Utf16Char::from_str_start(rest) {
+        let array = u16c.to_array();
+        units[i] = array[0];
+        if u16c.len() == 2 {
+            units[i+1] = array[1];
+        }
+        i += u16c.len();
+        rest = skip(rest, utf8_len);
+    }
+    units
+}
+
+/// The `Utf8Char` of a string that must be exactly one codepoint.
+#[doc(hidden)]
+pub const fn utf8char_macro(s: &str) -> Utf8Char {
+    match Utf8Char::from_str_start(s) {
+        Ok((u8c, len)) => [/* utf8char!() string contains more than one codepoint */u8c][(len != s.len()) as usize],
+        Err(EmptyStrError) => [/* utf8char!() string is empty */][s.len()],
+    }
+}
+
+/// The `Utf16Char` of a string that must be exactly one codepoint.
+#[doc(hidden)]
+pub const fn utf16char_macro(s: &str) -> Utf16Char {
+    match Utf16Char::from_str_start(s) {
+        Ok((u16c, len)) => [/* utf16char!() string contains more than one codepoint */u16c][(len != s.len()) as usize],
+        Err(EmptyStrError) => [/* utf16char!() string is empty */][s.len()],
+    }
+}
+
+/// Encode a string as UTF-16 at compile time.
+///
+/// The string can be a literal or any other constant `&str` expression,
+/// and the result is a `&'static [u16; N]`.
+///
+/// See [`utf16z!()`](macro.utf16z.html) for a NUL-terminated version.
+///
+/// # Examples
+///
+/// ```
+/// use encode_unicode::utf16;
+///
+/// const GREETING: &[u16; 3] = utf16!("hi!");
+/// assert_eq!(GREETING, &[b'h' as u16, b'i' as u16, b'!' as u16]);
+/// const CARDS: &str = "🂡🂱";
+/// assert_eq!(utf16!(CARDS), &[0xd83c, 0xdca1, 0xd83c, 0xdcb1]);
+/// assert_eq!(utf16!(""), &[]);
+/// ```
+///
+/// The argument must be a constant:
+///
+/// ```compile_fail
+/// let s = String::from("not a constant");
+/// let _ = encode_unicode::utf16!(&s);
+/// ```
+#[macro_export]
+macro_rules! utf16 {($string:expr) => {{
+    const __UTF16_LEN: usize = $crate::__utf16_macro_len($string, false);
+    const __UTF16: [u16; __UTF16_LEN] = $crate::__utf16_macro_encode($string);
+    &__UTF16
+}}}
+
+/// Encode a string as NUL-terminated UTF-16 at compile time.
+///
+/// The string can be a literal or any other constant `&str` expression,
+/// and the result is a `&'static [u16; N]` where the last unit is zero.
+///
+/// Strings that contain `'\0'` are rejected, as they would be cut off by
+/// functions that take NUL-terminated strings.
+///
+/// # Examples
+///
+/// ```
+/// use encode_unicode::utf16z;
+///
+/// const PATH: &[u16; 7] = utf16z!("C:\\Æ.ø");
+/// assert_eq!(PATH, &[67, 58, 92, 0xc6, 46, 0xf8, 0]);
+/// assert_eq!(utf16z!(""), &[0]);
+/// ```
+///
+/// ```compile_fail
+/// let _ = encode_unicode::utf16z!("interior\0nul");
+/// ```
+#[macro_export]
+macro_rules! utf16z {($string:expr) => {{
+    const __UTF16_LEN: usize = $crate::__utf16_macro_len($string, true);
+    const __UTF16: [u16; __UTF16_LEN] = $crate::__utf16_macro_encode($string);
+    &__UTF16
+}}}
+
+/// Create an `Utf8Char` constant from a string containing a single codepoint.
+///
+/// The string can be a literal or any other constant `&str` expression.  
+/// Strings that [`Utf8Char::from_str()`](struct.Utf8Char.html#impl-FromStr)
+/// would reject, because they are empty or contain multiple codepoints,
+/// fail to compile.
+///
+/// # Examples
+///
+/// ```
+/// use encode_unicode::{utf8char, Utf8Char};
+///
+/// const E_ACUTE: Utf8Char = utf8char!("é");
+/// assert_eq!(E_ACUTE.as_bytes(), "é".as_bytes());
+/// ```
+///
+/// ```compile_fail
+/// let _ = encode_unicode::utf8char!("");
+/// ```
+///
+/// ```compile_fail
+/// let _ = encode_unicode::utf8char!("e\u{301}");// 'e' + combining accent
+/// ```
+#[macro_export]
+macro_rules! utf8char {($string:expr) => {{
+    const __UTF8CHAR: $crate::Utf8Char = $crate::__utf8char_macro($string);
+    __UTF8CHAR
+}}}
+
+/// Create an `Utf16Char` constant from a string containing a single codepoint.
+///
+/// The string can be a literal or any other constant `&str` expression.  
+/// Strings that [`Utf16Char::from_str()`](struct.Utf16Char.html#impl-FromStr)
+/// would reject, because they are empty or contain multiple codepoints,
+/// fail to compile.
+///
+/// # Examples
+///
+/// ```
+/// use encode_unicode::{utf16char, Utf16Char};
+///
+/// const GRINNING: Utf16Char = utf16char!("😀");
+/// assert_eq!(GRINNING.to_tuple(), (0xd83d, Some(0xde00)));
+/// ```
+///
+/// ```compile_fail
+/// let _ = encode_unicode::utf16char!("");
+/// ```
+///
+/// ```compile_fail
+/// let _ = encode_unicode::utf16char!("🇳🇴");// two regional indicators
+/// ```
+#[macro_export]
+macro_rules! utf16char {($string:expr) => {{
+    const __UTF16CHAR: $crate::Utf16Char = $crate::__utf16char_macro($string);
+    __UTF16CHAR
+}}}
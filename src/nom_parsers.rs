@@ -0,0 +1,216 @@
+/* Copyright 2022 Torbjørn Birch Moltu
+ *
+ * Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+ * http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+ * http://opensource.org/licenses/MIT>, at your option. This file may not be
+ * copied, modified, or distributed except according to those terms.
+ */
+
+//! Streaming [nom](https://docs.rs/nom/) parsers for UTF-8 and UTF-16 in byte slices.
+//!
+//! Requires the feature "nom", and works with nom 7 and 8.
+//!
+//! Invalid sequences are reported as [`nom::Err::Error`] created with
+//! [`FromExternalError`] from an [`Utf8Error`](../error/struct.Utf8Error.html)
+//! or [`Utf16PairError`](../error/enum.Utf16PairError.html) and
+//! [`ErrorKind::Char`], which `nom::error::Error` accepts but discards.
+//! Input that ends in the middle of a codepoint produces [`nom::Err::Incomplete`]
+//! with the number of bytes needed to complete it.
+//!
+//! [`nom::Err::Error`]: https://docs.rs/nom/latest/nom/enum.Err.html#variant.Error
+//! [`nom::Err::Incomplete`]: https://docs.rs/nom/latest/nom/enum.Err.html#variant.Incomplete
+//! [`FromExternalError`]: https://docs.rs/nom/latest/nom/error/trait.FromExternalError.html
+//! [`ErrorKind::Char`]: https://docs.rs/nom/latest/nom/error/enum.ErrorKind.html#variant.Char
+//!
+//! # Examples
+//!
+//! Parsing a length-prefixed UTF-8 string followed by a UTF-16 character:
+//!
+//! ```
+//! use encode_unicode::nom_parsers::{utf8_str_len, utf16le_char};
+//! use nom::number::streaming::u8 as length;
+//!
+//! let input = &b"\x06h\xc3\xa9llo\x3d\xd8\x00\xde rest"[..];
+//! let (rest, len) = length::<_, nom::error::Error<_>>(input).unwrap();
+//! let (rest, s) = utf8_str_len::<nom::error::Error<_>>(len as usize)(rest).unwrap();
+//! assert_eq!(s, "héllo");
+//! let (rest, u16c) = utf16le_char::<nom::error::Error<_>>(rest).unwrap();
+//! assert_eq!(u16c, '😀');
+//! assert_eq!(rest, b" rest");
+//! ```
+
+use crate::utf8_char::Utf8Char;
+use crate::utf16_char::Utf16Char;
+use crate::errors::{Utf8Error, Utf8ErrorKind, Utf16PairError, Utf16TupleError};
+use crate::lengths::utf8_codepoint_count;
+use crate::traits::{U8UtfExt, U16UtfExt};
+use core::str;
+use nom::{Err, IResult, Needed};
+use nom::error::{ErrorKind, ParseError, FromExternalError};
+
+/// Parse a single UTF-8 encoded codepoint.
+///
+/// # Examples
+///
+/// ```
+/// use encode_unicode::nom_parsers::utf8_char;
+/// use encode_unicode::error::Utf8Error;
+/// use nom::{Err, Needed, error::ErrorKind, error::FromExternalError};
+///
+/// #[derive(Debug, PartialEq)]
+/// struct Invalid(Utf8Error);
+/// impl<I> nom::error::ParseError<I> for Invalid {
+///     fn from_error_kind(_: I,  _: ErrorKind) -> Self { unimplemented!() }
+///     fn append(_: I,  _: ErrorKind,  other: Self) -> Self { other }
+/// }
+/// impl<I> FromExternalError<I, Utf8Error> for Invalid {
+///     fn from_external_error(_: I,  _: ErrorKind,  e: Utf8Error) -> Self { Invalid(e) }
+/// }
+///
+/// assert_eq!(utf8_char::<Invalid>(b"\xe2\x82\xac1"), Ok((&b"1"[..], '€'.into())));
+/// assert_eq!(utf8_char::<Invalid>(b"\xe2\x82"), Err(Err::Incomplete(Needed::new(1))));
+/// assert_eq!(utf8_char::<Invalid>(b""), Err(Err::Incomplete(Needed::new(1))));
+/// let error = utf8_char::<Invalid>(b"\xe0\x80\x80").unwrap_err();
+/// assert!(matches!(error, Err::Error(Invalid(e)) if e.kind() == encode_unicode::error::Utf8ErrorKind::OverlongEncoding));
+/// ```
+pub fn utf8_char<'a, E>(input: &'a [u8]) -> IResult<&'a [u8], Utf8Char, E>
+where E: ParseError<&'a [u8]> + FromExternalError<&'a [u8], Utf8Error> {
+    match Utf8Char::from_slice_start(input) {
+        Ok((u8c, len)) => Ok((&input[len..], u8c)),
+        Err(e) if e.kind() == Utf8ErrorKind::TooFewBytes => {
+            let needed = match input.first() {
+                Some(&first) => first.extra_utf8_bytes_unchecked() + 1 - input.len(),
+                None => 1,
+            };
+            Err(Err::Incomplete(Needed::new(needed)))
+        }
+        Err(e) => Err(Err::Error(E::from_external_error(input, ErrorKind::Char, e))),
+    }
+}
+
+fn utf16_char<'a, E>(input: &'a [u8],  to_unit: fn([u8; 2]) -> u16)
+-> IResult<&'a [u8], Utf16Char, E>
+where E: ParseError<&'a [u8]> + FromExternalError<&'a [u8], Utf16PairError> {
+    let first = match *input {
+        [a, b, ..] => to_unit([a, b]),
+        _ => return Err(Err::Incomplete(Needed::new(2 - input.len()))),
+    };
+    let second = match *input {
+        _ if !first.is_utf16_leading_surrogate() => None,
+        [_, _, c, d, ..] => Some(to_unit([c, d])),
+        _ => return Err(Err::Incomplete(Needed::new(4 - input.len()))),
+    };
+    match Utf16Char::from_tuple((first, second)) {
+        Ok(u16c) => Ok((&input[2*u16c.len()..], u16c)),
+        Err(e) => {
+            let e = match e {
+                Utf16TupleError::FirstIsTrailingSurrogate => Utf16PairError::UnexpectedTrailingSurrogate,
+                _ => Utf16PairError::UnmatchedLeadingSurrogate,
+            };
+            Err(Err::Error(E::from_external_error(input, ErrorKind::Char, e)))
+        }
+    }
+}
+
+/// Parse a single UTF-16 encoded codepoint stored as little-endian bytes.
+///
+/// # Examples
+///
+/// ```
+/// use encode_unicode::nom_parsers::utf16le_char;
+/// use nom::{Err, Needed, error::Error};
+///
+/// assert_eq!(utf16le_char::<Error<_>>(b"\x3d\xd8\x00\xde"), Ok((&b""[..], '😀'.into())));
+/// assert_eq!(utf16le_char::<Error<_>>(b"\x3d\xd8\x00"), Err(Err::Incomplete(Needed::new(1))));
+/// assert!(utf16le_char::<Error<_>>(b"\x00\xde\x3d\xd8").is_err());
+/// ```
+pub fn utf16le_char<'a, E>(input: &'a [u8]) -> IResult<&'a [u8], Utf16Char, E>
+where E: ParseError<&'a [u8]> + FromExternalError<&'a [u8], Utf16PairError> {
+    utf16_char(input, u16::from_le_bytes)
+}
+
+/// Parse a single UTF-16 encoded codepoint stored as big-endian bytes.
+///
+/// # Examples
+///
+/// ```
+/// use encode_unicode::nom_parsers::utf16be_char;
+/// use nom::{Err, Needed, error::Error};
+///
+/// assert_eq!(utf16be_char::<Error<_>>(b"\x00A\x00B"), Ok((&b"\x00B"[..], 'A'.into())));
+/// assert_eq!(utf16be_char::<Error<_>>(b"\xd8"), Err(Err::Incomplete(Needed::new(1))));
+/// assert!(utf16be_char::<Error<_>>(b"\xd8\x3d\x00A").is_err());
+/// ```
+pub fn utf16be_char<'a, E>(input: &'a [u8]) -> IResult<&'a [u8], Utf16Char, E>
+where E: ParseError<&'a [u8]> + FromExternalError<&'a [u8], Utf16PairError> {
+    utf16_char(input, u16::from_be_bytes)
+}
+
+/// Parse the longest UTF-8 string where every codepoint matches the predicate.
+///
+/// Like nom's streaming `take_while()`, this returns `Incomplete` if the
+/// input ends before a codepoint that doesn't match, as more input might match too.
+/// Invalid UTF-8 before that produces an error.
+///
+/// # Examples
+///
+/// ```
+/// use encode_unicode::nom_parsers::take_utf8_while;
+/// use nom::{Err, Needed, error::Error};
+///
+/// let mut word = take_utf8_while::<_, Error<_>>(|u8c| u8c.to_char().is_alphabetic() );
+/// assert_eq!(word("Grüß Gott".as_bytes()), Ok((&b" Gott"[..], "Grüß")));
+/// assert_eq!(word(b"Gr\xc3"), Err(Err::Incomplete(Needed::new(1))));
+/// assert!(word(b"Gr\xff").is_err());
+/// ```
+pub fn take_utf8_while<'a, P, E>(mut predicate: P) -> impl FnMut(&'a [u8]) -> IResult<&'a [u8], &'a str, E>
+where P: FnMut(Utf8Char) -> bool,  E: ParseError<&'a [u8]> + FromExternalError<&'a [u8], Utf8Error> {
+    move |input: &'a [u8]| {
+        let mut len = 0;
+        loop {
+            let (_, u8c) = utf8_char(&input[len..])?;
+            if !predicate(u8c) {
+                break;
+            }
+            len += u8c.len();
+        }
+        // SAFETY: the bytes were validated by utf8_char()
+        let s = unsafe { str::from_utf8_unchecked(&input[..len]) };
+        Ok((&input[len..], s))
+    }
+}
+
+/// Parse exactly `n_bytes` bytes of UTF-8, which must end at a codepoint boundary.
+///
+/// `Incomplete` is returned if there are fewer bytes than that,
+/// and an error if the bytes are not valid UTF-8.
+///
+/// # Examples
+///
+/// ```
+/// use encode_unicode::nom_parsers::utf8_str_len;
+/// use nom::{Err, Needed, error::Error};
+///
+/// let mut field = utf8_str_len::<Error<_>>(3);
+/// assert_eq!(field("€uro".as_bytes()), Ok((&b"uro"[..], "€")));
+/// assert_eq!(field(b"ab"), Err(Err::Incomplete(Needed::new(1))));
+/// assert!(field(b"a\xc3\xa9").is_ok());
+/// assert!(field(b"ab\xc3").is_err());
+/// ```
+pub fn utf8_str_len<'a, E>(n_bytes: usize) -> impl FnMut(&'a [u8]) -> IResult<&'a [u8], &'a str, E>
+where E: ParseError<&'a [u8]> + FromExternalError<&'a [u8], Utf8Error> {
+    move |input: &'a [u8]| {
+        if input.len() < n_bytes {
+            return Err(Err::Incomplete(Needed::new(n_bytes - input.len())));
+        }
+        let (field, rest) = input.split_at(n_bytes);
+        match utf8_codepoint_count(field) {
+            // SAFETY: just validated
+            Ok(_) => Ok((rest, unsafe { str::from_utf8_unchecked(field) })),
+            Err(e) => {
+                let at = &input[e.offset()..];
+                Err(Err::Error(E::from_external_error(at, ErrorKind::Char, *e.error())))
+            }
+        }
+    }
+}
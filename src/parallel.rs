@@ -0,0 +1,255 @@
+/* Copyright 2022 Torbjørn Birch Moltu
+ *
+ * Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+ * http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+ * http://opensource.org/licenses/MIT>, at your option. This file may not be
+ * copied, modified, or distributed except according to those terms.
+ */
+
+//! Validating and transcoding large slices in parallel with [rayon](https://docs.rs/rayon/1/rayon/).
+//!
+//! Requires the feature "rayon".
+//!
+//! The input is split into chunks before a byte that isn't a continuation
+//! byte or an unit that isn't a trailing surrogate.
+//! [`Utf8CharDecoder`](../iterator/struct.Utf8CharDecoder.html) and
+//! [`Utf16CharDecoder`](../iterator/struct.Utf16CharDecoder.html) always start
+//! decoding a new codepoint or invalid sequence there, so each chunk decodes
+//! the same by itself as it does as part of the whole slice.
+//! The exception is a codepoint which is cut by the end of a chunk:
+//! It is reported as incomplete by itself, but the following byte or unit
+//! interrupts it, and the error is corrected to be what the decoders return.
+//! The results are therefore the same as for the sequential functions,
+//! including the offset and kind of the first error.
+//!
+//! # Examples
+//!
+//! ```
+//! use encode_unicode::parallel::{transcode_utf8_to_utf16, validate_utf8};
+//! use encode_unicode::error::Utf8ErrorKind;
+//!
+//! let text = "bløtkake 🎂 ".repeat(100_000);
+//! assert_eq!(validate_utf8(text.as_bytes()), Ok(()));
+//! let utf16 = transcode_utf8_to_utf16(text.as_bytes()).unwrap();
+//! assert_eq!(utf16.len(), text.encode_utf16().count());
+//!
+//! let mut bytes = text.into_bytes();
+//! bytes[1_000_001] = b'?';
+//! let error = validate_utf8(&bytes).unwrap_err();
+//! assert_eq!(error.offset(), 1_000_000);
+//! assert_eq!(error.error().kind(), Utf8ErrorKind::InterruptedSequence);
+//! ```
+
+use crate::errors::{PositionedError, TranscodeError, Utf8Error, Utf8ErrorKind, Utf16PairError};
+use crate::utf8_char::Utf8Char;
+use crate::lengths::utf8_codepoint_count;
+use crate::validation;
+use crate::transcoding;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::ops::Range;
+use rayon::prelude::*;
+
+/// Chunks are not made shorter than this, to keep the overhead low.
+const MIN_CHUNK_LEN: usize = 64*1024;
+
+/// Aim for a few chunks per thread, so that threads that finish early can take more.
+fn chunk_len(len: usize) -> usize {
+    usize::max(MIN_CHUNK_LEN, len / (4*rayon::current_num_threads()))
+}
+
+/// Split `0..len` into ranges of at least `target_len`,
+/// that each start at an index where `continues` is false.
+fn chunk_ranges(len: usize,  target_len: usize,  continues: impl Fn(usize) -> bool)
+-> Vec<Range<usize>> {
+    let mut ranges = Vec::with_capacity(len / target_len.max(1) + 1);
+    let mut start = 0;
+    while start < len {
+        let mut end = usize::min(start.saturating_add(target_len.max(1)), len);
+        while end < len  &&  continues(end) {
+            end += 1;
+        }
+        ranges.push(start..end);
+        start = end;
+    }
+    ranges
+}
+
+fn utf8_ranges(bytes: &[u8],  target_len: usize) -> Vec<Range<usize>> {
+    chunk_ranges(bytes.len(), target_len, |i| bytes[i] & 0b1100_0000 == 0b1000_0000 )
+}
+
+fn utf16_ranges(units: &[u16],  target_len: usize) -> Vec<Range<usize>> {
+    chunk_ranges(units.len(), target_len, |i| units[i] & 0xfc_00 == 0xdc_00 )
+}
+
+/// Split possibly invalid UTF-8 into pieces of at least `target_len` bytes
+/// (except the last) at places where the decoders start a new codepoint.
+///
+/// A piece only gets longer than `target_len` plus three bytes if it ends
+/// in a run of continuation bytes, which never happens in valid UTF-8.
+///
+/// # Examples
+///
+/// ```
+/// use encode_unicode::parallel::split_utf8;
+///
+/// let pieces = split_utf8("a€b😀".as_bytes(), 2);
+/// assert_eq!(pieces, ["a€".as_bytes(), "b😀".as_bytes()]);
+/// assert_eq!(split_utf8(b"\x80\x80\x80", 1), [b"\x80\x80\x80"]);
+/// ```
+pub fn split_utf8(bytes: &[u8],  target_len: usize) -> Vec<&[u8]> {
+    utf8_ranges(bytes, target_len).into_iter().map(|range| &bytes[range] ).collect()
+}
+
+/// Split possibly invalid UTF-16 into pieces of at least `target_len` units
+/// (except the last) at places where the decoders start a new codepoint.
+///
+/// A piece only gets longer than `target_len` plus one unit if it ends in
+/// multiple trailing surrogates, which never happens in valid UTF-16.
+///
+/// # Examples
+///
+/// ```
+/// use encode_unicode::parallel::split_utf16;
+///
+/// let units = "😀😀a".encode_utf16().collect::<Vec<u16>>();
+/// assert_eq!(split_utf16(&units, 1), [&units[..2], &units[2..4], &units[4..]]);
+/// ```
+pub fn split_utf16(units: &[u16],  target_len: usize) -> Vec<&[u16]> {
+    utf16_ranges(units, target_len).into_iter().map(|range| &units[range] ).collect()
+}
+
+/// Position an error from a chunk in the whole slice,
+/// and correct the kind if the codepoint is cut by the end of the chunk.
+fn utf8_error_in(bytes: &[u8],  chunk: &Range<usize>,  offset: usize,  error: Utf8Error)
+-> PositionedError<Utf8Error> {
+    let offset = chunk.start + offset;
+    let error = match Utf8Char::from_slice_start(&bytes[offset..]) {
+        Err(e) if error.kind() == Utf8ErrorKind::TooFewBytes => e,
+        _ => error,
+    };
+    PositionedError { offset,  error }
+}
+
+fn utf16_error_in(units: &[u16],  chunk: &Range<usize>,  offset: usize,  error: Utf16PairError)
+-> PositionedError<Utf16PairError> {
+    let error = match error {
+        // the next unit is not a trailing surrogate
+        Utf16PairError::Incomplete if chunk.end < units.len() => Utf16PairError::UnmatchedLeadingSurrogate,
+        error => error,
+    };
+    PositionedError { offset: chunk.start + offset,  error }
+}
+
+/// Check that a slice is valid UTF-8, in parallel.
+///
+/// # Errors
+///
+/// Returns the position and type of the first invalid sequence, which is
+/// the same as what [`slice::utf8_codepoint_count()`](../slice/fn.utf8_codepoint_count.html)
+/// returns.
+pub fn validate_utf8(bytes: &[u8]) -> Result<(), PositionedError<Utf8Error>> {
+    let ranges = utf8_ranges(bytes, chunk_len(bytes.len()));
+    let error = ranges.par_iter().find_map_first(|range| {
+        let e = utf8_codepoint_count(&bytes[range.clone()]).err()?;
+        Some(utf8_error_in(bytes, range, e.offset(), *e.error()))
+    });
+    match error {
+        Some(error) => Err(error),
+        None => Ok(()),
+    }
+}
+
+/// Check that a slice is valid UTF-16, in parallel.
+///
+/// # Errors
+///
+/// Returns the position and type of the first unpaired surrogate, which is
+/// the same as what [`slice::validate_utf16()`](../slice/fn.validate_utf16.html)
+/// returns.
+pub fn validate_utf16(units: &[u16]) -> Result<(), PositionedError<Utf16PairError>> {
+    let ranges = utf16_ranges(units, chunk_len(units.len()));
+    let error = ranges.par_iter().find_map_first(|range| {
+        let e = validation::validate_utf16(&units[range.clone()]).err()?;
+        Some(utf16_error_in(units, range, e.offset(), *e.error()))
+    });
+    match error {
+        Some(error) => Err(error),
+        None => Ok(()),
+    }
+}
+
+/// Convert UTF-8 to UTF-16, in parallel.
+///
+/// # Errors
+///
+/// Returns the position and type of the first invalid sequence.
+///
+/// # Examples
+///
+/// ```
+/// use encode_unicode::parallel::transcode_utf8_to_utf16;
+/// use encode_unicode::error::Utf8ErrorKind;
+///
+/// assert_eq!(transcode_utf8_to_utf16("ab😀".as_bytes()), Ok(vec![0x61, 0x62, 0xd83d, 0xde00]));
+/// let error = transcode_utf8_to_utf16(b"ab\xf0\x9f\x98").unwrap_err();
+/// assert_eq!((error.offset(), error.error().kind()), (2, Utf8ErrorKind::TooFewBytes));
+/// ```
+pub fn transcode_utf8_to_utf16(src: &[u8]) -> Result<Vec<u16>, PositionedError<Utf8Error>> {
+    let ranges = utf8_ranges(src, chunk_len(src.len()));
+    let chunks = ranges.par_iter().map(|range| {
+        // every byte produces at most one unit
+        let mut dst = alloc::vec![0; range.len()];
+        let (read, written, result) = transcoding::transcode_utf8_to_utf16(&src[range.clone()], &mut dst);
+        match result {
+            Ok(()) => {
+                dst.truncate(written);
+                Ok(dst)
+            }
+            Err(TranscodeError::Invalid(e)) => Err(utf8_error_in(src, range, read, e)),
+            Err(TranscodeError::OutputFull { .. }) => unreachable!("the output has room for everything"),
+        }
+    }).collect::<Vec<_>>();
+    // rayon would return any of the errors
+    let chunks = chunks.into_iter().collect::<Result<Vec<Vec<u16>>, _>>()?;
+    Ok(chunks.concat())
+}
+
+/// Convert UTF-16 to UTF-8, in parallel.
+///
+/// # Errors
+///
+/// Returns the position and type of the first unpaired surrogate.
+///
+/// # Examples
+///
+/// ```
+/// use encode_unicode::parallel::transcode_utf16_to_utf8;
+/// use encode_unicode::error::Utf16PairError;
+///
+/// assert_eq!(transcode_utf16_to_utf8(&[0x61, 0xd83d, 0xde00]).as_deref(), Ok("a😀"));
+/// let error = transcode_utf16_to_utf8(&[0x61, 0xde00]).unwrap_err();
+/// assert_eq!((error.offset(), *error.error()), (1, Utf16PairError::UnexpectedTrailingSurrogate));
+/// ```
+pub fn transcode_utf16_to_utf8(src: &[u16]) -> Result<String, PositionedError<Utf16PairError>> {
+    let ranges = utf16_ranges(src, chunk_len(src.len()));
+    let chunks = ranges.par_iter().map(|range| {
+        // no codepoint is more than three bytes per unit
+        let mut dst = alloc::vec![0; 3*range.len()];
+        let (read, written, result) = transcoding::transcode_utf16_to_utf8(&src[range.clone()], &mut dst);
+        match result {
+            Ok(()) => {
+                dst.truncate(written);
+                Ok(dst)
+            }
+            Err(TranscodeError::Invalid(e)) => Err(utf16_error_in(src, range, read, e)),
+            Err(TranscodeError::OutputFull { .. }) => unreachable!("the output has room for everything"),
+        }
+    }).collect::<Vec<_>>();
+    // rayon would return any of the errors
+    let chunks = chunks.into_iter().collect::<Result<Vec<Vec<u8>>, _>>()?;
+    // SAFETY: transcode_utf16_to_utf8() produces valid UTF-8,
+    //         and the chunks end at codepoint boundaries.
+    Ok(unsafe { String::from_utf8_unchecked(chunks.concat()) })
+}
@@ -0,0 +1,395 @@
+/* Copyright 2022 Torbjørn Birch Moltu
+ *
+ * Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+ * http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+ * http://opensource.org/licenses/MIT>, at your option. This file may not be
+ * copied, modified, or distributed except according to those terms.
+ */
+
+//! Searching possibly invalid UTF-8 and UTF-16 for a codepoint.
+//!
+//! The haystack is scanned for one byte or unit of the needle, and the rest
+//! of the needle is compared where it's found.
+//! For UTF-8 that is the last byte, as the first byte of a multi-byte sequence
+//! is shared by all codepoints in the same block, while the last byte has the
+//! lowest six bits of the codepoint.
+//! With the feature "memchr" the scanning is done by
+//! [memchr](https://docs.rs/memchr/2/memchr/), which uses SIMD where available.
+//!
+//! As the needle is a complete codepoint, a match is always at a codepoint
+//! boundary as the decoding iterators see it, even if the haystack is invalid:
+//! The first byte of the needle is not a continuation byte and a leading
+//! surrogate must be followed by a trailing one, so an invalid sequence
+//! before a match cannot extend into it.
+
+use crate::utf8_char::Utf8Char;
+use crate::utf16_char::Utf16Char;
+use core::iter::FusedIterator;
+
+#[cfg(feature="memchr")]
+fn find_byte(needle: u8,  haystack: &[u8]) -> Option<usize> {
+    memchr::memchr(needle, haystack)
+}
+#[cfg(not(feature="memchr"))]
+fn find_byte(needle: u8,  haystack: &[u8]) -> Option<usize> {
+    haystack.iter().position(|&b| b == needle )
+}
+
+#[cfg(feature="memchr")]
+fn rfind_byte(needle: u8,  haystack: &[u8]) -> Option<usize> {
+    memchr::memrchr(needle, haystack)
+}
+#[cfg(not(feature="memchr"))]
+fn rfind_byte(needle: u8,  haystack: &[u8]) -> Option<usize> {
+    haystack.iter().rposition(|&b| b == needle )
+}
+
+/// Pick the byte of an unit to scan for, and its index in the unit in memory.
+///
+/// Unless it's zero, which is the upper byte of all ASCII units,
+/// this is the lower byte, as the upper byte is shared by a whole block.
+#[cfg(feature="memchr")]
+fn unit_byte(unit: u16) -> (u8, usize) {
+    let (upper, lower) = ((unit >> 8) as u8, unit as u8);
+    let upper_index = cfg!(target_endian="little") as usize;
+    match lower {
+        0 => (upper, upper_index),
+        _ => (lower, 1-upper_index),
+    }
+}
+
+/// View units as the bytes they are stored as.
+#[cfg(feature="memchr")]
+fn unit_bytes(units: &[u16]) -> &[u8] {
+    // SAFETY: the bytes are initialized and have no alignment requirement,
+    //         and the length in bytes is that of the same allocation.
+    unsafe { core::slice::from_raw_parts(units.as_ptr() as *const u8, 2*units.len()) }
+}
+
+#[cfg(feature="memchr")]
+fn find_unit(needle: u16,  haystack: &[u16]) -> Option<usize> {
+    let (byte, index) = unit_byte(needle);
+    let bytes = unit_bytes(haystack);
+    let mut start = 0;
+    loop {
+        let found = start + memchr::memchr(byte, &bytes[start..])?;
+        if found % 2 == index  &&  haystack[found/2] == needle {
+            return Some(found/2);
+        }
+        start = found + 1;
+    }
+}
+#[cfg(not(feature="memchr"))]
+fn find_unit(needle: u16,  haystack: &[u16]) -> Option<usize> {
+    haystack.iter().position(|&unit| unit == needle )
+}
+
+#[cfg(feature="memchr")]
+fn rfind_unit(needle: u16,  haystack: &[u16]) -> Option<usize> {
+    let (byte, index) = unit_byte(needle);
+    let bytes = unit_bytes(haystack);
+    let mut end = bytes.len();
+    loop {
+        let found = memchr::memrchr(byte, &bytes[..end])?;
+        if found % 2 == index  &&  haystack[found/2] == needle {
+            return Some(found/2);
+        }
+        end = found;
+    }
+}
+#[cfg(not(feature="memchr"))]
+fn rfind_unit(needle: u16,  haystack: &[u16]) -> Option<usize> {
+    haystack.iter().rposition(|&unit| unit == needle )
+}
+
+
+fn find_utf8(haystack: &[u8],  needle: &[u8]) -> Option<usize> {
+    let last = needle.len() - 1;
+    let mut start = last;
+    while start < haystack.len() {
+        let found = start + find_byte(needle[last], &haystack[start..])?;
+        if &haystack[found-last..=found] == needle {
+            return Some(found-last);
+        }
+        start = found + 1;
+    }
+    None
+}
+
+fn rfind_utf8(haystack: &[u8],  needle: &[u8]) -> Option<usize> {
+    let last = needle.len() - 1;
+    let mut end = haystack.len();
+    while end > last {
+        let found = last + rfind_byte(needle[last], &haystack[last..end])?;
+        if &haystack[found-last..=found] == needle {
+            return Some(found-last);
+        }
+        end = found;
+    }
+    None
+}
+
+fn find_utf16(haystack: &[u16],  needle: &[u16]) -> Option<usize> {
+    let last = needle.len() - 1;
+    let mut start = last;
+    while start < haystack.len() {
+        let found = start + find_unit(needle[last], &haystack[start..])?;
+        if &haystack[found-last..=found] == needle {
+            return Some(found-last);
+        }
+        start = found + 1;
+    }
+    None
+}
+
+fn rfind_utf16(haystack: &[u16],  needle: &[u16]) -> Option<usize> {
+    let last = needle.len() - 1;
+    let mut end = haystack.len();
+    while end > last {
+        let found = last + rfind_unit(needle[last], &haystack[last..end])?;
+        if &haystack[found-last..=found] == needle {
+            return Some(found-last);
+        }
+        end = found;
+    }
+    None
+}
+
+
+/// Find the first occurrence of a codepoint in possibly invalid UTF-8,
+/// and return its byte offset.
+///
+/// # Examples
+///
+/// ```
+/// use encode_unicode::Utf8Char;
+/// use encode_unicode::slice::find_utf8char;
+///
+/// let bytes = b"\xe2\x82 cost: 5\xe2\x82\xac or 5\xe2\x82\xac";
+/// assert_eq!(find_utf8char(bytes, Utf8Char::from('€')), Some(10));
+/// assert_eq!(find_utf8char(bytes, Utf8Char::from('£')), None);
+/// assert_eq!(find_utf8char(bytes, Utf8Char::from(' ')), Some(2));
+/// ```
+pub fn find_utf8char(haystack: &[u8],  needle: Utf8Char) -> Option<usize> {
+    find_utf8(haystack, needle.as_bytes())
+}
+
+/// Find the last occurrence of a codepoint in possibly invalid UTF-8,
+/// and return its byte offset.
+///
+/// # Examples
+///
+/// ```
+/// use encode_unicode::Utf8Char;
+/// use encode_unicode::slice::rfind_utf8char;
+///
+/// let bytes = b"5\xe2\x82\xac or 5\xe2\x82\xac\xac";
+/// assert_eq!(rfind_utf8char(bytes, Utf8Char::from('€')), Some(9));
+/// assert_eq!(rfind_utf8char(bytes, Utf8Char::from('5')), Some(8));
+/// ```
+pub fn rfind_utf8char(haystack: &[u8],  needle: Utf8Char) -> Option<usize> {
+    rfind_utf8(haystack, needle.as_bytes())
+}
+
+/// Count the occurrences of a codepoint in possibly invalid UTF-8.
+///
+/// # Examples
+///
+/// ```
+/// use encode_unicode::Utf8Char;
+/// use encode_unicode::slice::count_utf8char;
+///
+/// assert_eq!(count_utf8char("smørbrød".as_bytes(), Utf8Char::from('ø')), 2);
+/// assert_eq!(count_utf8char(b"\xc3\xb8\xb8\xc3", Utf8Char::from('ø')), 1);
+/// ```
+pub fn count_utf8char(haystack: &[u8],  needle: Utf8Char) -> usize {
+    let mut rest = haystack;
+    let mut count = 0;
+    while let Some(found) = find_utf8(rest, needle.as_bytes()) {
+        count += 1;
+        rest = &rest[found+needle.len()..];
+    }
+    count
+}
+
+/// Split possibly invalid UTF-8 on a codepoint.
+///
+/// Like `str::split()` there is an empty slice before and after a
+/// separator at the start or end, and between adjacent separators.
+///
+/// # Examples
+///
+/// ```
+/// use encode_unicode::Utf8Char;
+/// use encode_unicode::slice::split_on_utf8char;
+///
+/// let bytes = b"a\xff\xe2\x80\xa2b\xe2\x80\xa2\xe2\x80\xa2";
+/// let parts = split_on_utf8char(bytes, Utf8Char::from('•')).collect::<Vec<_>>();
+/// assert_eq!(parts, [&b"a\xff"[..], b"b", b"", b""]);
+/// let reversed = split_on_utf8char(bytes, Utf8Char::from('•')).rev().collect::<Vec<_>>();
+/// assert_eq!(reversed, [&b""[..], b"", b"b", b"a\xff"]);
+/// ```
+pub fn split_on_utf8char(haystack: &[u8],  needle: Utf8Char) -> SplitOnUtf8Char<'_> {
+    SplitOnUtf8Char { rest: Some(haystack),  needle }
+}
+
+/// Find the first occurrence of a codepoint in possibly invalid UTF-16,
+/// and return its offset in units.
+///
+/// # Examples
+///
+/// ```
+/// use encode_unicode::Utf16Char;
+/// use encode_unicode::slice::find_utf16char;
+///
+/// let units = [0xd83d, 0x20, 0xd83d, 0xde00];
+/// assert_eq!(find_utf16char(&units, Utf16Char::from('😀')), Some(2));
+/// assert_eq!(find_utf16char(&units, Utf16Char::from(' ')), Some(1));
+/// assert_eq!(find_utf16char(&units, Utf16Char::from('A')), None);
+/// ```
+pub fn find_utf16char(haystack: &[u16],  needle: Utf16Char) -> Option<usize> {
+    find_utf16(haystack, needle.as_ref())
+}
+
+/// Find the last occurrence of a codepoint in possibly invalid UTF-16,
+/// and return its offset in units.
+///
+/// # Examples
+///
+/// ```
+/// use encode_unicode::Utf16Char;
+/// use encode_unicode::slice::rfind_utf16char;
+///
+/// let units = [0x41, 0xd83d, 0xde00, 0x41, 0xde00];
+/// assert_eq!(rfind_utf16char(&units, Utf16Char::from('😀')), Some(1));
+/// assert_eq!(rfind_utf16char(&units, Utf16Char::from('A')), Some(3));
+/// ```
+pub fn rfind_utf16char(haystack: &[u16],  needle: Utf16Char) -> Option<usize> {
+    rfind_utf16(haystack, needle.as_ref())
+}
+
+/// Count the occurrences of a codepoint in possibly invalid UTF-16.
+///
+/// # Examples
+///
+/// ```
+/// use encode_unicode::Utf16Char;
+/// use encode_unicode::slice::count_utf16char;
+///
+/// let units = "ab😀c😀".encode_utf16().collect::<Vec<u16>>();
+/// assert_eq!(count_utf16char(&units, Utf16Char::from('😀')), 2);
+/// assert_eq!(count_utf16char(&units[..3], Utf16Char::from('😀')), 0);
+/// ```
+pub fn count_utf16char(haystack: &[u16],  needle: Utf16Char) -> usize {
+    let mut rest = haystack;
+    let mut count = 0;
+    while let Some(found) = find_utf16(rest, needle.as_ref()) {
+        count += 1;
+        rest = &rest[found+needle.len()..];
+    }
+    count
+}
+
+/// Split possibly invalid UTF-16 on a codepoint.
+///
+/// Like `str::split()` there is an empty slice before and after a
+/// separator at the start or end, and between adjacent separators.
+///
+/// # Examples
+///
+/// ```
+/// use encode_unicode::Utf16Char;
+/// use encode_unicode::slice::split_on_utf16char;
+///
+/// let units = [0x61, 0x2c, 0xd800, 0x2c];
+/// let parts = split_on_utf16char(&units, Utf16Char::from(',')).collect::<Vec<_>>();
+/// assert_eq!(parts, [&[0x61][..], &[0xd800], &[]]);
+/// ```
+pub fn split_on_utf16char(haystack: &[u16],  needle: Utf16Char) -> SplitOnUtf16Char<'_> {
+    SplitOnUtf16Char { rest: Some(haystack),  needle }
+}
+
+
+/// Iterator over the parts of possibly invalid UTF-8 between occurrences of a codepoint.
+///
+/// Returned by [`split_on_utf8char()`](../slice/fn.split_on_utf8char.html).
+#[derive(Clone, Debug)]
+pub struct SplitOnUtf8Char<'a> {
+    /// `None` after the last part has been returned.
+    rest: Option<&'a [u8]>,
+    needle: Utf8Char,
+}
+impl<'a> Iterator for SplitOnUtf8Char<'a> {
+    type Item = &'a [u8];
+    fn next(&mut self) -> Option<&'a [u8]> {
+        let rest = self.rest?;
+        match find_utf8(rest, self.needle.as_bytes()) {
+            Some(found) => {
+                self.rest = Some(&rest[found+self.needle.len()..]);
+                Some(&rest[..found])
+            }
+            None => self.rest.take(),
+        }
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match self.rest {
+            Some(rest) => (1, Some(rest.len()/self.needle.len() + 1)),
+            None => (0, Some(0)),
+        }
+    }
+}
+impl<'a> DoubleEndedIterator for SplitOnUtf8Char<'a> {
+    fn next_back(&mut self) -> Option<&'a [u8]> {
+        let rest = self.rest?;
+        match rfind_utf8(rest, self.needle.as_bytes()) {
+            Some(found) => {
+                self.rest = Some(&rest[..found]);
+                Some(&rest[found+self.needle.len()..])
+            }
+            None => self.rest.take(),
+        }
+    }
+}
+impl<'a> FusedIterator for SplitOnUtf8Char<'a> {}
+
+/// Iterator over the parts of possibly invalid UTF-16 between occurrences of a codepoint.
+///
+/// Returned by [`split_on_utf16char()`](../slice/fn.split_on_utf16char.html).
+#[derive(Clone, Debug)]
+pub struct SplitOnUtf16Char<'a> {
+    /// `None` after the last part has been returned.
+    rest: Option<&'a [u16]>,
+    needle: Utf16Char,
+}
+impl<'a> Iterator for SplitOnUtf16Char<'a> {
+    type Item = &'a [u16];
+    fn next(&mut self) -> Option<&'a [u16]> {
+        let rest = self.rest?;
+        match find_utf16(rest, self.needle.as_ref()) {
+            Some(found) => {
+                self.rest = Some(&rest[found+self.needle.len()..]);
+                Some(&rest[..found])
+            }
+            None => self.rest.take(),
+        }
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match self.rest {
+            Some(rest) => (1, Some(rest.len()/self.needle.len() + 1)),
+            None => (0, Some(0)),
+        }
+    }
+}
+impl<'a> DoubleEndedIterator for SplitOnUtf16Char<'a> {
+    fn next_back(&mut self) -> Option<&'a [u16]> {
+        let rest = self.rest?;
+        match rfind_utf16(rest, self.needle.as_ref()) {
+            Some(found) => {
+                self.rest = Some(&rest[..found]);
+                Some(&rest[found+self.needle.len()..])
+            }
+            None => self.rest.take(),
+        }
+    }
+}
+impl<'a> FusedIterator for SplitOnUtf16Char<'a> {}
@@ -0,0 +1,178 @@
+/* Copyright 2022 Torbjørn Birch Moltu
+ *
+ * Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+ * http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+ * http://opensource.org/licenses/MIT>, at your option. This file may not be
+ * copied, modified, or distributed except according to those terms.
+ */
+
+//! [proptest](https://docs.rs/proptest/1/proptest/) strategies for valid
+//! UTF-8 and UTF-16, and for sequences that produce a chosen error.
+//!
+//! Requires the feature "proptest".
+//!
+//! # Examples
+//!
+//! ```
+//! use encode_unicode::strategies::utf8_with_error;
+//! use encode_unicode::error::Utf8ErrorKind;
+//! use encode_unicode::slice::utf8_codepoint_count;
+//! use proptest::prelude::*;
+//!
+//! proptest! {
+//!     fn offset_of_overlong_encoding((bytes, offset) in utf8_with_error(Utf8ErrorKind::OverlongEncoding)) {
+//!         let error = utf8_codepoint_count(&bytes).unwrap_err();
+//!         prop_assert_eq!(error.offset(), offset);
+//!         prop_assert_eq!(error.error().kind(), Utf8ErrorKind::OverlongEncoding);
+//!     }
+//! }
+//! # offset_of_overlong_encoding();
+//! ```
+
+use crate::utf8_char::Utf8Char;
+use crate::utf16_char::Utf16Char;
+use crate::errors::{Utf8ErrorKind, Utf16PairError};
+use alloc::vec;
+use alloc::vec::Vec;
+use alloc::string::String;
+use proptest::prelude::*;
+use proptest::collection::vec;
+
+/// The number of codepoints in the valid parts generated by this module.
+const MAX_CODEPOINTS: usize = 32;
+
+/// Requires the feature "proptest".
+impl Arbitrary for Utf8Char {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        any::<char>().prop_map(Utf8Char::from).boxed()
+    }
+}
+
+/// Requires the feature "proptest".
+impl Arbitrary for Utf16Char {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        any::<char>().prop_map(Utf16Char::from).boxed()
+    }
+}
+
+/// Generate strings of up to 32 codepoints.
+pub fn valid_utf8() -> BoxedStrategy<String> {
+    vec(any::<char>(), 0..=MAX_CODEPOINTS)
+        .prop_map(|chars| chars.into_iter().collect() )
+        .boxed()
+}
+
+/// Generate valid UTF-16 of up to 32 codepoints.
+pub fn valid_utf16() -> BoxedStrategy<Vec<u16>> {
+    vec(any::<Utf16Char>(), 0..=MAX_CODEPOINTS)
+        .prop_map(|chars| chars.into_iter().flatten().collect() )
+        .boxed()
+}
+
+/// Generate valid codepoints that are at least `min_len` bytes long as UTF-8.
+fn multibyte_utf8(min_len: usize) -> BoxedStrategy<Utf8Char> {
+    any::<Utf8Char>().prop_filter("too short", move |u8c| u8c.len() >= min_len ).boxed()
+}
+
+/// Generate `prefix` followed by `len` continuation bytes.
+fn with_continuation_bytes(prefix: Vec<u8>,  len: usize) -> BoxedStrategy<Vec<u8>> {
+    vec(0x80..=0xbfu8, len).prop_map(move |rest| [&prefix[..], &rest[..]].concat() ).boxed()
+}
+
+/// Generate sequences for which
+/// [`Utf8Char::from_slice_start()`](../struct.Utf8Char.html#method.from_slice_start)
+/// returns the given error kind.
+///
+/// The sequences are as short as possible, and `TooFewBytes` sequences must
+/// be at the end of the slice to produce that error;
+/// If followed by a continuation byte they might become valid,
+/// and otherwise they produce `InterruptedSequence`.
+pub fn invalid_utf8(kind: Utf8ErrorKind) -> BoxedStrategy<Vec<u8>> {
+    use Utf8ErrorKind::*;
+    match kind {
+        TooFewBytes => multibyte_utf8(2).prop_flat_map(|u8c| {
+            let bytes = u8c.as_bytes().to_vec();
+            (1..bytes.len()).prop_map(move |len| bytes[..len].to_vec() )
+        }).boxed(),
+        NonUtf8Byte => prop_oneof![0xc0..=0xc1u8, 0xf5..=0xffu8].prop_map(|b| vec![b] ).boxed(),
+        UnexpectedContinuationByte => (0x80..=0xbfu8).prop_map(|b| vec![b] ).boxed(),
+        InterruptedSequence => (multibyte_utf8(2), any::<prop::sample::Index>(), interrupting_byte())
+            .prop_map(|(u8c, index, interrupter)| {
+                let bytes = u8c.as_bytes();
+                let interrupt_at = 1 + index.index(bytes.len()-1);
+                let mut bytes = bytes[..interrupt_at].to_vec();
+                bytes.push(interrupter);
+                bytes
+            }).boxed(),
+        OverlongEncoding => prop_oneof![
+            (0x80..=0x9fu8).prop_flat_map(|second| with_continuation_bytes(vec![0xe0, second], 1) ),
+            (0x80..=0x8fu8).prop_flat_map(|second| with_continuation_bytes(vec![0xf0, second], 2) ),
+        ].boxed(),
+        Utf16ReservedCodepoint => (0xa0..=0xbfu8)
+            .prop_flat_map(|second| with_continuation_bytes(vec![0xed, second], 1) )
+            .boxed(),
+        TooHighCodepoint => (0x90..=0xbfu8)
+            .prop_flat_map(|second| with_continuation_bytes(vec![0xf4, second], 2) )
+            .boxed(),
+    }
+}
+
+/// Bytes that cannot continue a multi-byte sequence,
+/// but are not reported as `NonUtf8Byte` either.
+fn interrupting_byte() -> BoxedStrategy<u8> {
+    prop_oneof![0x00..=0x7fu8, 0xc2..=0xf4u8].boxed()
+}
+
+/// Generate valid UTF-8 with an invalid sequence for the given error kind
+/// inserted at the returned offset.
+///
+/// `TooFewBytes` sequences are placed at the end,
+/// while the other kinds are followed by more valid UTF-8.
+pub fn utf8_with_error(kind: Utf8ErrorKind) -> BoxedStrategy<(Vec<u8>, usize)> {
+    let suffix = match kind {
+        Utf8ErrorKind::TooFewBytes => Just(String::new()).boxed(),
+        _ => valid_utf8(),
+    };
+    (valid_utf8(), invalid_utf8(kind), suffix).prop_map(|(prefix, invalid, suffix)| {
+        let offset = prefix.len();
+        ([prefix.as_bytes(), &invalid[..], suffix.as_bytes()].concat(), offset)
+    }).boxed()
+}
+
+/// Generate sequences for which
+/// [`Utf16CharDecoder`](../iterator/struct.Utf16CharDecoder.html)
+/// returns the given error as the first item.
+///
+/// `Incomplete` sequences must be at the end of the slice to produce that error,
+/// and are otherwise reported as `UnmatchedLeadingSurrogate` or become valid.
+pub fn invalid_utf16(error: Utf16PairError) -> BoxedStrategy<Vec<u16>> {
+    match error {
+        Utf16PairError::UnexpectedTrailingSurrogate => (0xdc00..=0xdfffu16).prop_map(|unit| vec![unit] ).boxed(),
+        Utf16PairError::UnmatchedLeadingSurrogate => {
+            (0xd800..=0xdbffu16, prop_oneof![0..=0xdbffu16, 0xe000..=0xffffu16])
+                .prop_map(|(leading, unit)| vec![leading, unit] )
+                .boxed()
+        }
+        Utf16PairError::Incomplete => (0xd800..=0xdbffu16).prop_map(|unit| vec![unit] ).boxed(),
+    }
+}
+
+/// Generate valid UTF-16 with an invalid sequence for the given error
+/// inserted at the returned offset.
+///
+/// `Incomplete` sequences are placed at the end,
+/// while the other errors are followed by more valid UTF-16.
+pub fn utf16_with_error(error: Utf16PairError) -> BoxedStrategy<(Vec<u16>, usize)> {
+    let suffix = match error {
+        Utf16PairError::Incomplete => Just(Vec::new()).boxed(),
+        _ => valid_utf16(),
+    };
+    (valid_utf16(), invalid_utf16(error), suffix).prop_map(|(prefix, invalid, suffix)| {
+        let offset = prefix.len();
+        ([&prefix[..], &invalid[..], &suffix[..]].concat(), offset)
+    }).boxed()
+}
@@ -12,6 +12,7 @@ use crate::utf16_char::Utf16Char;
 use crate::utf8_iterators::*;
 use crate::utf16_iterators::*;
 use crate::decoding_iterators::*;
+use crate::utf8_validator::decode_utf8_start;
 use crate::error::*;
 use crate::error::Utf8ErrorKind::*;
 extern crate core;
@@ -153,6 +154,10 @@ impl U16UtfExt for u16 {
 
 
 /// Extension trait for `char` that adds methods for converting to and from UTF-8 or UTF-16.
+///
+/// Trait methods cannot be `const`, but the constructors of
+/// [`Utf8Char`](struct.Utf8Char.html) and [`Utf16Char`](struct.Utf16Char.html)
+/// can be used together with their `.to_char()` in `const fn`s instead.
 pub trait CharExt: Sized {
     /// Get the UTF-8 representation of this codepoint.
     ///
@@ -411,51 +416,15 @@ impl CharExt for char {
 
 
     fn from_utf8_slice_start(src: &[u8]) -> Result<(Self,usize),Utf8Error> {
-        let first = match src.first() {
-            Some(first) => *first,
-            None => return Err(Utf8Error{ kind: TooFewBytes }),
-        };
-        let bytes = match first.extra_utf8_bytes() {
-            Err(e)    => return Err(e),
-            Ok(0)     => return Ok((first as char, 1)),
-            Ok(extra) if extra >= src.len()
-                      => return Err(Utf8Error{ kind: TooFewBytes }),
-            Ok(extra) => &src[..=extra],
-        };
-        if bytes.iter().skip(1).any(|&b| (b >> 6) != 0b10 ) {
-            Err(Utf8Error{ kind: InterruptedSequence })
-        } else if overlong(bytes[0], bytes[1]) {
-            Err(Utf8Error{ kind: OverlongEncoding })
-        } else {
-            match char::from_u32_detailed(merge_nonascii_unchecked_utf8(bytes)) {
-                Ok(c) => Ok((c, bytes.len())),
-                Err(CodepointError::Utf16Reserved) => {
-                    Err(Utf8Error{ kind: Utf16ReservedCodepoint })
-                },
-                Err(CodepointError::TooHigh) => Err(Utf8Error{ kind: TooHighCodepoint }),
-            }
+        match decode_utf8_start(src) {
+            // SAFETY: the validator rejects surrogates and too high codepoints
+            Ok((c, len)) => Ok((unsafe { char::from_u32_unchecked(c) }, len)),
+            Err(kind) => Err(Utf8Error{ kind }),
         }
     }
 
     fn from_utf8_array(utf8: [u8; 4]) -> Result<Self,Utf8Error> {
-        let src = match utf8[0].extra_utf8_bytes() {
-            Err(error) => return Err(error),
-            Ok(0)      => return Ok(utf8[0] as char),
-            Ok(extra)  => &utf8[..=extra],
-        };
-        if src[1..].iter().any(|&b| (b >> 6) != 0b10 ) {
-            Err(Utf8Error{ kind: InterruptedSequence })
-        } else if overlong(utf8[0], utf8[1]) {
-            Err(Utf8Error{ kind: OverlongEncoding })
-        } else {
-            match char::from_u32_detailed(merge_nonascii_unchecked_utf8(src)) {
-                Ok(c) => Ok(c),
-                Err(CodepointError::Utf16Reserved) => {
-                    Err(Utf8Error{ kind: Utf16ReservedCodepoint })
-                },
-                Err(CodepointError::TooHigh) => Err(Utf8Error{ kind: TooHighCodepoint }),
-            }
-        }
+        char::from_utf8_slice_start(&utf8).map(|(c,_)| c )
     }
 
     unsafe fn from_utf8_exact_slice_unchecked(src: &[u8]) -> Self {
@@ -562,19 +531,6 @@ impl CharExt for char {
     }
 }
 
-// Adapted from https://www.cl.cam.ac.uk/~mgk25/ucs/utf8_check.c
-fn overlong(first: u8, second: u8) -> bool {
-    if first < 0x80 {
-        false
-    } else if (first & 0xe0) == 0xc0 {
-        (first & 0xfe) == 0xc0
-    } else if (first & 0xf0) == 0xe0 {
-        first == 0xe0 && (second & 0xe0) == 0x80
-    } else {
-        first == 0xf0 && (second & 0xf0) == 0x80
-    }
-}
-
 /// Decodes the codepoint represented by a multi-byte UTF-8 sequence.
 ///
 /// Does not check that the codepoint is valid,
@@ -612,6 +568,57 @@ pub trait StrExt: AsRef<str> {
     fn utf8char_indices(&self) -> Utf8CharIndices;
     /// Equivalent to `.char_indices()` but produces `Utf16Char`s.
     fn utf16char_indices(&self) -> Utf16CharIndices;
+    /// Iterate over the `Utf8Char`s together with their byte offset,
+    /// UTF-16 offset, codepoint index, line and column.
+    ///
+    /// Lines are separated by `'\n'`, and both lines and columns start at zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use encode_unicode::{StrExt, Utf8Char};
+    /// use encode_unicode::iterator::{CharPosition, ColumnUnit};
+    ///
+    /// let mut iter = "a😀\r\nb".utf8char_positions(ColumnUnit::Utf16Units);
+    /// assert_eq!(iter.next(), Some((CharPosition {
+    ///     byte: 0, utf16: 0, codepoint: 0, line: 0, column: 0
+    /// }, Utf8Char::from('a'))));
+    /// assert_eq!(iter.next(), Some((CharPosition {
+    ///     byte: 1, utf16: 1, codepoint: 1, line: 0, column: 1
+    /// }, Utf8Char::from('😀'))));
+    /// assert_eq!(iter.nth(2), Some((CharPosition {
+    ///     byte: 7, utf16: 5, codepoint: 4, line: 1, column: 0
+    /// }, Utf8Char::from('b'))));
+    /// ```
+    fn utf8char_positions(&self,  column_unit: ColumnUnit) -> Utf8CharPositions<'_>;
+    /// Iterate over runs of ASCII characters as `str`s, and the other
+    /// characters as `Utf8Char`s.
+    ///
+    /// This makes it possible to copy mostly-ASCII text a run at a time
+    /// while still handling the other characters individually.
+    ///
+    /// # Examples
+    ///
+    #[cfg_attr(feature="alloc", doc=" ```")]
+    #[cfg_attr(not(feature="alloc"), doc=" ```no_compile")]
+    /// use encode_unicode::{StrExt, Utf8Char};
+    /// use encode_unicode::iterator::AsciiRunOrChar;
+    ///
+    /// let mut escaped = String::new();
+    /// for item in "Smørbrød (1 stk.)".ascii_runs() {
+    ///     match item {
+    ///         AsciiRunOrChar::Ascii(run) => escaped.push_str(run),
+    ///         AsciiRunOrChar::Char(c) => escaped.push_str(&format!("&#{};", c.to_char() as u32)),
+    ///     }
+    /// }
+    /// assert_eq!(escaped, "Sm&#248;rbr&#248;d (1 stk.)");
+    ///
+    /// let mut iter = "ab€".ascii_runs();
+    /// assert_eq!(iter.next(), Some(AsciiRunOrChar::Ascii("ab")));
+    /// assert_eq!(iter.next(), Some(AsciiRunOrChar::Char(Utf8Char::from('€'))));
+    /// assert_eq!(iter.next(), None);
+    /// ```
+    fn ascii_runs(&self) -> AsciiRuns<'_>;
 }
 
 impl StrExt for str {
@@ -627,6 +634,12 @@ impl StrExt for str {
     fn utf16char_indices(&self) -> Utf16CharIndices {
         Utf16CharIndices::from(self)
     }
+    fn utf8char_positions(&self,  column_unit: ColumnUnit) -> Utf8CharPositions<'_> {
+        Utf8CharPositions::from(self).with_column_unit(column_unit)
+    }
+    fn ascii_runs(&self) -> AsciiRuns<'_> {
+        AsciiRuns::from(self)
+    }
 }
 
 #[cfg(feature="ascii")]
@@ -643,6 +656,12 @@ impl StrExt for AsciiStr {
     fn utf16char_indices(&self) -> Utf16CharIndices {
         Utf16CharIndices::from(self.as_str())
     }
+    fn utf8char_positions(&self,  column_unit: ColumnUnit) -> Utf8CharPositions<'_> {
+        Utf8CharPositions::from(self.as_str()).with_column_unit(column_unit)
+    }
+    fn ascii_runs(&self) -> AsciiRuns<'_> {
+        AsciiRuns::from(self.as_str())
+    }
 }
 
 
@@ -682,8 +701,8 @@ pub trait IterExt: Iterator+Sized {
     ///
     /// From iterator of references:
     ///
-    #[cfg_attr(feature="std", doc=" ```")]
-    #[cfg_attr(not(feature="std"), doc=" ```no_compile")]
+    #[cfg_attr(feature="alloc", doc=" ```")]
+    #[cfg_attr(not(feature="alloc"), doc=" ```no_compile")]
     /// use encode_unicode::{IterExt, StrExt, Utf8Char};
     ///
     /// let chars: Vec<Utf8Char> = "💣 bomb 💣".utf8chars().collect();
@@ -735,8 +754,8 @@ pub trait IterExt: Iterator+Sized {
     ///
     /// From iterator of references:
     ///
-    #[cfg_attr(feature="std", doc=" ```")]
-    #[cfg_attr(not(feature="std"), doc=" ```no_compile")]
+    #[cfg_attr(feature="alloc", doc=" ```")]
+    #[cfg_attr(not(feature="alloc"), doc=" ```no_compile")]
     /// use encode_unicode::{IterExt, StrExt, Utf16Char};
     ///
     /// // (💣 takes two units)
@@ -775,8 +794,8 @@ pub trait IterExt: Iterator+Sized {
     /// ```
     ///
     /// Collect everything up until the first error into a string:
-    #[cfg_attr(feature="std", doc=" ```")]
-    #[cfg_attr(not(feature="std"), doc=" ```no_compile")]
+    #[cfg_attr(feature="alloc", doc=" ```")]
+    #[cfg_attr(not(feature="alloc"), doc=" ```no_compile")]
     /// use encode_unicode::iterator::Utf8CharMerger;
     /// let mut good = String::new();
     /// for r in Utf8CharMerger::from(b"foo\xcc\xbbbar\xcc\xddbaz") {
@@ -790,8 +809,8 @@ pub trait IterExt: Iterator+Sized {
     /// ```
     ///
     /// Abort decoding on error:
-    #[cfg_attr(feature="std", doc=" ```")]
-    #[cfg_attr(not(feature="std"), doc=" ```no_compile")]
+    #[cfg_attr(feature="alloc", doc=" ```")]
+    #[cfg_attr(not(feature="alloc"), doc=" ```no_compile")]
     /// use encode_unicode::{IterExt, Utf8Char};
     /// use encode_unicode::error::{Utf8Error, Utf8ErrorKind};
     ///
@@ -811,8 +830,8 @@ pub trait IterExt: Iterator+Sized {
     /// # Examples
     ///
     /// Replace errors with '�':
-    #[cfg_attr(feature="std", doc=" ```")]
-    #[cfg_attr(not(feature="std"), doc=" ```no_compile")]
+    #[cfg_attr(feature="alloc", doc=" ```")]
+    #[cfg_attr(not(feature="alloc"), doc=" ```no_compile")]
     /// use encode_unicode::{IterExt, Utf16Char};
     ///
     /// let slice = &['a' as u16, 0xdf00, 0xd83c, 0xdca0][..];
@@ -880,8 +899,8 @@ pub trait SliceExt: Index<RangeFull> {
     /// # Examples
     ///
     /// Get the index and error type of the first error:
-    #[cfg_attr(feature="std", doc=" ```")]
-    #[cfg_attr(not(feature="std"), doc=" ```no_compile")]
+    #[cfg_attr(feature="alloc", doc=" ```")]
+    #[cfg_attr(not(feature="alloc"), doc=" ```no_compile")]
     /// use encode_unicode::{SliceExt, Utf8Char, error::Utf8ErrorKind};
     ///
     /// let slice = b"ab\0\xe0\xbc\xa9 \xf3\x80\x77";
@@ -889,7 +908,7 @@ pub trait SliceExt: Index<RangeFull> {
     ///     .map(|(offset,r,length)| r.map_err(|e| (offset,e.kind(),length) ) )
     ///     .collect::<Result<String,(usize,Utf8ErrorKind,usize)>>();
     ///
-    /// assert_eq!(result, Err((7, Utf8ErrorKind::TooFewBytes, 1)));
+    /// assert_eq!(result, Err((7, Utf8ErrorKind::InterruptedSequence, 1)));
     /// ```
     ///
     /// ```
@@ -908,8 +927,8 @@ pub trait SliceExt: Index<RangeFull> {
     /// assert_eq!(fixed_size, chars);
     /// ```
     ///
-    #[cfg_attr(feature="std", doc=" ```")]
-    #[cfg_attr(not(feature="std"), doc=" ```no_compile")]
+    #[cfg_attr(feature="alloc", doc=" ```")]
+    #[cfg_attr(not(feature="alloc"), doc=" ```no_compile")]
     /// use encode_unicode::{SliceExt, Utf8Char, error::Utf8ErrorKind};
     ///
     /// let bytes = b"\xfa-\xf4\x8f\xee\xa1\x8f-\xed\xa9\x87\xf0\xcc\xbb";
@@ -935,7 +954,7 @@ pub trait SliceExt: Index<RangeFull> {
     ///     ( 8, Utf8ErrorKind::Utf16ReservedCodepoint),
     ///     ( 9, Utf8ErrorKind::UnexpectedContinuationByte),
     ///     (10, Utf8ErrorKind::UnexpectedContinuationByte),
-    ///     (11, Utf8ErrorKind::TooFewBytes), // (but it was not the last element returned!)
+    ///     (11, Utf8ErrorKind::InterruptedSequence), // even though there aren't enough bytes
     /// ]);
     /// ```
     fn utf8char_indices(&self) -> Utf8CharDecoder where Self::Output: Borrow<[u8]>;
@@ -952,8 +971,8 @@ pub trait SliceExt: Index<RangeFull> {
     ///
     /// # Examples
     ///
-    #[cfg_attr(feature="std", doc=" ```")]
-    #[cfg_attr(not(feature="std"), doc=" ```no_compile")]
+    #[cfg_attr(feature="alloc", doc=" ```")]
+    #[cfg_attr(not(feature="alloc"), doc=" ```no_compile")]
     /// use encode_unicode::{SliceExt, Utf8Char};
     ///
     /// let slice = &['a' as u16, 0xdf00, 0xd83c, 0xdca0][..];
@@ -1000,6 +1019,102 @@ pub trait SliceExt: Index<RangeFull> {
     /// assert_eq!(iter.as_slice(), [])
     /// ```
     fn utf16char_indices(&self) -> Utf16CharDecoder where Self::Output: Borrow<[u16]>;
+
+    /// Decode `u8` slices as UTF-8 and iterate over the codepoints as
+    /// `Utf8Char`s, together with their byte offset, UTF-16 offset, codepoint
+    /// index, line and column.
+    ///
+    /// The iterator produces `(CharPosition,Result<Utf8Char,Utf8Error>,usize)`,
+    /// where the last `usize` is the length in bytes, which is always 1 for errors.
+    ///
+    /// Every invalid byte is counted as one codepoint and one UTF-16 unit,
+    /// as if it had been replaced with U+FFFD REPLACEMENT CHARACTER.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use encode_unicode::SliceExt;
+    /// use encode_unicode::iterator::ColumnUnit;
+    ///
+    /// let slice = b"if x\n\t\xff= \xe2\x88\x9e";
+    /// let mut iter = slice.utf8char_positions(ColumnUnit::Codepoints);
+    /// let (error_position, error, _) = iter.nth(6).unwrap();
+    /// assert!(error.is_err());
+    /// assert_eq!((error_position.line, error_position.column), (1, 1));
+    /// let (infinity_position, _, len) = iter.last().unwrap();
+    /// assert_eq!((infinity_position.byte, len), (9, 3));
+    /// assert_eq!((infinity_position.line, infinity_position.column), (1, 4));
+    /// assert_eq!(infinity_position.codepoint, 9);
+    /// ```
+    fn utf8char_positions(&self,  column_unit: ColumnUnit) -> Utf8CharPositionDecoder<'_>
+    where Self::Output: Borrow<[u8]>;
+
+    /// Split `u8` slices into runs of valid UTF-8 and the invalid sequences
+    /// between them.
+    ///
+    /// The iterator produces `(&str, Option<(&[u8], Utf8Error)>)`,
+    /// where the `str` might be empty and the invalid part is only `None`
+    /// for the last run.
+    ///
+    /// Invalid sequences are split the same way as by `std`'s `Utf8Chunks`
+    /// and `String::from_utf8_lossy()`: Each is the longest start of a
+    /// valid sequence, but at least one byte, so it is a good place to insert
+    /// one U+FFFD REPLACEMENT CHARACTER.
+    /// The error is the same as `utf8char_indices()` produces for its first byte.
+    ///
+    /// (This method isn't called `utf8_chunks()` because that name would be
+    /// shadowed by the inherent method on `[u8]` which returns `std`'s type.)
+    ///
+    /// # Examples
+    ///
+    #[cfg_attr(feature="alloc", doc=" ```")]
+    #[cfg_attr(not(feature="alloc"), doc=" ```no_compile")]
+    /// use encode_unicode::SliceExt;
+    /// use encode_unicode::error::Utf8ErrorKind;
+    ///
+    /// let bytes = b"L\xf6we \xf0\x9f\xa6\x81 \xf0\x9f\xa6";
+    /// let mut lossy = String::new();
+    /// let mut errors = Vec::new();
+    /// for (valid, invalid) in bytes.utf8char_chunks() {
+    ///     lossy.push_str(valid);
+    ///     if let Some((invalid, error)) = invalid {
+    ///         lossy.push('\u{fffd}');
+    ///         errors.push((invalid, error.kind()));
+    ///     }
+    /// }
+    /// assert_eq!(lossy, String::from_utf8_lossy(bytes));
+    /// assert_eq!(lossy, "L\u{fffd}we 🦁 \u{fffd}");
+    /// assert_eq!(errors, [
+    ///     (&b"\xf6"[..], Utf8ErrorKind::NonUtf8Byte),
+    ///     (&b"\xf0\x9f\xa6"[..], Utf8ErrorKind::TooFewBytes),
+    /// ]);
+    /// ```
+    fn utf8char_chunks(&self) -> Utf8Chunks<'_> where Self::Output: Borrow<[u8]>;
+
+    /// Split `u16` slices into runs of well-formed UTF-16 and the invalid
+    /// units between them.
+    ///
+    /// The iterator produces `(&[u16], Option<(u16, Utf16PairError)>)`,
+    /// where the run might be empty and the invalid unit is only `None`
+    /// for the last run.
+    /// Runs never start with a trailing surrogate or end with a leading surrogate.
+    ///
+    /// # Examples
+    ///
+    #[cfg_attr(feature="alloc", doc=" ```")]
+    #[cfg_attr(not(feature="alloc"), doc=" ```no_compile")]
+    /// use encode_unicode::SliceExt;
+    /// use encode_unicode::error::Utf16PairError;
+    ///
+    /// let units = [0x48, 0x69, 0xdc00, 0xd83d, 0xde00, 0xd800];
+    /// let chunks = units.utf16char_chunks().collect::<Vec<_>>();
+    /// assert_eq!(chunks, [
+    ///     (&units[..2], Some((0xdc00, Utf16PairError::UnexpectedTrailingSurrogate))),
+    ///     (&units[3..5], Some((0xd800, Utf16PairError::Incomplete))),
+    /// ]);
+    /// assert_eq!(String::from_utf16(chunks[1].0).unwrap(), "😀");
+    /// ```
+    fn utf16char_chunks(&self) -> Utf16Chunks<'_> where Self::Output: Borrow<[u16]>;
 }
 
 impl<S: ?Sized+Index<RangeFull>> SliceExt for S {
@@ -1009,4 +1124,14 @@ impl<S: ?Sized+Index<RangeFull>> SliceExt for S {
     fn utf16char_indices(&self) -> Utf16CharDecoder where Self::Output: Borrow<[u16]> {
         Utf16CharDecoder::from(self[..].borrow())
     }
+    fn utf8char_positions(&self,  column_unit: ColumnUnit) -> Utf8CharPositionDecoder<'_>
+    where Self::Output: Borrow<[u8]> {
+        Utf8CharPositionDecoder::from(self[..].borrow()).with_column_unit(column_unit)
+    }
+    fn utf8char_chunks(&self) -> Utf8Chunks<'_> where Self::Output: Borrow<[u8]> {
+        Utf8Chunks::from(self[..].borrow())
+    }
+    fn utf16char_chunks(&self) -> Utf16Chunks<'_> where Self::Output: Borrow<[u16]> {
+        Utf16Chunks::from(self[..].borrow())
+    }
 }
@@ -0,0 +1,333 @@
+/* Copyright 2022 Torbjørn Birch Moltu
+ *
+ * Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+ * http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+ * http://opensource.org/licenses/MIT>, at your option. This file may not be
+ * copied, modified, or distributed except according to those terms.
+ */
+
+//! Converting whole slices between UTF-8 and UTF-16.
+//!
+//! Runs of ASCII are converted sixteen elements at a time, with SSE2
+//! instructions on x86 and `x86_64` when the target has it, and with
+//! word-sized checks elsewhere. There is no AVX2 or NEON path.
+//! Other codepoints are converted one at a time, so text that is mostly
+//! not ASCII is converted at about the speed of a `char` loop.
+//!
+//! The functions return `(read, written, result)`, where `read` and `written`
+//! is how much of the source and destination has been processed.
+//! `result` is only `Ok` if the whole source has been converted.
+
+use crate::errors::{Utf8Error, Utf16PairError, TranscodeError};
+use crate::errors::TranscodeError::*;
+use crate::errors::Utf16PairError::*;
+use crate::traits::CharExt;
+
+/// Number of ASCII elements that are checked and converted together.
+const ASCII_BLOCK: usize = 16;
+
+#[cfg(all(target_arch="x86", target_feature="sse2"))]
+use core::arch::x86::*;
+#[cfg(all(target_arch="x86_64", target_feature="sse2"))]
+use core::arch::x86_64::*;
+
+/// Convert blocks of ASCII from the start of `src` until a block contains
+/// something else or either slice ends, and return the number of bytes converted.
+#[cfg(all(any(target_arch="x86", target_arch="x86_64"), target_feature="sse2"))]
+fn widen_ascii(src: &[u8],  dst: &mut[u16]) -> usize {
+    let mut done = 0;
+    while done + ASCII_BLOCK <= src.len()  &&  done + ASCII_BLOCK <= dst.len() {
+        // SAFETY: both slices have at least ASCII_BLOCK elements after done,
+        // and the loads and stores don't need to be aligned.
+        unsafe {
+            let bytes = _mm_loadu_si128(src.as_ptr().add(done) as *const __m128i);
+            if _mm_movemask_epi8(bytes) != 0 {
+                break;
+            }
+            let zero = _mm_setzero_si128();
+            let units = dst.as_mut_ptr().add(done) as *mut __m128i;
+            _mm_storeu_si128(units, _mm_unpacklo_epi8(bytes, zero));
+            _mm_storeu_si128(units.add(1), _mm_unpackhi_epi8(bytes, zero));
+        }
+        done += ASCII_BLOCK;
+    }
+    done
+}
+
+/// Convert blocks of ASCII from the start of `src` until a block contains
+/// something else or either slice ends, and return the number of units converted.
+#[cfg(all(any(target_arch="x86", target_arch="x86_64"), target_feature="sse2"))]
+fn narrow_ascii(src: &[u16],  dst: &mut[u8]) -> usize {
+    let mut done = 0;
+    while done + ASCII_BLOCK <= src.len()  &&  done + ASCII_BLOCK <= dst.len() {
+        // SAFETY: both slices have at least ASCII_BLOCK elements after done,
+        // and the loads and stores don't need to be aligned.
+        unsafe {
+            let first = _mm_loadu_si128(src.as_ptr().add(done) as *const __m128i);
+            let second = _mm_loadu_si128(src.as_ptr().add(done+8) as *const __m128i);
+            // -0x80 is 0xff80, the bits that are set in non-ASCII units
+            let non_ascii = _mm_and_si128(_mm_or_si128(first, second), _mm_set1_epi16(-0x80));
+            if _mm_movemask_epi8(_mm_cmpeq_epi16(non_ascii, _mm_setzero_si128())) != 0xffff {
+                break;
+            }
+            let bytes = dst.as_mut_ptr().add(done) as *mut __m128i;
+            _mm_storeu_si128(bytes, _mm_packus_epi16(first, second));
+        }
+        done += ASCII_BLOCK;
+    }
+    done
+}
+
+/// Convert blocks of ASCII from the start of `src` until a block contains
+/// something else or either slice ends, and return the number of bytes converted.
+#[cfg(not(all(any(target_arch="x86", target_arch="x86_64"), target_feature="sse2")))]
+fn widen_ascii(src: &[u8],  dst: &mut[u16]) -> usize {
+    const WORD: usize = core::mem::size_of::<usize>();
+    const HIGH: usize = usize::MAX / 0xff * 0x80;
+    let mut done = 0;
+    while done + ASCII_BLOCK <= src.len()  &&  done + ASCII_BLOCK <= dst.len() {
+        let block = &src[done..done+ASCII_BLOCK];
+        let mut combined = 0;
+        for word in block.chunks_exact(WORD) {
+            let mut bytes = [0; WORD];
+            bytes.copy_from_slice(word);
+            combined |= usize::from_ne_bytes(bytes);
+        }
+        if combined & HIGH != 0 {
+            break;
+        }
+        for (unit, &byte) in dst[done..done+ASCII_BLOCK].iter_mut().zip(block) {
+            *unit = byte as u16;
+        }
+        done += ASCII_BLOCK;
+    }
+    done
+}
+
+/// Convert blocks of ASCII from the start of `src` until a block contains
+/// something else or either slice ends, and return the number of units converted.
+#[cfg(not(all(any(target_arch="x86", target_arch="x86_64"), target_feature="sse2")))]
+fn narrow_ascii(src: &[u16],  dst: &mut[u8]) -> usize {
+    let mut done = 0;
+    while done + ASCII_BLOCK <= src.len()  &&  done + ASCII_BLOCK <= dst.len() {
+        let block = &src[done..done+ASCII_BLOCK];
+        if block.iter().fold(0, |acc, &unit| acc | unit ) >= 0x80 {
+            break;
+        }
+        for (byte, &unit) in dst[done..done+ASCII_BLOCK].iter_mut().zip(block) {
+            *byte = unit as u8;
+        }
+        done += ASCII_BLOCK;
+    }
+    done
+}
+
+/// Decode a codepoint that fits in one UTF-16 unit from the start of `src`,
+/// without the checks needed to tell what's wrong with invalid sequences.
+#[inline]
+fn decode_bmp_utf8(src: &[u8]) -> Option<(u16, usize)> {
+    match *src {
+        [b0 @ 0x00..=0x7f, ..] => Some((b0 as u16, 1)),
+        [b0 @ 0xc2..=0xdf, b1, ..] if b1 & 0xc0 == 0x80 => {
+            Some(((b0 as u16 & 0x1f) << 6 | (b1 as u16 & 0x3f), 2))
+        },
+        [b0 @ 0xe0..=0xef, b1, b2, ..] if b1 & 0xc0 == 0x80  &&  b2 & 0xc0 == 0x80 => {
+            let unit = (b0 as u16 & 0x0f) << 12 | (b1 as u16 & 0x3f) << 6 | (b2 as u16 & 0x3f);
+            // reject overlong encodings and surrogates
+            if unit >= 0x800  &&  unit & 0xf800 != 0xd800 {Some((unit, 3))} else {None}
+        },
+        _ => None,
+    }
+}
+
+/// Convert a possibly invalid UTF-8 slice to UTF-16, until the end of the
+/// source, an error or a full destination is reached.
+///
+/// A `dst` with room for at least two units can always fit the next codepoint.
+///
+/// Only runs of ASCII are converted several bytes at a time (with SSE2 if the
+/// target has it); other codepoints are decoded and encoded one at a time.
+///
+/// # Errors
+///
+/// The returned number of bytes read is the offset of the codepoint or
+/// sequence that could not be converted:
+///
+/// * `Invalid` with [`TooFewBytes`](../error/enum.Utf8ErrorKind.html#variant.TooFewBytes)
+///   is returned if `src` ends in the middle of a codepoint, which means that
+///   `src[read..]` should be kept and prepended to the next chunk of input.
+/// * `Invalid` with another kind if the sequence is invalid.
+/// * `OutputFull` if the codepoint needs more units than there is room for
+///   in `dst[written..]`.
+///
+/// # Examples
+///
+/// Converting in chunks:
+///
+/// ```
+/// use encode_unicode::slice::transcode_utf8_to_utf16;
+/// use encode_unicode::error::{TranscodeError, Utf8ErrorKind};
+///
+/// let mut buf = [0u16; 5];
+/// let src = "en €, 😀".as_bytes();
+/// let (read, written, result) = transcode_utf8_to_utf16(&src[..5], &mut buf);
+/// assert_eq!((read, written), (3, 3));// stopped before the incomplete '€'
+/// match result {
+///     Err(TranscodeError::Invalid(e)) => assert_eq!(e.kind(), Utf8ErrorKind::TooFewBytes),
+///     _ => unreachable!(),
+/// }
+///
+/// let (more_read, more_written, result) = transcode_utf8_to_utf16(&src[read..], &mut buf[written..]);
+/// assert_eq!((more_read, more_written), (4, 2));
+/// assert_eq!(result, Err(TranscodeError::OutputFull { needed: 1 }));
+/// assert_eq!(buf, [b'e' as u16, b'n' as u16, b' ' as u16, '€' as u16, b',' as u16]);
+/// ```
+pub fn transcode_utf8_to_utf16(src: &[u8],  dst: &mut[u16])
+-> (usize, usize, Result<(), TranscodeError<Utf8Error>>) {
+    let (mut read, mut written) = (0, 0);
+    let mut no_blocks_before = 0;
+    while read < src.len() {
+        if read >= no_blocks_before  &&  src[read] < 0x80 {
+            let ascii = widen_ascii(&src[read..], &mut dst[written..]);
+            read += ascii;
+            written += ascii;
+            if read == src.len() {
+                break;
+            }
+            // Don't check for a block of ASCII in every gap between
+            // codepoints in mixed text.
+            no_blocks_before = read + ASCII_BLOCK;
+        }
+        // a single codepoint, or ASCII that didn't fill a block
+        if let Some((unit, len)) = decode_bmp_utf8(&src[read..]) {
+            if written == dst.len() {
+                return (read, written, Err(OutputFull { needed: 1 }));
+            }
+            dst[written] = unit;
+            read += len;
+            written += 1;
+            continue;
+        }
+        // four-byte codepoints, and finding out what's wrong with invalid sequences
+        let (c, len) = match char::from_utf8_slice_start(&src[read..]) {
+            Ok(decoded) => decoded,
+            Err(e) => return (read, written, Err(Invalid(e))),
+        };
+        let needed = c.len_utf16();
+        if written + needed > dst.len() {
+            return (read, written, Err(OutputFull { needed }));
+        }
+        c.encode_utf16(&mut dst[written..]);
+        read += len;
+        written += needed;
+    }
+    (read, written, Ok(()))
+}
+
+/// Convert a possibly invalid UTF-16 slice to UTF-8, until the end of the
+/// source, an error or a full destination is reached.
+///
+/// A `dst` with room for at least four bytes can always fit the next codepoint.
+///
+/// Only runs of ASCII are converted several units at a time (with SSE2 if the
+/// target has it); other codepoints are decoded and encoded one at a time.
+///
+/// # Errors
+///
+/// The returned number of units read is the offset of the codepoint or
+/// unpaired surrogate that could not be converted:
+///
+/// * `Invalid` with [`Incomplete`](../error/enum.Utf16PairError.html#variant.Incomplete)
+///   is returned if `src` ends with a leading surrogate, which means that it
+///   should be kept and prepended to the next chunk of input.
+/// * `Invalid` with another variant for unpaired surrogates.
+/// * `OutputFull` if the codepoint needs more bytes than there is room for
+///   in `dst[written..]`.
+///
+/// # Examples
+///
+/// ```
+/// use encode_unicode::slice::transcode_utf16_to_utf8;
+/// use encode_unicode::error::{TranscodeError, Utf16PairError};
+///
+/// let src = [0x41, 0xd83d, 0xde00, 0xdc00];
+/// let mut buf = [0u8; 4];
+/// let (read, written, result) = transcode_utf16_to_utf8(&src, &mut buf);
+/// assert_eq!((read, written), (1, 1));
+/// assert_eq!(result, Err(TranscodeError::OutputFull { needed: 4 }));
+///
+/// let mut buf = [0u8; 8];
+/// let (read, written, result) = transcode_utf16_to_utf8(&src, &mut buf);
+/// assert_eq!((read, written), (3, 5));
+/// assert_eq!(result, Err(TranscodeError::Invalid(Utf16PairError::UnexpectedTrailingSurrogate)));
+/// assert_eq!(&buf[..written], "A😀".as_bytes());
+/// ```
+pub fn transcode_utf16_to_utf8(src: &[u16],  dst: &mut[u8])
+-> (usize, usize, Result<(), TranscodeError<Utf16PairError>>) {
+    let (mut read, mut written) = (0, 0);
+    let mut no_blocks_before = 0;
+    while read < src.len() {
+        if read >= no_blocks_before  &&  src[read] < 0x80 {
+            let ascii = narrow_ascii(&src[read..], &mut dst[written..]);
+            read += ascii;
+            written += ascii;
+            if read == src.len() {
+                break;
+            }
+            // Don't check for a block of ASCII in every gap between
+            // codepoints in mixed text.
+            no_blocks_before = read + ASCII_BLOCK;
+        }
+        // a single codepoint, or ASCII that didn't fill a block
+        let first = src[read];
+        let (c, len) = match first {
+            0x0000..=0x007f => {
+                if written == dst.len() {
+                    return (read, written, Err(OutputFull { needed: 1 }));
+                }
+                dst[written] = first as u8;
+                read += 1;
+                written += 1;
+                continue;
+            },
+            0x0080..=0x07ff => {
+                if written + 2 > dst.len() {
+                    return (read, written, Err(OutputFull { needed: 2 }));
+                }
+                dst[written] = 0xc0 | (first >> 6) as u8;
+                dst[written+1] = 0x80 | (first & 0x3f) as u8;
+                read += 1;
+                written += 2;
+                continue;
+            },
+            0xd800..=0xdbff => match src.get(read+1) {
+                Some(&second) if second & 0xfc00 == 0xdc00 => {
+                    (char::from_utf16_array_unchecked([first, second]), 2)
+                },
+                Some(_) => return (read, written, Err(Invalid(UnmatchedLeadingSurrogate))),
+                None => return (read, written, Err(Invalid(Incomplete))),
+            },
+            0xdc00..=0xdfff => return (read, written, Err(Invalid(UnexpectedTrailingSurrogate))),
+            _ => {
+                if written + 3 > dst.len() {
+                    return (read, written, Err(OutputFull { needed: 3 }));
+                }
+                dst[written] = 0xe0 | (first >> 12) as u8;
+                dst[written+1] = 0x80 | ((first >> 6) & 0x3f) as u8;
+                dst[written+2] = 0x80 | (first & 0x3f) as u8;
+                read += 1;
+                written += 3;
+                continue;
+            },
+        };
+        // a surrogate pair
+        let needed = c.len_utf8();
+        if written + needed > dst.len() {
+            return (read, written, Err(OutputFull { needed }));
+        }
+        c.encode_utf8(&mut dst[written..]);
+        read += len;
+        written += needed;
+    }
+    (read, written, Ok(()))
+}
@@ -0,0 +1,234 @@
+/* Copyright 2022 Torbjørn Birch Moltu
+ *
+ * Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+ * http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+ * http://opensource.org/licenses/MIT>, at your option. This file may not be
+ * copied, modified, or distributed except according to those terms.
+ */
+
+//! Cutting text to fit in a limited number of bytes or units.
+//!
+//! All functions return a prefix of the input, which is the whole input if
+//! it fits. If the input doesn't fit, the prefix leaves room for `ellipsis`
+//! so that the caller can append it.
+//! Whether the text was truncated can be checked by comparing lengths.
+
+use crate::utf8_char::Utf8Char;
+use crate::utf16_char::Utf16Char;
+use crate::boundaries::CharBoundaryExt;
+
+/// Reduce the budget by the length of an ellipsis, if any.
+#[inline]
+fn reserve(max_len: usize,  ellipsis_len: Option<usize>) -> usize {
+    max_len.saturating_sub(ellipsis_len.unwrap_or(0))
+}
+
+/// Cut a `str` to at most `max_bytes` bytes, or `max_bytes` minus the length
+/// of `ellipsis` if it needs to be cut.
+///
+/// If the ellipsis is longer than `max_bytes`, an empty string is returned
+/// for inputs that don't fit.
+///
+/// # Examples
+///
+/// ```
+/// use encode_unicode::Utf8Char;
+/// use encode_unicode::slice::truncate_str;
+///
+/// assert_eq!(truncate_str("bløtkake", 3, None), "bl");
+/// assert_eq!(truncate_str("bløtkake", 9, None), "bløtkake");
+/// let ellipsis = Utf8Char::from('…');
+/// let truncated = truncate_str("bløtkake", 8, Some(ellipsis));
+/// assert_eq!(truncated, "bløt");
+/// assert_eq!(truncated.len() + ellipsis.len(), 8);
+/// ```
+pub fn truncate_str(s: &str,  max_bytes: usize,  ellipsis: Option<Utf8Char>) -> &str {
+    if s.len() <= max_bytes {
+        return s;
+    }
+    let max_bytes = reserve(max_bytes, ellipsis.map(|u8c| u8c.len() ));
+    &s[..s.as_bytes().floor_char_boundary(max_bytes)]
+}
+
+/// Cut a `str` so that it takes at most `max_units` units when encoded as UTF-16,
+/// or `max_units` minus the UTF-16 length of `ellipsis` if it needs to be cut.
+///
+/// The UTF-16 length is computed without allocating.
+///
+/// # Examples
+///
+/// ```
+/// use encode_unicode::Utf8Char;
+/// use encode_unicode::slice::truncate_str_to_utf16_len;
+///
+/// assert_eq!(truncate_str_to_utf16_len("ø😀", 2, None), "ø");
+/// assert_eq!(truncate_str_to_utf16_len("ø😀", 3, None), "ø😀");
+/// let ellipsis = Some(Utf8Char::from('…'));
+/// assert_eq!(truncate_str_to_utf16_len("øøøø", 3, ellipsis), "øø");
+/// ```
+pub fn truncate_str_to_utf16_len(s: &str,  max_units: usize,  ellipsis: Option<Utf8Char>)
+-> &str {
+    // no codepoint has more UTF-16 units than UTF-8 bytes
+    if s.len() <= max_units {
+        return s;
+    }
+    let reserved = reserve(max_units, ellipsis.map(|u8c| u8c.to_char().len_utf16() ));
+    let mut units = 0;
+    let mut end_within_reserved = 0;
+    for (offset, c) in s.char_indices() {
+        units += c.len_utf16();
+        if units > max_units {
+            return &s[..end_within_reserved];
+        } else if units <= reserved {
+            end_within_reserved = offset + c.len_utf8();
+        }
+    }
+    s
+}
+
+/// Cut a possibly invalid UTF-8 slice to at most `max_bytes` bytes, or
+/// `max_bytes` minus the length of `ellipsis` if it needs to be cut.
+///
+/// Invalid sequences are not split if they look like a codepoint,
+/// see [`CharBoundaryExt`](../trait.CharBoundaryExt.html) for details.
+///
+/// # Examples
+///
+/// ```
+/// use encode_unicode::slice::truncate_utf8;
+///
+/// assert_eq!(truncate_utf8(b"ab\xe2\x82\xac", 4, None), b"ab");
+/// assert_eq!(truncate_utf8(b"ab\xe2\x82\xff", 4, None), b"ab\xe2\x82");
+/// assert_eq!(truncate_utf8(b"ab\x80\x80\x80", 4, None), b"ab\x80\x80");
+/// ```
+pub fn truncate_utf8(bytes: &[u8],  max_bytes: usize,  ellipsis: Option<Utf8Char>) -> &[u8] {
+    if bytes.len() <= max_bytes {
+        return bytes;
+    }
+    let max_bytes = reserve(max_bytes, ellipsis.map(|u8c| u8c.len() ));
+    &bytes[..bytes.floor_char_boundary(max_bytes)]
+}
+
+/// Cut a possibly invalid UTF-8 slice so that it takes at most `max_units`
+/// units when encoded as UTF-16, or `max_units` minus the UTF-16 length of
+/// `ellipsis` if it needs to be cut.
+///
+/// The UTF-16 length is computed without allocating.
+/// Invalid bytes are grouped into sequences the way
+/// [`CharBoundaryExt`](../trait.CharBoundaryExt.html) does, and each invalid
+/// sequence is counted as one unit, which is the length of the
+/// U+FFFD REPLACEMENT CHARACTER it would be converted to.
+/// Four-byte sequences are counted as two units even if invalid.
+///
+/// # Examples
+///
+/// ```
+/// use encode_unicode::Utf8Char;
+/// use encode_unicode::slice::truncate_utf8_to_utf16_len;
+///
+/// let bytes = "ø😀".as_bytes();
+/// assert_eq!(truncate_utf8_to_utf16_len(bytes, 2, None), "ø".as_bytes());
+/// assert_eq!(truncate_utf8_to_utf16_len(bytes, 3, None), bytes);
+/// let invalid = b"\xff\xe2\x82x";
+/// assert_eq!(truncate_utf8_to_utf16_len(invalid, 2, None), b"\xff\xe2\x82");
+/// let ellipsis = Some(Utf8Char::from('…'));
+/// assert_eq!(truncate_utf8_to_utf16_len(invalid, 2, ellipsis), b"\xff");
+/// ```
+pub fn truncate_utf8_to_utf16_len(bytes: &[u8],  max_units: usize,  ellipsis: Option<Utf8Char>)
+-> &[u8] {
+    // no sequence has more UTF-16 units than bytes
+    if bytes.len() <= max_units {
+        return bytes;
+    }
+    let reserved = reserve(max_units, ellipsis.map(|u8c| u8c.to_char().len_utf16() ));
+    let mut units = 0;
+    let mut end_within_reserved = 0;
+    let mut i = 0;
+    while i < bytes.len() {
+        let next = bytes.next_char_start_after(i);
+        units += if next-i == 4 {2} else {1};
+        i = next;
+        if units > max_units {
+            return &bytes[..end_within_reserved];
+        } else if units <= reserved {
+            end_within_reserved = i;
+        }
+    }
+    bytes
+}
+
+/// Cut a possibly invalid UTF-16 slice to at most `max_units` units, or
+/// `max_units` minus the length of `ellipsis` if it needs to be cut.
+///
+/// Surrogate pairs are not split, but unpaired surrogates can be cut after.
+///
+/// # Examples
+///
+/// ```
+/// use encode_unicode::Utf16Char;
+/// use encode_unicode::slice::truncate_utf16;
+///
+/// let units = [0x41, 0xd83d, 0xde00];
+/// assert_eq!(truncate_utf16(&units, 2, None), &units[..1]);
+/// let ellipsis = Some(Utf16Char::from('…'));
+/// assert_eq!(truncate_utf16(&units, 2, ellipsis), &units[..1]);
+/// assert_eq!(truncate_utf16(&units, 3, ellipsis), &units[..]);
+/// ```
+pub fn truncate_utf16(units: &[u16],  max_units: usize,  ellipsis: Option<Utf16Char>)
+-> &[u16] {
+    if units.len() <= max_units {
+        return units;
+    }
+    let max_units = reserve(max_units, ellipsis.map(|u16c| u16c.len() ));
+    &units[..units.floor_char_boundary(max_units)]
+}
+
+/// Cut a possibly invalid UTF-16 slice so that it takes at most `max_bytes`
+/// bytes when encoded as UTF-8, or `max_bytes` minus the UTF-8 length of
+/// `ellipsis` if it needs to be cut.
+///
+/// The UTF-8 length is computed without allocating.
+/// Unpaired surrogates are counted as three bytes,
+/// which is the length of the U+FFFD REPLACEMENT CHARACTER they would
+/// normally be converted to, and of their WTF-8 encoding.
+///
+/// # Examples
+///
+/// ```
+/// use encode_unicode::Utf16Char;
+/// use encode_unicode::slice::truncate_utf16_to_utf8_len;
+///
+/// let units = [0x41, 0xe6, 0xd83d, 0xde00];
+/// assert_eq!(truncate_utf16_to_utf8_len(&units, 6, None), &units[..2]);
+/// assert_eq!(truncate_utf16_to_utf8_len(&units, 7, None), &units[..]);
+/// let ellipsis = Some(Utf16Char::from('…'));
+/// assert_eq!(truncate_utf16_to_utf8_len(&units, 6, ellipsis), &units[..2]);
+/// assert_eq!(truncate_utf16_to_utf8_len(&units, 5, ellipsis), &units[..1]);
+/// ```
+pub fn truncate_utf16_to_utf8_len(units: &[u16],  max_bytes: usize,  ellipsis: Option<Utf16Char>)
+-> &[u16] {
+    // no unit needs more than three bytes, and pairs need two bytes per unit.
+    if units.len() <= max_bytes / 3 {
+        return units;
+    }
+    let reserved = reserve(max_bytes, ellipsis.map(|u16c| u16c.to_char().len_utf8() ));
+    let mut bytes = 0;
+    let mut end_within_reserved = 0;
+    let mut i = 0;
+    while i < units.len() {
+        let (len, utf8_len) = match units[i] {
+            0x0000..=0x007f => (1, 1),
+            0x0080..=0x07ff => (1, 2),
+            0xd800..=0xdbff if !units.is_char_boundary(i+1) => (2, 4),
+            _ => (1, 3),
+        };
+        bytes += utf8_len;
+        i += len;
+        if bytes > max_bytes {
+            return &units[..end_within_reserved];
+        } else if bytes <= reserved {
+            end_within_reserved = i;
+        }
+    }
+    units
+}
@@ -7,30 +7,35 @@
  */
 
 use crate::utf16_iterators::Utf16Iterator;
-use crate::traits::{CharExt, U16UtfExt};
+use crate::traits::CharExt;
 use crate::utf8_char::Utf8Char;
 use crate::errors::{Utf16SliceError, Utf16ArrayError, Utf16TupleError};
-use crate::errors::{NonBmpError, EmptyStrError, FromStrError};
+use crate::errors::{NonBmpError, EmptyStrError, FromStrError, PackedCharError};
 extern crate core;
-use core::{hash,fmt};
+use core::{hash,fmt,mem};
+use core::num::NonZeroU32;
 use core::cmp::Ordering;
 use core::borrow::Borrow;
 use core::ops::Deref;
 use core::str::FromStr;
-#[cfg(feature="std")]
+#[cfg(feature="alloc")]
 use core::iter::FromIterator;
+#[cfg(feature="alloc")]
+use alloc::{vec::Vec, string::String};
 #[cfg(feature="ascii")]
 use core::char;
 #[cfg(feature="ascii")]
 extern crate ascii;
 #[cfg(feature="ascii")]
 use ascii::{AsciiChar,ToAsciiChar,ToAsciiCharError};
+#[cfg(feature="serde")]
+use serde::{de, Serialize, Serializer, Deserialize, Deserializer};
+#[cfg(feature="serde")]
+use serde::ser::SerializeSeq;
 
 
-// I don't think there is any good default value for char, but char does.
-#[derive(Default)]
 // char doesn't do anything more advanced than u32 for Eq/Ord, so we shouldn't either.
-// When it's a single unit, the second is zero, so Eq works.
+// When it's a single unit, the second is zero (or the marker of '\0'), so Eq works.
 // #[derive(Ord)] however, breaks on surrogate pairs.
 #[derive(PartialEq,Eq)]
 #[derive(Clone,Copy)]
@@ -38,9 +43,17 @@ use ascii::{AsciiChar,ToAsciiChar,ToAsciiCharError};
 
 /// An unicode codepoint stored as UTF-16.
 ///
-/// It can be borrowed as an `u16` slice, and has the same size as `char`.
+/// It can be borrowed as an `u16` slice, and has the same size as `char`.  
+/// `Option<Utf16Char>` also has the same size as `Option<char>`.
+#[repr(transparent)]
+#[cfg_attr(feature="zerocopy", derive(zerocopy::IntoBytes, zerocopy::Immutable, zerocopy::KnownLayout))]
 pub struct Utf16Char {
-    units: [u16; 2],
+    // The units in order, with the second zeroed if unused.
+    // '\0' would then be all zeros, so it is stored with the second unit set
+    // to 1 instead, which lets the compiler use zero as the niche for `None`.
+    // The marker doesn't have the most significant bit set,
+    // so it is never mistaken for a trailing surrogate by `len()`.
+    units: NonZeroU32,
 }
 
 
@@ -76,14 +89,14 @@ impl FromStr for Utf16Char {
 impl From<char> for Utf16Char {
     fn from(c: char) -> Self {
         let (first, second) = c.to_utf16_tuple();
-        Utf16Char{ units: [first, second.unwrap_or(0)] }
+        Utf16Char::from_padded([first, second.unwrap_or(0)])
     }
 }
 impl From<Utf8Char> for Utf16Char {
     fn from(utf8: Utf8Char) -> Utf16Char {
         let (b, utf8_len) = utf8.to_array();
         match utf8_len {
-            1 => Utf16Char{ units: [b[0] as u16, 0] },
+            1 => Utf16Char::from_padded([b[0] as u16, 0]),
             4 => {// need surrogate
                 let mut first = 0xd800 - (0x01_00_00u32 >> 10) as u16;
                 first += (b[0] as u16 & 0x07) << 8;
@@ -92,21 +105,21 @@ impl From<Utf8Char> for Utf16Char {
                 let mut second = 0xdc00;
                 second |= (b[2] as u16 & 0x0f) << 6;
                 second |=  b[3] as u16 & 0x3f;
-                Utf16Char{ units: [first, second] }
+                Utf16Char::from_padded([first, second])
             },
             _ => { // 2 or 3
                 let mut unit = ((b[0] as u16 & 0x1f) << 6) | (b[1] as u16 & 0x3f);
                 if utf8_len == 3 {
                     unit = (unit << 6) | (b[2] as u16 & 0x3f);
                 }
-                Utf16Char{ units: [unit, 0] }
+                Utf16Char::from_padded([unit, 0])
             },
         }
     }
 }
 impl From<Utf16Char> for char {
     fn from(uc: Utf16Char) -> char {
-        char::from_utf16_array_unchecked(uc.to_array())
+        uc.to_char()
     }
 }
 impl IntoIterator for Utf16Char {
@@ -118,26 +131,27 @@ impl IntoIterator for Utf16Char {
     }
 }
 
-#[cfg(feature="std")]
+#[cfg(feature="alloc")]
 impl Extend<Utf16Char> for Vec<u16> {
     fn extend<I:IntoIterator<Item=Utf16Char>>(&mut self,  iter: I) {
         let iter = iter.into_iter();
         self.reserve(iter.size_hint().0);
         for u16c in iter {
-            self.push(u16c.units[0]);
-            if u16c.units[1] != 0 {
-                self.push(u16c.units[1]);
+            let units = u16c.to_array();
+            self.push(units[0]);
+            if units[1] != 0 {
+                self.push(units[1]);
             }
         }
     }
 }
-#[cfg(feature="std")]
+#[cfg(feature="alloc")]
 impl<'a> Extend<&'a Utf16Char> for Vec<u16> {
     fn extend<I:IntoIterator<Item=&'a Utf16Char>>(&mut self,  iter: I) {
         self.extend(iter.into_iter().cloned())
     }
 }
-#[cfg(feature="std")]
+#[cfg(feature="alloc")]
 impl FromIterator<Utf16Char> for Vec<u16> {
     fn from_iter<I:IntoIterator<Item=Utf16Char>>(iter: I) -> Self {
         let mut vec = Vec::new();
@@ -145,26 +159,26 @@ impl FromIterator<Utf16Char> for Vec<u16> {
         return vec;
     }
 }
-#[cfg(feature="std")]
+#[cfg(feature="alloc")]
 impl<'a> FromIterator<&'a Utf16Char> for Vec<u16> {
     fn from_iter<I:IntoIterator<Item=&'a Utf16Char>>(iter: I) -> Self {
         Self::from_iter(iter.into_iter().cloned())
     }
 }
 
-#[cfg(feature="std")]
+#[cfg(feature="alloc")]
 impl Extend<Utf16Char> for String {
     fn extend<I:IntoIterator<Item=Utf16Char>>(&mut self,  iter: I) {
         self.extend(iter.into_iter().map(Utf8Char::from));
     }
 }
-#[cfg(feature="std")]
+#[cfg(feature="alloc")]
 impl<'a> Extend<&'a Utf16Char> for String {
     fn extend<I:IntoIterator<Item=&'a Utf16Char>>(&mut self,  iter: I) {
         self.extend(iter.into_iter().cloned());
     }
 }
-#[cfg(feature="std")]
+#[cfg(feature="alloc")]
 impl FromIterator<Utf16Char> for String {
     fn from_iter<I:IntoIterator<Item=Utf16Char>>(iter: I) -> Self {
         let mut s = String::new();
@@ -172,7 +186,7 @@ impl FromIterator<Utf16Char> for String {
         return s;
     }
 }
-#[cfg(feature="std")]
+#[cfg(feature="alloc")]
 impl<'a> FromIterator<&'a Utf16Char> for String {
     fn from_iter<I:IntoIterator<Item=&'a Utf16Char>>(iter: I) -> Self {
         Self::from_iter(iter.into_iter().cloned())
@@ -186,7 +200,7 @@ impl<'a> FromIterator<&'a Utf16Char> for String {
 impl AsRef<[u16]> for Utf16Char {
     #[inline]
     fn as_ref(&self) -> &[u16] {
-        &self.units[..self.len()]
+        &self.as_array()[..self.len()]
     }
 }
 impl Borrow<[u16]> for Utf16Char {
@@ -212,7 +226,7 @@ impl Deref for Utf16Char {
 impl From<AsciiChar> for Utf16Char {
     #[inline]
     fn from(ac: AsciiChar) -> Self {
-        Utf16Char{ units: [ac.as_byte() as u16, 0] }
+        Utf16Char::from_padded([ac.as_byte() as u16, 0])
     }
 }
 #[cfg(feature="ascii")]
@@ -220,11 +234,120 @@ impl From<AsciiChar> for Utf16Char {
 impl ToAsciiChar for Utf16Char {
     #[inline]
     fn to_ascii_char(self) -> Result<AsciiChar, ToAsciiCharError> {
-        self.units[0].to_ascii_char()
+        self.raw()[0].to_ascii_char()
     }
     #[inline]
     unsafe fn to_ascii_char_unchecked(self) -> AsciiChar {
-        unsafe { self.units[0].to_ascii_char_unchecked() }
+        unsafe { self.raw()[0].to_ascii_char_unchecked() }
+    }
+}
+
+
+  ///////////////////
+ //bytemuck traits//
+///////////////////
+#[cfg(feature="bytemuck")]
+/// Requires the feature "bytemuck".
+unsafe impl bytemuck::NoUninit for Utf16Char {}
+#[cfg(feature="bytemuck")]
+/// Requires the feature "bytemuck".
+///
+/// `Utf16Char` itself isn't `Zeroable` because `'\0'` isn't stored as zero.
+unsafe impl bytemuck::ZeroableInOption for Utf16Char {}
+#[cfg(feature="bytemuck")]
+/// Requires the feature "bytemuck".
+///
+/// The valid bit patterns are the units of
+/// [`to_packed_u32()`](struct.Utf16Char.html#method.to_packed_u32)
+/// in native-endian order.
+unsafe impl bytemuck::CheckedBitPattern for Utf16Char {
+    type Bits = u32;
+    fn is_valid_bit_pattern(bits: &u32) -> bool {
+        let b = bits.to_ne_bytes();
+        let first = u16::from_ne_bytes([b[0], b[1]]) as u32;
+        let second = u16::from_ne_bytes([b[2], b[3]]) as u32;
+        Utf16Char::from_packed_u32(first | second << 16).is_ok()
+    }
+}
+
+
+  ////////////////
+ //serde traits//
+////////////////
+#[cfg(feature="serde")]
+/// Requires the feature "serde".
+///
+/// Human-readable formats get a string containing the codepoint,
+/// while binary formats get a sequence of its one or two units.
+impl Serialize for Utf16Char {
+    fn serialize<S: Serializer>(&self,  serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            serializer.serialize_str(Utf8Char::from(*self).as_str())
+        } else {
+            let mut seq = serializer.serialize_seq(Some(self.len()))?;
+            for unit in &self[..] {
+                seq.serialize_element(unit)?;
+            }
+            seq.end()
+        }
+    }
+}
+#[cfg(feature="serde")]
+/// Requires the feature "serde".
+///
+/// Strings are validated with [`FromStr`](#impl-FromStr) and sequences of
+/// units with [`from_tuple()`](#method.from_tuple), and must contain
+/// exactly one codepoint. `char`s are also accepted.
+impl<'de> Deserialize<'de> for Utf16Char {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_str(Utf16CharVisitor)
+        } else {
+            deserializer.deserialize_seq(Utf16CharVisitor)
+        }
+    }
+}
+#[cfg(feature="serde")]
+struct Utf16CharVisitor;
+#[cfg(feature="serde")]
+impl<'de> de::Visitor<'de> for Utf16CharVisitor {
+    type Value = Utf16Char;
+    fn expecting(&self,  fmtr: &mut fmt::Formatter) -> fmt::Result {
+        fmtr.write_str("a single codepoint as a string or one or two UTF-16 units")
+    }
+    fn visit_char<E: de::Error>(self,  c: char) -> Result<Utf16Char, E> {
+        Ok(Utf16Char::from(c))
+    }
+    fn visit_str<E: de::Error>(self,  s: &str) -> Result<Utf16Char, E> {
+        s.parse().map_err(|e| E::custom(format_args!("string {}", e)) )
+    }
+    fn visit_seq<A: de::SeqAccess<'de>>(self,  mut seq: A) -> Result<Utf16Char, A::Error> {
+        use serde::de::Error;
+        let first = match seq.next_element()? {
+            Some(first) => first,
+            None => return Err(A::Error::invalid_length(0, &self)),
+        };
+        let second = seq.next_element()?;
+        if second.is_some()  &&  seq.next_element::<de::IgnoredAny>()?.is_some() {
+            return Err(A::Error::invalid_length(3, &self));
+        }
+        Utf16Char::from_tuple((first, second))
+            .map_err(|e| A::Error::custom(format_args!("invalid UTF-16: {}", e)) )
+    }
+}
+
+
+  ////////////////////
+ //arbitrary traits//
+////////////////////
+#[cfg(feature="arbitrary")]
+/// Requires the feature "arbitrary".
+impl<'a> arbitrary::Arbitrary<'a> for Utf16Char {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        char::arbitrary(u).map(Utf16Char::from)
+    }
+    fn size_hint(depth: usize) -> (usize, Option<usize>) {
+        <char as arbitrary::Arbitrary>::size_hint(depth)
     }
 }
 
@@ -237,6 +360,12 @@ impl hash::Hash for Utf16Char {
         self.to_char().hash(state);
     }
 }
+// I don't think there is any good default value for char, but char does.
+impl Default for Utf16Char {
+    fn default() -> Self {
+        Utf16Char::new('\0')
+    }
+}
 impl fmt::Debug for Utf16Char {
     fn fmt(&self,  fmtr: &mut fmt::Formatter) -> fmt::Result {
         fmt::Debug::fmt(&self.to_char(), fmtr)
@@ -264,8 +393,9 @@ impl Ord for Utf16Char {
         // Multiplying by a constant factor isn't enough because that factor
         // would have to be greater than 1023 and smaller than 5.5.
         // This transformation is less complicated than combine_surrogates().
-        let lhs = (self.units[0] as u32, self.units[1] as u32);
-        let rhs = (rhs.units[0] as u32, rhs.units[1] as u32);
+        let (lhs, rhs) = (self.to_array(), rhs.to_array());
+        let lhs = (lhs[0] as u32, lhs[1] as u32);
+        let rhs = (rhs[0] as u32, rhs[1] as u32);
         let lhs = (lhs.0 << (lhs.1 >> 12)) + lhs.1;
         let rhs = (rhs.0 << (rhs.1 >> 12)) + rhs.1;
         lhs.cmp(&rhs)
@@ -325,7 +455,7 @@ impl PartialOrd<Utf8Char> for Utf16Char {
 /// ```
 impl PartialEq<u16> for Utf16Char {
     fn eq(&self,  unit: &u16) -> bool {
-        self.units[0] == *unit  &&  self.units[1] == 0
+        self.raw()[0] == *unit  &&  self.is_bmp()
     }
 }
 /// Only considers the byte equal if the codepoint of the `Utf16Char` is <= U+FF.
@@ -340,7 +470,7 @@ impl PartialEq<u16> for Utf16Char {
 /// ```
 impl PartialEq<u8> for Utf16Char {
     fn eq(&self,  byte: &u8) -> bool {
-        self.units[0] == *byte as u16
+        self.raw()[0] == *byte as u16
     }
 }
 #[cfg(feature = "ascii")]
@@ -348,7 +478,7 @@ impl PartialEq<u8> for Utf16Char {
 impl PartialEq<AsciiChar> for Utf16Char {
     #[inline]
     fn eq(&self,  ascii: &AsciiChar) -> bool {
-        self.units[0] == *ascii as u16
+        self.raw()[0] == *ascii as u16
     }
 }
 #[cfg(feature = "ascii")]
@@ -356,7 +486,7 @@ impl PartialEq<AsciiChar> for Utf16Char {
 impl PartialEq<Utf16Char> for AsciiChar {
     #[inline]
     fn eq(&self,  u16c: &Utf16Char) -> bool {
-        *self as u16 == u16c.units[0]
+        *self as u16 == u16c.raw()[0]
     }
 }
 #[cfg(feature = "ascii")]
@@ -364,7 +494,7 @@ impl PartialEq<Utf16Char> for AsciiChar {
 impl PartialOrd<AsciiChar> for Utf16Char {
     #[inline]
     fn partial_cmp(&self,  ascii: &AsciiChar) -> Option<Ordering> {
-        self.units[0].partial_cmp(&(*ascii as u16))
+        self.raw()[0].partial_cmp(&(*ascii as u16))
     }
 }
 #[cfg(feature = "ascii")]
@@ -372,7 +502,7 @@ impl PartialOrd<AsciiChar> for Utf16Char {
 impl PartialOrd<Utf16Char> for AsciiChar {
     #[inline]
     fn partial_cmp(&self,  u16c: &Utf16Char) -> Option<Ordering> {
-        (*self as u16).partial_cmp(&u16c.units[0])
+        (*self as u16).partial_cmp(&u16c.raw()[0])
     }
 }
 
@@ -391,12 +521,12 @@ impl Utf16Char {
     /// ```
     pub const fn new(c: char) -> Self {
         if c <= '\u{ffff}' {
-            Utf16Char{ units: [c as u16, 0] }
+            Utf16Char::from_padded([c as u16, 0])
         } else {
             let c = (c as u32).wrapping_sub(0x01_00_00);
             let first = 0xd8_00 | (c >> 10) as u16;
             let second = 0xdc_00 | (c & 0x0_03_ff) as u16;
-            Utf16Char{ units: [first, second] }
+            Utf16Char::from_padded([first, second])
         }
     }
     /// Create an `Utf16Char` from the first codepoint in a string slice,
@@ -427,18 +557,18 @@ impl Utf16Char {
         match b[0] {
             0..=127 => {// 1 byte => 1 unit
                 let unit = b[0] as u16;// 0b0000_0000_0xxx_xxxx
-                Ok((Utf16Char{ units: [unit, 0] }, 1))
+                Ok((Utf16Char::from_padded([unit, 0]), 1))
             },
             0b1000_0000..=0b1101_1111 => {// 2 bytes => 1 unit
                 let unit = (((b[1] & 0x3f) as u16) << 0) // 0b0000_0000_00xx_xxxx
                          | (((b[0] & 0x1f) as u16) << 6);// 0b0000_0xxx_xx00_0000
-                Ok((Utf16Char{ units: [unit, 0] }, 2))
+                Ok((Utf16Char::from_padded([unit, 0]), 2))
             },
             0b1110_0000..=0b1110_1111 => {// 3 bytes => 1 unit
                 let unit = (((b[2] & 0x3f) as u16) <<  0) // 0b0000_0000_00xx_xxxx
                          | (((b[1] & 0x3f) as u16) <<  6) // 0b0000_xxxx_xx00_0000
                          | (((b[0] & 0x0f) as u16) << 12);// 0bxxxx_0000_0000_0000
-                Ok((Utf16Char{ units: [unit, 0] }, 3))
+                Ok((Utf16Char::from_padded([unit, 0]), 3))
             },
             _ => {// 4 bytes => 2 units
                 let second = 0xdc00                        // 0b1101_1100_0000_0000
@@ -448,17 +578,36 @@ impl Utf16Char {
                           + (((b[2] & 0x30) as u16) >> 4)    // 0b0000_0000_0000_00xx
                           + (((b[1] & 0x3f) as u16) << 2)    // 0b0000_0000_xxxx_xx00
                           + (((b[0] & 0x07) as u16) << 8);   // 0b0000_0xxx_0000_0000
-                Ok((Utf16Char{ units: [first, second] }, 4))
+                Ok((Utf16Char::from_padded([first, second]), 4))
             }
         }
     }
     /// Validate and store the first UTF-16 codepoint in the slice.
     /// Also return how many units were needed.
-    pub fn from_slice_start(src: &[u16]) -> Result<(Self,usize), Utf16SliceError> {
-        char::from_utf16_slice_start(src).map(|(_,len)| {
-            let second = if len==2 {src[1]} else {0};
-            (Utf16Char{ units: [src[0], second] }, len)
-        })
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use encode_unicode::Utf16Char;
+    /// use encode_unicode::error::Utf16SliceError::*;
+    ///
+    /// assert_eq!(Utf16Char::from_slice_start(&[0x41, 0xdc00]), Ok((Utf16Char::from('A'), 1)));
+    /// assert_eq!(Utf16Char::from_slice_start(&[0xdbff, 0xdfff]), Ok((Utf16Char::from('\u{10ffff}'), 2)));
+    /// assert_eq!(Utf16Char::from_slice_start(&[]), Err(EmptySlice));
+    /// assert_eq!(Utf16Char::from_slice_start(&[0xdc00, 0xdc00]), Err(FirstIsTrailingSurrogate));
+    /// assert_eq!(Utf16Char::from_slice_start(&[0xd800]), Err(MissingSecond));
+    /// assert_eq!(Utf16Char::from_slice_start(&[0xd800, 0xd800]), Err(SecondIsNotTrailingSurrogate));
+    /// ```
+    pub const fn from_slice_start(src: &[u16]) -> Result<(Self,usize), Utf16SliceError> {
+        match *src {
+            [] => Err(Utf16SliceError::EmptySlice),
+            [0xdc_00..=0xdf_ff, ..] => Err(Utf16SliceError::FirstIsTrailingSurrogate),
+            [first @ 0xd8_00..=0xdb_ff, second @ 0xdc_00..=0xdf_ff, ..]
+                => Ok((Utf16Char::from_padded([first, second]), 2)),
+            [0xd8_00..=0xdb_ff, _, ..] => Err(Utf16SliceError::SecondIsNotTrailingSurrogate),
+            [0xd8_00..=0xdb_ff] => Err(Utf16SliceError::MissingSecond),
+            [first, ..] => Ok((Utf16Char::from_padded([first, 0]), 1)),
+        }
     }
     /// Store the first UTF-16 codepoint of the slice.
     ///
@@ -466,14 +615,10 @@ impl Utf16Char {
     ///
     /// The slice must be non-empty and start with a valid UTF-16 codepoint.  
     /// The length of the slice is never checked.
-    pub unsafe fn from_slice_start_unchecked(src: &[u16]) -> (Self,usize) {
-        unsafe {
-            let first = *src.get_unchecked(0);
-            if first.is_utf16_leading_surrogate() {
-                (Utf16Char{ units: [first, *src.get_unchecked(1)] }, 2)
-            } else {
-                (Utf16Char{ units: [first, 0] }, 1)
-            }
+    pub const unsafe fn from_slice_start_unchecked(src: &[u16]) -> (Self,usize) {
+        match *src {
+            [first @ 0xd8_00..=0xdb_ff, second, ..] => (Utf16Char::from_padded([first, second]), 2),
+            _ => (Utf16Char::from_padded([src[0], 0]), 1),
         }
     }
     /// Validate and store an UTF-16 array as returned from `char.to_utf16_array()`.
@@ -493,9 +638,9 @@ impl Utf16Char {
     /// ```
     pub const fn from_array(units: [u16; 2]) -> Result<Self,Utf16ArrayError> {
         if (units[0] & 0xf8_00) != 0xd8_00 {
-            Ok(Utf16Char { units: [units[0], 0] })
+            Ok(Utf16Char::from_padded([units[0], 0]))
         } else if units[0] < 0xdc_00  &&  (units[1] & 0xfc_00) == 0xdc_00 {
-            Ok(Utf16Char { units })
+            Ok(Utf16Char::from_padded(units))
         } else if units[0] < 0xdc_00 {
             Err(Utf16ArrayError::SecondIsNotTrailingSurrogate)
         } else {
@@ -511,7 +656,7 @@ impl Utf16Char {
     /// Violating this can easily lead to undefined behavior, although unlike
     /// `char` bad `Utf16Char`s simply existing is not immediately UB.
     pub const unsafe fn from_array_unchecked(units: [u16; 2]) -> Self {
-        Utf16Char { units }
+        Utf16Char::from_padded(units)
     }
     pub(crate) const fn validate_tuple(utf16: (u16,Option<u16>)) -> Result<(),Utf16TupleError> {
         match utf16 {
@@ -546,7 +691,7 @@ impl Utf16Char {
             Some(extra) => extra,
             None => 0,
         };
-        Utf16Char { units: [utf16.0, second] }
+        Utf16Char::from_padded([utf16.0, second])
     }
     /// Create an `Utf16Char` from a single unit.
     ///
@@ -569,7 +714,7 @@ impl Utf16Char {
     /// ```
     pub const fn from_bmp(bmp_codepoint: u16) -> Result<Self,NonBmpError> {
         let is_not_bmp = bmp_codepoint & 0xf800 == 0xd800;
-        let if_good = Utf16Char{ units: [bmp_codepoint, 0] };
+        let if_good = Utf16Char::from_padded([bmp_codepoint, 0]);
         [Ok(if_good), Err(NonBmpError)][is_not_bmp as usize]
     }
     /// Create an `Utf16Char` from a single unit without checking that it's a
@@ -582,7 +727,7 @@ impl Utf16Char {
     /// Violating this can easily lead to undefined behavior.
     #[inline]
     pub const unsafe fn from_bmp_unchecked(bmp_codepoint: u16) -> Self {
-        Utf16Char{ units: [bmp_codepoint, 0] }
+        Utf16Char::from_padded([bmp_codepoint, 0])
     }
     /// Checks that the codepoint is in the basic multilingual plane.
     ///
@@ -595,7 +740,7 @@ impl Utf16Char {
     /// ```
     #[inline]
     pub const fn is_bmp(self) -> bool {
-        self.units[1] == 0
+        self.len() == 1
     }
 
     /// The number of units this character is made up of.
@@ -604,23 +749,23 @@ impl Utf16Char {
     /// or `.as_ref().len()`.
     #[inline]
     pub const fn len(self) -> usize {
-        1 + (self.units[1] as usize >> 15)
+        1 + (self.raw()[1] as usize >> 15)
     }
     // There is no `.is_emty()` because it would always return false.
 
     /// Checks that the codepoint is an ASCII character.
     #[inline]
     pub const fn is_ascii(self) -> bool {
-        self.units[0] <= 127
+        self.raw()[0] <= 127
     }
     /// Checks that two characters are an ASCII case-insensitive match.
     ///
     /// Is equivalent to `a.to_ascii_lowercase() == b.to_ascii_lowercase()`.
     pub const fn eq_ignore_ascii_case(&self,  other: &Self) -> bool {
         if self.is_ascii() && other.is_ascii() {
-            (self.units[0] as u8).eq_ignore_ascii_case(&(other.units[0] as u8))
+            (self.raw()[0] as u8).eq_ignore_ascii_case(&(other.raw()[0] as u8))
         } else {
-            self.units[0] == other.units[0] && self.units[1] == other.units[1]
+            self.raw()[0] == other.raw()[0] && self.raw()[1] == other.raw()[1]
         }
     }
     /// Converts the character to its ASCII upper case equivalent.
@@ -628,8 +773,8 @@ impl Utf16Char {
     /// ASCII letters 'a' to 'z' are mapped to 'A' to 'Z',
     /// but non-ASCII letters are unchanged.
     pub const fn to_ascii_uppercase(self) -> Self {
-        let n = self.units[0].wrapping_sub(b'a' as u16);
-        if n < 26 {Utf16Char{ units: [n+b'A' as u16, 0] }}
+        let n = self.raw()[0].wrapping_sub(b'a' as u16);
+        if n < 26 {Utf16Char::from_padded([n+b'A' as u16, 0])}
         else      {self}
     }
     /// Converts the character to its ASCII lower case equivalent.
@@ -637,8 +782,8 @@ impl Utf16Char {
     /// ASCII letters 'A' to 'Z' are mapped to 'a' to 'z',
     /// but non-ASCII letters are unchanged.
     pub const fn to_ascii_lowercase(self) -> Self {
-        let n = self.units[0].wrapping_sub(b'A' as u16);
-        if n < 26 {Utf16Char{ units: [n+b'a' as u16, 0] }}
+        let n = self.raw()[0].wrapping_sub(b'A' as u16);
+        if n < 26 {Utf16Char::from_padded([n+b'a' as u16, 0])}
         else      {self}
     }
     /// Converts the character to its ASCII upper case equivalent in-place.
@@ -657,8 +802,19 @@ impl Utf16Char {
     }
 
     /// Convert from UTF-16 to UTF-32
-    pub fn to_char(self) -> char {
-        self.into()
+    pub const fn to_char(self) -> char {
+        let units = self.raw();
+        let c = if self.len() == 1 {
+            units[0] as u32
+        } else {
+            let high = (units[0] & 0x_03_ff) as u32;
+            let low = (units[1] & 0x_03_ff) as u32;
+            ((high << 10) | low) + 0x_01_00_00
+        };
+        // transmute() is the only way to create a char from an u32 in const fn
+        // on the minimum supported Rust version.
+        #[allow(unknown_lints, unnecessary_transmutes)]
+        unsafe { mem::transmute::<u32,char>(c) }
     }
     /// Write the internal representation to a slice,
     /// and then returns the number of `u16`s written.
@@ -669,12 +825,13 @@ impl Utf16Char {
     /// but a buffer of length two is always large enough.
     pub fn to_slice(self,  dst: &mut[u16]) -> usize {
         // Write the last unit first to avoid repeated length checks.
-        let extra = self.units[1] as usize >> 15;
+        let units = self.raw();
+        let extra = units[1] as usize >> 15;
         match dst.get_mut(extra) {
-            Some(first) => *first = self.units[extra],
+            Some(first) => *first = units[extra],
             None => panic!("The provided buffer is too small.")
         }
-        if extra != 0 {dst[0] = self.units[0];}
+        if extra != 0 {dst[0] = units[0];}
         extra+1
     }
     /// Get the character represented as an array of two units.
@@ -682,11 +839,101 @@ impl Utf16Char {
     /// The second `u16` is zero for codepoints that fit in one unit.
     #[inline]
     pub const fn to_array(self) -> [u16;2] {
-        self.units
+        let mut units = self.raw();
+        // remove the marker of '\0'
+        units[1] &= !((units[0] == 0) as u16);
+        units
     }
     /// The second `u16` is used for surrogate pairs.
     #[inline]
     pub const fn to_tuple(self) -> (u16,Option<u16>) {
-        (self.units[0], [None, Some(self.units[1])][self.units[1] as usize >> 15])
+        let units = self.raw();
+        (units[0], [None, Some(units[1])][units[1] as usize >> 15])
+    }
+    /// Pack the units into an `u32`, with the first unit in the least
+    /// significant bits.
+    ///
+    /// The second unit is zero if unused, except for `'\0'` which is packed
+    /// as `0x0001_0000` so that the value is never zero.  
+    /// This format is stable, and is how `Utf16Char` is stored in memory on
+    /// little-endian targets.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use encode_unicode::Utf16Char;
+    /// assert_eq!(Utf16Char::from('ñ').to_packed_u32(), 0x0000_00f1);
+    /// assert_eq!(Utf16Char::from('\u{10ffff}').to_packed_u32(), 0xdfff_dbff);
+    /// assert_eq!(Utf16Char::from('\0').to_packed_u32(), 0x0001_0000);
+    /// ```
+    #[inline]
+    pub const fn to_packed_u32(self) -> u32 {
+        let units = self.raw();
+        units[0] as u32 | (units[1] as u32) << 16
+    }
+    /// Validate and unpack a value returned by
+    /// [`to_packed_u32()`](#method.to_packed_u32).
+    ///
+    /// # Errors
+    ///
+    /// Returns `PackedCharError::InvalidCodepoint` if the units don't start
+    /// with a valid UTF-16 codepoint, and `PackedCharError::InvalidPadding` if
+    /// the second unit is not what `to_packed_u32()` would have returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use encode_unicode::Utf16Char;
+    /// use encode_unicode::error::PackedCharError::*;
+    ///
+    /// assert_eq!(Utf16Char::from_packed_u32(0xdfff_dbff), Ok(Utf16Char::from('\u{10ffff}')));
+    /// assert_eq!(Utf16Char::from_packed_u32(0x0001_0000), Ok(Utf16Char::from('\0')));
+    /// assert_eq!(Utf16Char::from_packed_u32(0x0000_dc00), Err(InvalidCodepoint));
+    /// assert_eq!(Utf16Char::from_packed_u32(0x0061_0061), Err(InvalidPadding));
+    /// assert_eq!(Utf16Char::from_packed_u32(0), Err(InvalidPadding));
+    /// ```
+    pub const fn from_packed_u32(packed: u32) -> Result<Self,PackedCharError> {
+        match Utf16Char::from_array([packed as u16, (packed >> 16) as u16]) {
+            Ok(utf16) if utf16.to_packed_u32() == packed => Ok(utf16),
+            Ok(_) => Err(PackedCharError::InvalidPadding),
+            Err(_) => Err(PackedCharError::InvalidCodepoint),
+        }
+    }
+    /// Unpack a value returned by [`to_packed_u32()`](#method.to_packed_u32)
+    /// without validating it.
+    ///
+    /// # Safety
+    ///
+    /// The value must be one that `to_packed_u32()` can return.  
+    /// Bad values can easily lead to undefined behavior.
+    #[inline]
+    pub const unsafe fn from_packed_u32_unchecked(packed: u32) -> Self {
+        Utf16Char::from_padded([packed as u16, (packed >> 16) as u16])
+    }
+
+    /// Create from the units of a codepoint with the second zeroed if unused,
+    /// adding the marker if it's `'\0'`.
+    ///
+    /// Also accepts the value from `.raw()`.
+    #[inline]
+    const fn from_padded(mut units: [u16;2]) -> Self {
+        units[1] |= (units[0] == 0) as u16;
+        let (first, second) = (units[0].to_ne_bytes(), units[1].to_ne_bytes());
+        let bytes = [first[0], first[1], second[0], second[1]];
+        // The first unit is only zero for '\0', and then the second isn't.
+        Utf16Char{ units: unsafe { NonZeroU32::new_unchecked(u32::from_ne_bytes(bytes)) } }
+    }
+    /// The stored units, including the marker if this is `'\0'`.
+    #[inline]
+    const fn raw(self) -> [u16;2] {
+        let bytes = self.units.get().to_ne_bytes();
+        [u16::from_ne_bytes([bytes[0], bytes[1]]), u16::from_ne_bytes([bytes[2], bytes[3]])]
+    }
+    /// Borrow the stored units, including the marker if this is `'\0'`.
+    #[inline]
+    fn as_array(&self) -> &[u16;2] {
+        // Utf16Char is `repr(transparent)` around an `u32`,
+        // which has the same size and greater alignment than `[u16; 2]`.
+        unsafe { &*(&self.units as *const NonZeroU32 as *const [u16;2]) }
     }
 }
@@ -91,8 +91,8 @@ impl fmt::Debug for Utf16Iterator {
 ///
 /// From iterator of references:
 ///
-#[cfg_attr(feature="std", doc=" ```")]
-#[cfg_attr(not(feature="std"), doc=" ```no_compile")]
+#[cfg_attr(feature="alloc", doc=" ```")]
+#[cfg_attr(not(feature="alloc"), doc=" ```no_compile")]
 /// use encode_unicode::{IterExt, CharExt, Utf16Char};
 ///
 /// // (💣 takes two units)
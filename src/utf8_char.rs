@@ -6,39 +6,48 @@
  * copied, modified, or distributed except according to those terms.
  */
 
-use crate::errors::{FromStrError, EmptyStrError, NonAsciiError, Utf8Error};
+use crate::errors::{FromStrError, EmptyStrError, NonAsciiError, PackedCharError, Utf8Error};
+use crate::utf8_validator::decode_utf8_start;
 use crate::utf8_iterators::Utf8Iterator;
-use crate::traits::{CharExt, U8UtfExt};
+use crate::traits::U8UtfExt;
 use crate::utf16_char::Utf16Char;
 extern crate core;
-use core::{hash, fmt, str, ptr};
+use core::{hash, fmt, str, mem};
+use core::num::NonZeroU32;
 use core::cmp::Ordering;
 use core::borrow::Borrow;
 use core::ops::Deref;
-#[cfg(feature="std")]
+#[cfg(feature="alloc")]
 use core::iter::FromIterator;
+#[cfg(feature="alloc")]
+use alloc::{vec::Vec, string::String};
 #[cfg(feature="ascii")]
 extern crate ascii;
 #[cfg(feature="ascii")]
 use ascii::{AsciiChar,ToAsciiChar,ToAsciiCharError};
+#[cfg(feature="serde")]
+use serde::{de, Serialize, Serializer, Deserialize, Deserializer};
 
 
-// I don't think there is any good default value for char, but char does.
-#[derive(Default)]
 // char doesn't do anything more advanced than u32 for Eq/Ord, so we shouldn't either.
-// The default impl of Ord for arrays works out because longer codepoints
-//     start with more ones, so if they're equal, the length is the same,
-// breaks down for values above 0x1f_ff_ff but those can only be created by unsafe code.
-#[derive(PartialEq,Eq, PartialOrd,Ord)]
-
+// Every codepoint has exactly one representation, so Eq works.
+#[derive(PartialEq,Eq)]
 #[derive(Clone,Copy)]
 
 
 /// An unicode codepoint stored as UTF-8.
 ///
-/// It can be borrowed as a `str`, and has the same size as `char`.
+/// It can be borrowed as a `str`, and has the same size as `char`.  
+/// `Option<Utf8Char>` also has the same size as `Option<char>`.
+#[repr(transparent)]
+#[cfg_attr(feature="zerocopy", derive(zerocopy::IntoBytes, zerocopy::Immutable, zerocopy::KnownLayout))]
 pub struct Utf8Char {
-    bytes: [u8; 4],
+    // The bytes in order, with unused bytes zeroed.
+    // '\0' would then be all zeros, so it is stored with the last byte set to
+    // 1 instead, which lets the compiler use zero as the niche for `None`.
+    // The marker doesn't have the most significant bit set,
+    // so it is never mistaken for a continuation byte by `len()`.
+    bytes: NonZeroU32,
 }
 
 
@@ -71,7 +80,7 @@ impl str::FromStr for Utf8Char {
         } else {
             let mut bytes = [0; 4];
             bytes[..s.len()].copy_from_slice(s.as_bytes());
-            Ok(Utf8Char{bytes})
+            Ok(Utf8Char::from_padded(bytes))
         }
     }
 }
@@ -79,18 +88,18 @@ impl From<Utf16Char> for Utf8Char {
     fn from(utf16: Utf16Char) -> Utf8Char {
         match utf16.to_tuple() {
             (ascii @ 0..=0x00_7f, _) => {
-                Utf8Char{ bytes: [ascii as u8, 0, 0, 0] }
+                Utf8Char::from_padded([ascii as u8, 0, 0, 0])
             },
             (unit @ 0..=0x07_ff, _) => {
                 let byte2 = 0x80 |  (unit & 0x00_3f) as u8;
                 let byte1 = 0xc0 | ((unit & 0x07_c0) >> 6) as u8;
-                Utf8Char{ bytes: [byte1, byte2, 0, 0] }
+                Utf8Char::from_padded([byte1, byte2, 0, 0])
             },
             (unit, None) => {
                 let byte3 = 0x80 |  (unit & 0x00_3f) as u8;
                 let byte2 = 0x80 | ((unit & 0x0f_c0) >> 6) as u8;
                 let byte1 = 0xe0 | ((unit & 0xf0_00) >> 12) as u8;
-                Utf8Char{ bytes: [byte1, byte2, byte3, 0] }
+                Utf8Char::from_padded([byte1, byte2, byte3, 0])
             },
             (first, Some(second)) => {
                 let first = first + (0x01_00_00u32 >> 10) as u16;
@@ -99,7 +108,7 @@ impl From<Utf16Char> for Utf8Char {
                                  | (( first & 0x00_03) << 4) as u8;
                 let byte2 = 0x80 | (( first & 0x00_fc) >> 2) as u8;
                 let byte1 = 0xf0 | (( first & 0x07_00) >> 8) as u8;
-                Utf8Char{ bytes: [byte1, byte2, byte3, byte4] }
+                Utf8Char::from_padded([byte1, byte2, byte3, byte4])
             }
         }
     }
@@ -123,15 +132,16 @@ impl IntoIterator for Utf8Char {
     }
 }
 
-#[cfg(feature="std")]
+#[cfg(feature="alloc")]
 impl Extend<Utf8Char> for Vec<u8> {
     fn extend<I:IntoIterator<Item=Utf8Char>>(&mut self,  iter: I) {
         let iter = iter.into_iter();
         self.reserve(iter.size_hint().0);
         for u8c in iter {
             // twice as fast as self.extend_from_slice(u8c.as_bytes());
-            self.push(u8c.bytes[0]);
-            for &extra in &u8c.bytes[1..] {
+            let bytes = u8c.to_array().0;
+            self.push(bytes[0]);
+            for &extra in &bytes[1..] {
                 if extra != 0 {
                     self.push(extra);
                 }
@@ -139,25 +149,25 @@ impl Extend<Utf8Char> for Vec<u8> {
         }
     }
 }
-#[cfg(feature="std")]
+#[cfg(feature="alloc")]
 impl<'a> Extend<&'a Utf8Char> for Vec<u8> {
     fn extend<I:IntoIterator<Item=&'a Utf8Char>>(&mut self,  iter: I) {
         self.extend(iter.into_iter().cloned())
     }
 }
-#[cfg(feature="std")]
+#[cfg(feature="alloc")]
 impl Extend<Utf8Char> for String {
     fn extend<I:IntoIterator<Item=Utf8Char>>(&mut self,  iter: I) {
         unsafe { self.as_mut_vec().extend(iter) }
     }
 }
-#[cfg(feature="std")]
+#[cfg(feature="alloc")]
 impl<'a> Extend<&'a Utf8Char> for String {
     fn extend<I:IntoIterator<Item=&'a Utf8Char>>(&mut self,  iter: I) {
         self.extend(iter.into_iter().cloned())
     }
 }
-#[cfg(feature="std")]
+#[cfg(feature="alloc")]
 impl FromIterator<Utf8Char> for String {
     fn from_iter<I:IntoIterator<Item=Utf8Char>>(iter: I) -> String {
         let mut string = String::new();
@@ -165,19 +175,19 @@ impl FromIterator<Utf8Char> for String {
         return string;
     }
 }
-#[cfg(feature="std")]
+#[cfg(feature="alloc")]
 impl<'a> FromIterator<&'a Utf8Char> for String {
     fn from_iter<I:IntoIterator<Item=&'a Utf8Char>>(iter: I) -> String {
         iter.into_iter().cloned().collect()
     }
 }
-#[cfg(feature="std")]
+#[cfg(feature="alloc")]
 impl FromIterator<Utf8Char> for Vec<u8> {
     fn from_iter<I:IntoIterator<Item=Utf8Char>>(iter: I) -> Self {
         iter.into_iter().collect::<String>().into_bytes()
     }
 }
-#[cfg(feature="std")]
+#[cfg(feature="alloc")]
 impl<'a> FromIterator<&'a Utf8Char> for Vec<u8> {
     fn from_iter<I:IntoIterator<Item=&'a Utf8Char>>(iter: I) -> Self {
         iter.into_iter().cloned().collect::<String>().into_bytes()
@@ -190,7 +200,7 @@ impl<'a> FromIterator<&'a Utf8Char> for Vec<u8> {
 /////////////////
 impl AsRef<[u8]> for Utf8Char {
     fn as_ref(&self) -> &[u8] {
-        &self.bytes[..self.len()]
+        &self.as_array()[..self.len()]
     }
 }
 impl AsRef<str> for Utf8Char {
@@ -223,17 +233,113 @@ impl Deref for Utf8Char {
 /// Requires the feature "ascii".
 impl From<AsciiChar> for Utf8Char {
     fn from(ac: AsciiChar) -> Self {
-        Utf8Char{ bytes: [ac.as_byte(),0,0,0] }
+        Utf8Char::from_padded([ac.as_byte(),0,0,0])
     }
 }
 #[cfg(feature="ascii")]
 /// Requires the feature "ascii".
 impl ToAsciiChar for Utf8Char {
     fn to_ascii_char(self) -> Result<AsciiChar, ToAsciiCharError> {
-        self.bytes[0].to_ascii_char()
+        self.raw()[0].to_ascii_char()
     }
     unsafe fn to_ascii_char_unchecked(self) -> AsciiChar {
-        unsafe { self.bytes[0].to_ascii_char_unchecked() }
+        unsafe { self.raw()[0].to_ascii_char_unchecked() }
+    }
+}
+
+
+  ///////////////////
+ //bytemuck traits//
+///////////////////
+#[cfg(feature="bytemuck")]
+/// Requires the feature "bytemuck".
+unsafe impl bytemuck::NoUninit for Utf8Char {}
+#[cfg(feature="bytemuck")]
+/// Requires the feature "bytemuck".
+///
+/// `Utf8Char` itself isn't `Zeroable` because `'\0'` isn't stored as zero.
+unsafe impl bytemuck::ZeroableInOption for Utf8Char {}
+#[cfg(feature="bytemuck")]
+/// Requires the feature "bytemuck".
+///
+/// The valid bit patterns are the bytes of
+/// [`to_packed_u32()`](struct.Utf8Char.html#method.to_packed_u32)
+/// in little-endian order.
+unsafe impl bytemuck::CheckedBitPattern for Utf8Char {
+    type Bits = u32;
+    fn is_valid_bit_pattern(bits: &u32) -> bool {
+        Utf8Char::from_packed_u32(u32::from_le_bytes(bits.to_ne_bytes())).is_ok()
+    }
+}
+
+
+  ////////////////
+ //serde traits//
+////////////////
+#[cfg(feature="serde")]
+/// Requires the feature "serde".
+///
+/// Human-readable formats get a string containing the codepoint,
+/// while binary formats get its one to four UTF-8 bytes.
+impl Serialize for Utf8Char {
+    fn serialize<S: Serializer>(&self,  serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            serializer.serialize_str(self.as_str())
+        } else {
+            serializer.serialize_bytes(self.as_bytes())
+        }
+    }
+}
+#[cfg(feature="serde")]
+/// Requires the feature "serde".
+///
+/// Strings are validated with [`FromStr`](#impl-FromStr) and bytes with
+/// [`from_slice_start()`](#method.from_slice_start), and must contain
+/// exactly one codepoint. `char`s are also accepted.
+impl<'de> Deserialize<'de> for Utf8Char {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_str(Utf8CharVisitor)
+        } else {
+            deserializer.deserialize_bytes(Utf8CharVisitor)
+        }
+    }
+}
+#[cfg(feature="serde")]
+struct Utf8CharVisitor;
+#[cfg(feature="serde")]
+impl<'de> de::Visitor<'de> for Utf8CharVisitor {
+    type Value = Utf8Char;
+    fn expecting(&self,  fmtr: &mut fmt::Formatter) -> fmt::Result {
+        fmtr.write_str("a single codepoint as a string or UTF-8 bytes")
+    }
+    fn visit_char<E: de::Error>(self,  c: char) -> Result<Utf8Char, E> {
+        Ok(Utf8Char::from(c))
+    }
+    fn visit_str<E: de::Error>(self,  s: &str) -> Result<Utf8Char, E> {
+        s.parse().map_err(|e| E::custom(format_args!("string {}", e)) )
+    }
+    fn visit_bytes<E: de::Error>(self,  bytes: &[u8]) -> Result<Utf8Char, E> {
+        match Utf8Char::from_slice_start(bytes) {
+            Ok((u8c, len)) if len == bytes.len() => Ok(u8c),
+            Ok(_) => Err(E::invalid_length(bytes.len(), &self)),
+            Err(e) => Err(E::custom(format_args!("invalid UTF-8: {:?}", e.kind()))),
+        }
+    }
+}
+
+
+  ////////////////////
+ //arbitrary traits//
+////////////////////
+#[cfg(feature="arbitrary")]
+/// Requires the feature "arbitrary".
+impl<'a> arbitrary::Arbitrary<'a> for Utf8Char {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        char::arbitrary(u).map(Utf8Char::from)
+    }
+    fn size_hint(depth: usize) -> (usize, Option<usize>) {
+        <char as arbitrary::Arbitrary>::size_hint(depth)
     }
 }
 
@@ -246,6 +352,29 @@ impl hash::Hash for Utf8Char {
         self.to_char().hash(state);
     }
 }
+// I don't think there is any good default value for char, but char does.
+impl Default for Utf8Char {
+    fn default() -> Self {
+        Utf8Char::new('\0')
+    }
+}
+// The ordering of byte arrays works out because longer codepoints
+//     start with more ones, so if they're equal, the length is the same,
+// breaks down for values above 0x1f_ff_ff but those can only be created by unsafe code.
+// Comparing as big-endian integers is equivalent, and the marker of '\0'
+// keeps it smaller than all other values.
+impl PartialOrd for Utf8Char {
+    #[inline]
+    fn partial_cmp(&self,  rhs: &Self) -> Option<Ordering> {
+        Some(self.cmp(rhs))
+    }
+}
+impl Ord for Utf8Char {
+    #[inline]
+    fn cmp(&self,  rhs: &Self) -> Ordering {
+        u32::from_be(self.bytes.get()).cmp(&u32::from_be(rhs.bytes.get()))
+    }
+}
 impl fmt::Debug for Utf8Char {
     fn fmt(&self,  fmtr: &mut fmt::Formatter) -> fmt::Result {
         fmt::Debug::fmt(&self.to_char(), fmtr)
@@ -310,7 +439,7 @@ impl PartialOrd<Utf16Char> for Utf8Char {
 /// ```
 impl PartialEq<u8> for Utf8Char {
     fn eq(&self,  byte: &u8) -> bool {
-        self.bytes[0] == *byte  &&  self.bytes[1] == 0
+        self.raw()[0] == *byte  &&  self.raw()[1] == 0
     }
 }
 #[cfg(feature = "ascii")]
@@ -318,7 +447,7 @@ impl PartialEq<u8> for Utf8Char {
 impl PartialEq<AsciiChar> for Utf8Char {
     #[inline]
     fn eq(&self,  ascii: &AsciiChar) -> bool {
-        self.bytes[0] == *ascii as u8
+        self.raw()[0] == *ascii as u8
     }
 }
 #[cfg(feature = "ascii")]
@@ -334,7 +463,7 @@ impl PartialEq<Utf8Char> for AsciiChar {
 impl PartialOrd<AsciiChar> for Utf8Char {
     #[inline]
     fn partial_cmp(&self,  ascii: &AsciiChar) -> Option<Ordering> {
-        self.bytes[0].partial_cmp(ascii)
+        self.raw()[0].partial_cmp(ascii)
     }
 }
 #[cfg(feature = "ascii")]
@@ -342,7 +471,7 @@ impl PartialOrd<AsciiChar> for Utf8Char {
 impl PartialOrd<Utf8Char> for AsciiChar {
     #[inline]
     fn partial_cmp(&self,  u8c: &Utf8Char) -> Option<Ordering> {
-        self.partial_cmp(&u8c.bytes[0])
+        self.partial_cmp(&u8c.raw()[0])
     }
 }
 
@@ -361,7 +490,7 @@ impl Utf8Char {
     /// ```
     pub const fn new(c: char) -> Self {
         if c.is_ascii() {
-            Utf8Char{bytes: [c as u8, 0, 0, 0]}
+            Utf8Char::from_padded([c as u8, 0, 0, 0])
         } else {
             // How many extra UTF-8 bytes that are needed to represent an
             // UTF-32 codepoint with a number of bits.
@@ -389,7 +518,7 @@ impl Utf8Char {
             parts |= (0xff_00u32 >> len)  &  0xff;// store length
             parts &= !(1u32 << (7-len));// clear the next bit after it
 
-            Utf8Char {bytes: parts.to_le_bytes()}
+            Utf8Char::from_padded(parts.to_le_bytes())
         }
     }
 
@@ -408,7 +537,7 @@ impl Utf8Char {
     /// assert_eq!(Utf8Char::from_str_start("é"), Ok((Utf8Char::from('e'),1)));// 'e'+u301 combining mark
     /// assert!(Utf8Char::from_str_start("").is_err());
     /// ```
-    pub fn from_str_start(src: &str) -> Result<(Self,usize),EmptyStrError> {
+    pub const fn from_str_start(src: &str) -> Result<(Self,usize),EmptyStrError> {
         unsafe {
             if src.is_empty() {
                 Err(EmptyStrError)
@@ -441,12 +570,26 @@ impl Utf8Char {
     /// assert_eq!(Utf8Char::from_slice_start(&[0xee, b'F', 0x80]).unwrap_err().kind(), InterruptedSequence);
     /// assert_eq!(Utf8Char::from_slice_start(&[0xee, 0x99, 0x0f]).unwrap_err().kind(), InterruptedSequence);
     /// ```
-    pub fn from_slice_start(src: &[u8]) -> Result<(Self,usize),Utf8Error> {
-        char::from_utf8_slice_start(src).map(|(_,len)| {
-            let mut bytes = [0; 4];
-            bytes[..len].copy_from_slice(&src[..len]);
-            (Utf8Char{bytes}, len)
-        })
+    ///
+    /// This function is `const`, and can be used to build tables from byte strings:
+    ///
+    /// ```
+    /// use encode_unicode::Utf8Char;
+    ///
+    /// const fn decode(bytes: &[u8]) -> Utf8Char {
+    ///     match Utf8Char::from_slice_start(bytes) {
+    ///         Ok((utf8, _)) => utf8,
+    ///         Err(_) => Utf8Char::new(char::REPLACEMENT_CHARACTER),
+    ///     }
+    /// }
+    /// const SUITS: [Utf8Char; 3] = [decode(b"\xe2\x99\xa0"), decode(b"\xe2\x99"), decode(b"\xe2\x99\xa6")];
+    /// assert_eq!(SUITS, ['♠', '\u{fffd}', '♦']);
+    /// ```
+    pub const fn from_slice_start(src: &[u8]) -> Result<(Self,usize),Utf8Error> {
+        match decode_utf8_start(src) {
+            Ok((_, len)) => Ok((Utf8Char::from_array_start(src, len), len)),
+            Err(kind) => Err(Utf8Error{ kind }),
+        }
     }
     /// A `from_slice_start()` that doesn't validate the codepoint.
     ///
@@ -454,13 +597,11 @@ impl Utf8Char {
     ///
     /// The slice must be non-empty and start with a valid UTF-8 codepoint.  
     /// Invalid or incomplete values might cause reads of uninitalized memory.
-    pub unsafe fn from_slice_start_unchecked(src: &[u8]) -> (Self,usize) {
-        unsafe {
-            let len = 1+src.get_unchecked(0).extra_utf8_bytes_unchecked();
-            let mut bytes = [0; 4];
-            ptr::copy_nonoverlapping(src.as_ptr(), bytes.as_mut_ptr() as *mut u8, len);
-            (Utf8Char{bytes}, len)
-        }
+    pub const unsafe fn from_slice_start_unchecked(src: &[u8]) -> (Self,usize) {
+        // same as u8::extra_utf8_bytes_unchecked(), which cannot be called in const fn
+        let first = src[0];
+        let len = 1 + if first < 128 {0} else {(!((first as u32) << 25)).leading_zeros() as usize};
+        (Utf8Char::from_array_start(src, len), len)
     }
     /// Create an `Utf8Char` from a byte array after validating it.
     ///
@@ -487,15 +628,11 @@ impl Utf8Char {
     /// assert_eq!(Utf8Char::from_array([0xe0, 0x9a, 0xbf, 0]).unwrap_err().kind(), OverlongEncoding);
     /// assert_eq!(Utf8Char::from_array([0xf4, 0xaa, 0x99, 0x88]).unwrap_err().kind(), TooHighCodepoint);
     /// ```
-    pub fn from_array(utf8: [u8;4]) -> Result<Self,Utf8Error> {
-        // perform all validation
-        char::from_utf8_array(utf8)?;
-        let extra = utf8[0].extra_utf8_bytes_unchecked() as u32;
-        // zero unused bytes in one operation by transmuting the arrary to
-        // u32, apply an endian-corrected mask and transmute back
-        let mask = u32::from_le(0xff_ff_ff_ff >> (8*(3-extra)));
-        let unused_zeroed = mask  &  u32::from_ne_bytes(utf8); // native endian
-        Ok(Utf8Char{ bytes: unused_zeroed.to_ne_bytes() })
+    pub const fn from_array(utf8: [u8;4]) -> Result<Self,Utf8Error> {
+        match decode_utf8_start(&utf8) {
+            Ok((_, len)) => Ok(Utf8Char::from_array_start(&utf8, len)),
+            Err(kind) => Err(Utf8Error{ kind }),
+        }
     }
     /// Zero-cost constructor.
     ///
@@ -506,7 +643,7 @@ impl Utf8Char {
     /// Bad values can easily lead to undefined behavior.
     #[inline]
     pub const unsafe fn from_array_unchecked(utf8: [u8;4]) -> Self {
-        Utf8Char{ bytes: utf8 }
+        Utf8Char::from_padded(utf8)
     }
     /// Create an `Utf8Char` from a single byte.
     ///
@@ -524,7 +661,7 @@ impl Utf8Char {
     /// assert!(Utf8Char::from_ascii(128).is_err());
     /// ```
     pub const fn from_ascii(ascii: u8) -> Result<Self,NonAsciiError> {
-        [Ok(Utf8Char{ bytes: [ascii, 0, 0, 0] }), Err(NonAsciiError)][(ascii >> 7) as usize]
+        [Ok(Utf8Char::from_padded([ascii, 0, 0, 0])), Err(NonAsciiError)][(ascii >> 7) as usize]
     }
     /// Create an `Utf8Char` from a single byte without checking that it's a
     /// valid codepoint on its own, which is only true for ASCII characters.
@@ -534,7 +671,7 @@ impl Utf8Char {
     /// The byte must be less than 128.
     #[inline]
     pub const unsafe fn from_ascii_unchecked(ascii: u8) -> Self {
-        Utf8Char{ bytes: [ascii, 0, 0, 0] }
+        Utf8Char::from_padded([ascii, 0, 0, 0])
     }
 
     /// The number of bytes this character needs.
@@ -547,20 +684,22 @@ impl Utf8Char {
         // `u8.extra_utf8_bytes_unchecked()` cannot use.
         // Some of them turned out to require fewer x86 instructions:
 
-        // Exploits that unused bytes are zero and calculates the number of
-        // trailing zero bytes.
+        // Exploits that used extra bytes have their most significant bit set
+        // and unused bytes are zero (or the marker of '\0'),
+        // and calculates the number of trailing bytes without it.
         // Setting a bit in the first byte prevents the function from returning
         // 0 for '\0' (which has 32 leading zeros).
         // trailing and leading is swapped below to optimize for little-endian
         // architectures.
-        (4 - (u32::from_le_bytes(self.bytes)|1).leading_zeros()/8) as usize
+        let extra = u32::from_le_bytes(self.raw()) & 0x80_80_80_00;
+        (4 - (extra|1).leading_zeros()/8) as usize
 
         // Exploits that the extra bytes have their most significant bit set if
         // in use.
         // Takes fewer instructions than the one above if popcnt can be used,
         // (which it cannot by default,
         //  set RUSTFLAGS='-C target-cpu=native' to enable)
-        //let all = u32::from_ne_bytes(self.bytes);
+        //let all = self.bytes.get();
         //let msb_mask = u32::from_be(0x00808080);
         //let add_one = u32::from_be(0x80000000);
         //((all & msb_mask) | add_one).count_ones() as usize
@@ -569,34 +708,36 @@ impl Utf8Char {
 
     /// Checks that the codepoint is an ASCII character.
     pub const fn is_ascii(self) -> bool {
-        self.bytes[0].is_ascii()
+        self.raw()[0].is_ascii()
     }
     /// Checks that two characters are an ASCII case-insensitive match.
     ///
     /// Is equivalent to `a.to_ascii_lowercase() == b.to_ascii_lowercase()`.
     pub const fn eq_ignore_ascii_case(&self,  other: &Self) -> bool {
         if self.is_ascii() {
-            self.bytes[0].eq_ignore_ascii_case(&other.bytes[0])
+            self.raw()[0].eq_ignore_ascii_case(&other.raw()[0])
         } else {
             // [u8; 4] can't be const compared as of Rust 1.60, but u32 can
-            u32::from_le_bytes(self.bytes) == u32::from_le_bytes(other.bytes)
+            self.bytes.get() == other.bytes.get()
         }
     }
     /// Converts the character to its ASCII upper case equivalent.
     ///
     /// ASCII letters 'a' to 'z' are mapped to 'A' to 'Z',
     /// but non-ASCII letters are unchanged.
-    pub const fn to_ascii_uppercase(mut self) -> Self {
-        self.bytes[0] = self.bytes[0].to_ascii_uppercase();
-        self
+    pub const fn to_ascii_uppercase(self) -> Self {
+        let mut bytes = self.raw();
+        bytes[0] = bytes[0].to_ascii_uppercase();
+        Utf8Char::from_padded(bytes)
     }
     /// Converts the character to its ASCII lower case equivalent.
     ///
     /// ASCII letters 'A' to 'Z' are mapped to 'a' to 'z',
     /// but non-ASCII letters are unchanged.
-    pub const fn to_ascii_lowercase(mut self) -> Self {
-        self.bytes[0] = self.bytes[0].to_ascii_lowercase();
-        self
+    pub const fn to_ascii_lowercase(self) -> Self {
+        let mut bytes = self.raw();
+        bytes[0] = bytes[0].to_ascii_lowercase();
+        Utf8Char::from_padded(bytes)
     }
     /// Converts the character to its ASCII upper case equivalent in-place.
     ///
@@ -604,7 +745,7 @@ impl Utf8Char {
     /// but non-ASCII letters are unchanged.
     #[inline]
     pub fn make_ascii_uppercase(&mut self) {
-        self.bytes[0].make_ascii_uppercase()
+        *self = self.to_ascii_uppercase();
     }
     /// Converts the character to its ASCII lower case equivalent in-place.
     ///
@@ -612,12 +753,23 @@ impl Utf8Char {
     /// but non-ASCII letters are unchanged.
     #[inline]
     pub fn make_ascii_lowercase(&mut self) {
-        self.bytes[0].make_ascii_lowercase();
+        *self = self.to_ascii_lowercase();
     }
 
     /// Convert from UTF-8 to UTF-32
-    pub fn to_char(self) -> char {
-        unsafe { char::from_utf8_exact_slice_unchecked(&self.bytes[..self.len()]) }
+    pub const fn to_char(self) -> char {
+        let bytes = self.raw();
+        let len = self.len();
+        let mut c = if len == 1 {bytes[0] as u32} else {bytes[0] as u32 & (0x7f >> len)};
+        let mut i = 1;
+        while i < len {
+            c = (c << 6)  |  (bytes[i] & 0b0011_1111) as u32;
+            i += 1;
+        }
+        // transmute() is the only way to create a char from an u32 in const fn
+        // on the minimum supported Rust version.
+        #[allow(unknown_lints, unnecessary_transmutes)]
+        unsafe { mem::transmute::<u32,char>(c) }
     }
     /// Write the internal representation to a slice,
     /// and then returns the number of bytes written.
@@ -631,12 +783,82 @@ impl Utf8Char {
         if self.len() > dst.len() {
             panic!("The provided buffer is too small.");
         }
-        dst[..self.len()].copy_from_slice(&self.bytes[..self.len()]);
+        dst[..self.len()].copy_from_slice(self.as_bytes());
         self.len()
     }
     /// Expose the internal array and the number of used bytes.
     pub const fn to_array(self) -> ([u8;4],usize) {
-        (self.bytes, self.len())
+        let mut bytes = self.raw();
+        // remove the marker of '\0'
+        bytes[3] &= !((bytes[0] == 0) as u8);
+        (bytes, self.len())
+    }
+    /// Pack the bytes into an `u32`, with the first byte in the least
+    /// significant bits.
+    ///
+    /// Unused bytes are zero, except for `'\0'` which is packed as
+    /// `0x01_00_00_00` so that the value is never zero.  
+    /// This format is stable, and is how `Utf8Char` is stored in memory on
+    /// little-endian targets.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use encode_unicode::Utf8Char;
+    /// assert_eq!(Utf8Char::from('a').to_packed_u32(), 0x00_00_00_61);
+    /// assert_eq!(Utf8Char::from('ñ').to_packed_u32(), 0x00_00_b1_c3);
+    /// assert_eq!(Utf8Char::from('\u{10ffff}').to_packed_u32(), 0xbf_bf_8f_f4);
+    /// assert_eq!(Utf8Char::from('\0').to_packed_u32(), 0x01_00_00_00);
+    /// ```
+    #[inline]
+    pub const fn to_packed_u32(self) -> u32 {
+        u32::from_le_bytes(self.raw())
+    }
+    /// Validate and unpack a value returned by
+    /// [`to_packed_u32()`](#method.to_packed_u32).
+    ///
+    /// # Errors
+    ///
+    /// Returns `PackedCharError::InvalidCodepoint` if the bytes don't start
+    /// with a valid UTF-8 sequence, and `PackedCharError::InvalidPadding` if
+    /// the bytes after it are not what `to_packed_u32()` would have returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use encode_unicode::Utf8Char;
+    /// use encode_unicode::error::PackedCharError::*;
+    ///
+    /// assert_eq!(Utf8Char::from_packed_u32(0x00_00_b1_c3), Ok(Utf8Char::from('ñ')));
+    /// assert_eq!(Utf8Char::from_packed_u32(0x01_00_00_00), Ok(Utf8Char::from('\0')));
+    /// assert_eq!(Utf8Char::from_packed_u32(0x00_00_00_c3), Err(InvalidCodepoint));
+    /// assert_eq!(Utf8Char::from_packed_u32(0x00_00_61_61), Err(InvalidPadding));
+    /// assert_eq!(Utf8Char::from_packed_u32(0), Err(InvalidPadding));
+    /// ```
+    pub const fn from_packed_u32(packed: u32) -> Result<Self,PackedCharError> {
+        match decode_utf8_start(&packed.to_le_bytes()) {
+            Ok((_, len)) => {
+                let used = packed & (0xff_ff_ff_ff >> (8*(4-len as u32)));
+                let utf8 = Utf8Char::from_padded(used.to_le_bytes());
+                if utf8.to_packed_u32() == packed {
+                    Ok(utf8)
+                } else {
+                    Err(PackedCharError::InvalidPadding)
+                }
+            },
+            Err(_) => Err(PackedCharError::InvalidCodepoint),
+        }
+    }
+    /// Unpack a value returned by [`to_packed_u32()`](#method.to_packed_u32)
+    /// without validating it.
+    ///
+    /// # Safety
+    ///
+    /// The value must be one that `to_packed_u32()` can return.  
+    /// Bad values can easily lead to undefined behavior.
+    #[inline]
+    pub const unsafe fn from_packed_u32_unchecked(packed: u32) -> Self {
+        Utf8Char::from_padded(packed.to_le_bytes())
     }
     /// Return a `str` view of the array the codepoint is stored as.
     ///
@@ -644,4 +866,47 @@ impl Utf8Char {
     pub fn as_str(&self) -> &str {
         self.deref()
     }
+
+    /// Create from the first `len` bytes of a slice, which must be at least
+    /// that long if the result is to be correct.
+    ///
+    /// Reads up to four bytes at once with slice patterns, which is possible
+    /// in `const fn`s unlike `ptr::copy_nonoverlapping()` or range indexing.
+    #[inline]
+    const fn from_array_start(src: &[u8],  len: usize) -> Self {
+        let bytes = match *src {
+            [a, b, c, d, ..] => [a, b, c, d],
+            [a, b, c] => [a, b, c, 0],
+            [a, b] => [a, b, 0, 0],
+            [a] => [a, 0, 0, 0],
+            [] => [0, 0, 0, 0],
+        };
+        // zero unused bytes in one operation by transmuting the arrary to
+        // u32, apply an endian-corrected mask and transmute back
+        let mask = u32::from_le(0xff_ff_ff_ff >> (8*(4-len)));
+        let unused_zeroed = mask  &  u32::from_ne_bytes(bytes); // native endian
+        Utf8Char::from_padded(unused_zeroed.to_ne_bytes())
+    }
+    /// Create from the bytes of a codepoint with unused bytes zeroed,
+    /// adding the marker if it's `'\0'`.
+    ///
+    /// Also accepts the value from `.raw()`.
+    #[inline]
+    const fn from_padded(mut bytes: [u8;4]) -> Self {
+        bytes[3] |= (bytes[0] == 0) as u8;
+        // The first byte is only zero for '\0', and then the last byte isn't.
+        Utf8Char{ bytes: unsafe { NonZeroU32::new_unchecked(u32::from_ne_bytes(bytes)) } }
+    }
+    /// The stored bytes, including the marker if this is `'\0'`.
+    #[inline]
+    const fn raw(self) -> [u8;4] {
+        self.bytes.get().to_ne_bytes()
+    }
+    /// Borrow the stored bytes, including the marker if this is `'\0'`.
+    #[inline]
+    fn as_array(&self) -> &[u8;4] {
+        // Utf8Char is `repr(transparent)` around an `u32`,
+        // and an `u32` can be viewed as bytes in native-endian order.
+        unsafe { &*(&self.bytes as *const NonZeroU32 as *const [u8;4]) }
+    }
 }
@@ -8,11 +8,17 @@
 
 use crate::utf8_char::Utf8Char;
 use crate::errors::EmptyStrError;
+use crate::lengths::ascii_prefix_len;
 extern crate core;
 use core::{u32, u64};
 use core::ops::Not;
 use core::fmt;
 use core::borrow::Borrow;
+use core::str::FromStr;
+#[cfg(feature="ascii")]
+extern crate ascii;
+#[cfg(feature="ascii")]
+use ascii::AsciiStr;
 #[cfg(feature="std")]
 use std::io::{Read, Error as ioError};
 
@@ -113,8 +119,8 @@ impl fmt::Debug for Utf8Iterator {
 ///
 /// From iterator of references:
 ///
-#[cfg_attr(feature="std", doc=" ```")]
-#[cfg_attr(not(feature="std"), doc=" ```no_compile")]
+#[cfg_attr(feature="alloc", doc=" ```")]
+#[cfg_attr(not(feature="alloc"), doc=" ```no_compile")]
 /// use encode_unicode::{IterExt, CharExt, Utf8Char};
 ///
 /// let chars: Vec<Utf8Char> = "💣 bomb 💣".chars().map(|c| c.to_utf8() ).collect();
@@ -232,10 +238,12 @@ impl<U:Borrow<Utf8Char>, I:Iterator<Item=U>> Read for Utf8CharSplitter<U,I> {
 pub struct Utf8CharIndices<'a>{
     str: &'a str,
     index: usize,
+    /// End of the run of ASCII that `index` is in, if it has been found.
+    ascii_end: usize,
 }
 impl<'a> From<&'a str> for Utf8CharIndices<'a> {
     fn from(s: &str) -> Utf8CharIndices {
-        Utf8CharIndices{str: s, index: 0}
+        Utf8CharIndices{str: s, index: 0, ascii_end: 0}
     }
 }
 impl<'a> Utf8CharIndices<'a> {
@@ -257,6 +265,16 @@ impl<'a> Utf8CharIndices<'a> {
 impl<'a> Iterator for Utf8CharIndices<'a> {
     type Item = (usize,Utf8Char);
     fn next(&mut self) -> Option<(usize,Utf8Char)> {
+        let bytes = self.str.as_bytes();
+        if self.index >= self.ascii_end  &&  self.index < bytes.len()  &&  bytes[self.index].is_ascii() {
+            self.ascii_end = self.index + ascii_prefix_len(&bytes[self.index..]);
+        }
+        if self.index < self.ascii_end {
+            // SAFETY: checked by ascii_prefix_len()
+            let item = (self.index, unsafe { Utf8Char::from_ascii_unchecked(bytes[self.index]) });
+            self.index += 1;
+            return Some(item);
+        }
         match Utf8Char::from_str_start(&self.str[self.index..]) {
             Ok((u8c, len)) => {
                 let item = (self.index, u8c);
@@ -283,6 +301,7 @@ impl<'a> DoubleEndedIterator for Utf8CharIndices<'a> {
             let starts = self.str.len() - len;
             let (u8c,_) = Utf8Char::from_str_start(&self.str[starts..]).unwrap();
             self.str = &self.str[..starts];
+            self.ascii_end = self.ascii_end.min(starts);
             Some((starts, u8c))
         } else {
             None
@@ -344,3 +363,245 @@ impl<'a> fmt::Debug for Utf8Chars<'a> {
             .finish()
     }
 }
+
+
+
+/// A run of ASCII characters or a single other character,
+/// produced by [`AsciiRuns`](struct.AsciiRuns.html).
+#[derive(Clone,Copy, Debug, PartialEq,Eq, Hash)]
+pub enum AsciiRunOrChar<'a> {
+    /// One or more ASCII characters.
+    ///
+    /// Is a `str` even when the `ascii` feature is enabled,
+    /// so that enabling it doesn't break code that matches on this variant.
+    /// Use [`as_ascii_str()`](#method.as_ascii_str) to get an `AsciiStr`.
+    Ascii(&'a str),
+    /// A character that is not ASCII.
+    Char(Utf8Char),
+}
+impl<'a> AsciiRunOrChar<'a> {
+    /// Get the run or character as a `str`.
+    pub fn as_str(&self) -> &str {
+        match self {
+            AsciiRunOrChar::Ascii(run) => run,
+            AsciiRunOrChar::Char(u8c) => u8c.as_str(),
+        }
+    }
+    /// Get the run of ASCII characters, if this is one.
+    #[cfg(feature="ascii")]
+    pub fn as_ascii_str(&self) -> Option<&'a AsciiStr> {
+        match *self {
+            // SAFETY: AsciiRuns only produces runs of ASCII
+            AsciiRunOrChar::Ascii(run) => Some(unsafe { AsciiStr::from_ascii_unchecked(run.as_bytes()) }),
+            AsciiRunOrChar::Char(_) => None,
+        }
+    }
+}
+
+/// An iterator over the runs of ASCII characters and the other characters
+/// of a string slice.
+///
+/// The runs are found a `usize` at a time, and are never empty,
+/// and two runs never come after each other.
+///
+/// This struct is created by the `ascii_runs()` method from [`StrExt`](../trait.StrExt.html)
+/// trait. See its documentation for an example.
+#[derive(Clone)]
+pub struct AsciiRuns<'a>(&'a str);
+impl<'a> From<&'a str> for AsciiRuns<'a> {
+    fn from(s: &str) -> AsciiRuns<'_> {
+        AsciiRuns(s)
+    }
+}
+impl<'a> AsciiRuns<'a> {
+    /// Extract the remainder of the source `str`.
+    pub fn as_str(&self) -> &'a str {
+        self.0
+    }
+}
+impl<'a> Iterator for AsciiRuns<'a> {
+    type Item = AsciiRunOrChar<'a>;
+    fn next(&mut self) -> Option<AsciiRunOrChar<'a>> {
+        let ascii = ascii_prefix_len(self.0.as_bytes());
+        if ascii != 0 {
+            let (run, rest) = self.0.split_at(ascii);
+            self.0 = rest;
+            Some(AsciiRunOrChar::Ascii(run))
+        } else {
+            let (u8c, len) = Utf8Char::from_str_start(self.0).ok()?;
+            self.0 = &self.0[len..];
+            Some(AsciiRunOrChar::Char(u8c))
+        }
+    }
+    fn size_hint(&self) -> (usize,Option<usize>) {
+        let len = self.0.len();
+        // a single run can cover the whole string
+        ((len != 0) as usize, Some(len))
+    }
+}
+impl<'a> DoubleEndedIterator for AsciiRuns<'a> {
+    fn next_back(&mut self) -> Option<AsciiRunOrChar<'a>> {
+        let bytes = self.0.as_bytes();
+        match bytes.last() {
+            None => None,
+            Some(last) if last.is_ascii() => {
+                let start = bytes.iter().rposition(|b| !b.is_ascii() ).map_or(0, |i| i+1 );
+                let (rest, run) = self.0.split_at(start);
+                self.0 = rest;
+                Some(AsciiRunOrChar::Ascii(run))
+            },
+            Some(_) => {
+                let len = 1 + bytes.iter().rev().take_while(|&&b| b & 0b1100_0000 == 0b1000_0000 ).count();
+                let (rest, c) = self.0.split_at(self.0.len()-len);
+                self.0 = rest;
+                Some(AsciiRunOrChar::Char(Utf8Char::from_str(c).unwrap()))
+            },
+        }
+    }
+}
+impl<'a> fmt::Debug for AsciiRuns<'a> {
+    fn fmt(&self,  fmtr: &mut fmt::Formatter) -> fmt::Result {
+        fmtr.debug_tuple("AsciiRuns")
+            .field(&self.0)
+            .finish()
+    }
+}
+
+
+/// Which unit [`CharPosition.column`](struct.CharPosition.html#structfield.column)
+/// is counted in.
+///
+/// Different tools disagree on what a column is, so pick the one that matches
+/// whatever the positions will be passed to.
+#[derive(Clone,Copy, Debug, PartialEq,Eq, Hash)]
+pub enum ColumnUnit {
+    /// Count UTF-8 bytes, like Rust's `str` indexes.
+    Utf8Bytes,
+    /// Count UTF-16 units, like JavaScript strings and the Language Server Protocol.
+    Utf16Units,
+    /// Count codepoints, like `.chars().count()`.
+    Codepoints,
+}
+
+/// The position of a codepoint, measured in several units at once.
+///
+/// All fields start at zero.
+/// Only `'\n'` starts a new line, so a `'\r'` before it is the last character
+/// of the line it ends.
+///
+/// When produced by [`Utf8CharPositionDecoder`](struct.Utf8CharPositionDecoder.html),
+/// each invalid byte is counted as if it had been replaced by one
+/// U+FFFD REPLACEMENT CHARACTER, except for `byte` and byte columns which
+/// count the original byte.
+#[derive(Clone,Copy, Default, Debug, PartialEq,Eq, Hash)]
+pub struct CharPosition {
+    /// Offset in UTF-8 bytes from the start of the text.
+    pub byte: usize,
+    /// Offset in UTF-16 units from the start of the text.
+    pub utf16: usize,
+    /// Number of codepoints before this one.
+    pub codepoint: usize,
+    /// Number of `'\n'`s before this codepoint.
+    pub line: usize,
+    /// Offset from the start of the line, in the unit the iterator was configured with.
+    pub column: usize,
+}
+impl CharPosition {
+    /// Move past a codepoint or invalid byte.
+    pub(crate) fn advance(&mut self,  utf8_len: usize,  utf16_len: usize,  newline: bool,
+                          unit: ColumnUnit) {
+        self.byte += utf8_len;
+        self.utf16 += utf16_len;
+        self.codepoint += 1;
+        if newline {
+            self.line += 1;
+            self.column = 0;
+        } else {
+            self.column += match unit {
+                ColumnUnit::Utf8Bytes => utf8_len,
+                ColumnUnit::Utf16Units => utf16_len,
+                ColumnUnit::Codepoints => 1,
+            };
+        }
+    }
+}
+
+
+/// An iterator over the `Utf8Char`s of a string slice, together with their
+/// byte, UTF-16 and codepoint offsets and their line and column.
+///
+/// This struct is created by the `utf8char_positions()` method from
+/// [`StrExt`](../trait.StrExt.html). See its documentation for more.
+#[derive(Clone)]
+pub struct Utf8CharPositions<'a> {
+    str: &'a str,
+    next: CharPosition,
+    unit: ColumnUnit,
+}
+impl<'a> From<&'a str> for Utf8CharPositions<'a> {
+    /// Creates an iterator which counts columns in codepoints.
+    fn from(s: &str) -> Utf8CharPositions<'_> {
+        Utf8CharPositions { str: s,  next: CharPosition::default(),  unit: ColumnUnit::Codepoints }
+    }
+}
+impl<'a> Utf8CharPositions<'a> {
+    /// Change which unit columns are counted in.
+    ///
+    /// Changing the unit in the middle of a line only affects characters
+    /// after the current column.
+    pub fn with_column_unit(mut self,  unit: ColumnUnit) -> Self {
+        self.unit = unit;
+        self
+    }
+    /// Get the position of the next codepoint,
+    /// or of the end of the string if there are none left.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use encode_unicode::StrExt;
+    /// use encode_unicode::iterator::ColumnUnit;
+    ///
+    /// let mut iter = "æ\n".utf8char_positions(ColumnUnit::Utf8Bytes);
+    /// assert_eq!(iter.position().column, 0);
+    /// iter.next();
+    /// assert_eq!(iter.position().column, 2);
+    /// iter.next();
+    /// assert_eq!((iter.position().line, iter.position().column), (1, 0));
+    /// assert_eq!(iter.position().byte, 3);
+    /// ```
+    pub fn position(&self) -> CharPosition {
+        self.next
+    }
+    /// Extract the remainder of the source `str`.
+    pub fn as_str(&self) -> &'a str {
+        &self.str[self.next.byte..]
+    }
+}
+impl<'a> Iterator for Utf8CharPositions<'a> {
+    type Item = (CharPosition, Utf8Char);
+    fn next(&mut self) -> Option<Self::Item> {
+        match Utf8Char::from_str_start(self.as_str()) {
+            Ok((u8c, len)) => {
+                let position = self.next;
+                let utf16_len = if len == 4 {2} else {1};
+                self.next.advance(len, utf16_len, u8c == b'\n', self.unit);
+                Some((position, u8c))
+            },
+            Err(EmptyStrError) => None
+        }
+    }
+    fn size_hint(&self) -> (usize,Option<usize>) {
+        let len = self.str.len() - self.next.byte;
+        (len.wrapping_add(3)/4, Some(len))
+    }
+}
+impl<'a> fmt::Debug for Utf8CharPositions<'a> {
+    fn fmt(&self,  fmtr: &mut fmt::Formatter) -> fmt::Result {
+        fmtr.debug_struct("Utf8CharPositions")
+            .field("position", &self.next)
+            .field("unit", &self.unit)
+            .field("remaining", &self.as_str())
+            .finish()
+    }
+}
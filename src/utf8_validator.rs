@@ -0,0 +1,322 @@
+/* Copyright 2022 Torbjørn Birch Moltu
+ *
+ * Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+ * http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+ * http://opensource.org/licenses/MIT>, at your option. This file may not be
+ * copied, modified, or distributed except according to those terms.
+ */
+
+//! A byte-at-a-time UTF-8 state machine which all the UTF-8 decoders are built on.
+//!
+//! It's in the style of [Björn Höhrmann's decoder](https://bjoern.hoehrmann.de/utf-8/decoder/dfa/):
+//! Each byte is first mapped to one of twelve classes,
+//! and the class together with the current state then gives the next state.
+//!
+//! Unlike that decoder, what's wrong with an invalid sequence is remembered,
+//! and overlong encodings, surrogates and too high codepoints are only
+//! reported once the sequence is complete, so that an interrupted sequence is
+//! always reported as such.
+
+use crate::errors::{Utf8Error, Utf8ErrorKind};
+use crate::errors::Utf8ErrorKind::*;
+
+
+// byte classes
+const ASCII: u8 = 0; // 0x00..=0x7f
+const CONT_80: u8 = 1; // 0x80..=0x8f
+const CONT_90: u8 = 2; // 0x90..=0x9f
+const CONT_A0: u8 = 3; // 0xa0..=0xbf
+const NON_UTF8: u8 = 4; // 0xc0, 0xc1 and 0xf5..=0xff
+const START_2: u8 = 5; // 0xc2..=0xdf
+const START_E0: u8 = 6;
+const START_3: u8 = 7; // 0xe1..=0xec and 0xee..=0xef
+const START_ED: u8 = 8;
+const START_F0: u8 = 9;
+const START_4: u8 = 10; // 0xf1..=0xf3
+const START_F4: u8 = 11;
+const CLASSES: usize = 12;
+
+// states, with the errors last
+const ACCEPT: u8 = 0;
+const NEED_1: u8 = 1;
+const NEED_2: u8 = 2;
+const NEED_3: u8 = 3;
+const AFTER_E0: u8 = 4; // the next byte decides whether it's overlong
+const AFTER_ED: u8 = 5; // the next byte decides whether it's a surrogate
+const AFTER_F0: u8 = 6; // the next byte decides whether it's overlong
+const AFTER_F4: u8 = 7; // the next byte decides whether it's too high
+const OVERLONG_NEED_1: u8 = 8;
+const OVERLONG_NEED_2: u8 = 9;
+const RESERVED_NEED_1: u8 = 10;
+const TOO_HIGH_NEED_1: u8 = 11;
+const TOO_HIGH_NEED_2: u8 = 12;
+const NON_UTF8_BYTE: u8 = 13;
+const UNEXPECTED_CONTINUATION: u8 = 14;
+const INTERRUPTED: u8 = 15;
+const OVERLONG: u8 = 16;
+const RESERVED: u8 = 17;
+const TOO_HIGH: u8 = 18;
+const FIRST_ERROR: u8 = NON_UTF8_BYTE;
+/// in the same order as the error states
+const ERRORS: [Utf8ErrorKind; 6] = [
+    NonUtf8Byte,
+    UnexpectedContinuationByte,
+    InterruptedSequence,
+    OverlongEncoding,
+    Utf16ReservedCodepoint,
+    TooHighCodepoint,
+];
+const STATES: usize = FIRST_ERROR as usize + ERRORS.len();
+
+const fn class_of(byte: u8) -> u8 {
+    match byte {
+        0x00..=0x7f => ASCII,
+        0x80..=0x8f => CONT_80,
+        0x90..=0x9f => CONT_90,
+        0xa0..=0xbf => CONT_A0,
+        0xc0..=0xc1 | 0xf5..=0xff => NON_UTF8,
+        0xc2..=0xdf => START_2,
+        0xe0 => START_E0,
+        0xe1..=0xec | 0xee..=0xef => START_3,
+        0xed => START_ED,
+        0xf0 => START_F0,
+        0xf1..=0xf3 => START_4,
+        0xf4 => START_F4,
+    }
+}
+
+const fn transition(state: u8,  class: u8) -> u8 {
+    let is_continuation = class == CONT_80 || class == CONT_90 || class == CONT_A0;
+    match state {
+        ACCEPT => match class {
+            ASCII => ACCEPT,
+            NON_UTF8 => NON_UTF8_BYTE,
+            START_2 => NEED_1,
+            START_E0 => AFTER_E0,
+            START_3 => NEED_2,
+            START_ED => AFTER_ED,
+            START_F0 => AFTER_F0,
+            START_4 => NEED_3,
+            START_F4 => AFTER_F4,
+            _ => UNEXPECTED_CONTINUATION,
+        },
+        // errors don't go away by themselves
+        _ if state >= FIRST_ERROR => state,
+        // in the middle of a sequence
+        _ if !is_continuation => INTERRUPTED,
+        NEED_1 => ACCEPT,
+        NEED_2 => NEED_1,
+        NEED_3 => NEED_2,
+        AFTER_E0 => if class == CONT_A0 {NEED_1} else {OVERLONG_NEED_1},
+        AFTER_ED => if class == CONT_A0 {RESERVED_NEED_1} else {NEED_1},
+        AFTER_F0 => if class == CONT_80 {OVERLONG_NEED_2} else {NEED_2},
+        AFTER_F4 => if class == CONT_80 {NEED_2} else {TOO_HIGH_NEED_2},
+        OVERLONG_NEED_2 => OVERLONG_NEED_1,
+        TOO_HIGH_NEED_2 => TOO_HIGH_NEED_1,
+        OVERLONG_NEED_1 => OVERLONG,
+        RESERVED_NEED_1 => RESERVED,
+        _ => TOO_HIGH,
+    }
+}
+
+const fn byte_classes() -> [u8; 256] {
+    let mut classes = [0; 256];
+    let mut byte = 0;
+    while byte < 256 {
+        classes[byte] = class_of(byte as u8);
+        byte += 1;
+    }
+    classes
+}
+
+const fn transitions() -> [u8; STATES*CLASSES] {
+    let mut table = [0; STATES*CLASSES];
+    let mut state = 0;
+    while state < STATES {
+        let mut class = 0;
+        while class < CLASSES {
+            table[state*CLASSES+class] = transition(state as u8, class as u8);
+            class += 1;
+        }
+        state += 1;
+    }
+    table
+}
+
+const BYTE_CLASSES: [u8; 256] = byte_classes();
+const TRANSITIONS: [u8; STATES*CLASSES] = transitions();
+
+
+/// A state machine for validating UTF-8 one byte at a time.
+///
+/// Useful for validating text which arrives in pieces without having to
+/// buffer incomplete codepoints, and for checking UTF-8 in `const` contexts.
+///
+/// Once an invalid sequence is detected the validator stays in that error
+/// state, so it only needs to be checked at the end.
+/// Call [`finish()`](#method.finish) to also detect input that ends in the
+/// middle of a codepoint, or start over with a new validator to continue
+/// after an error.
+///
+/// The errors are the same as returned by all the other UTF-8 decoding
+/// functions and iterators in this crate.
+///
+/// # Examples
+///
+/// ```
+/// use encode_unicode::Utf8Validator;
+/// use encode_unicode::error::Utf8ErrorKind;
+///
+/// const fn validate(bytes: &[u8]) -> Utf8Validator {
+///     let mut validator = Utf8Validator::new();
+///     let mut i = 0;
+///     while i < bytes.len() {
+///         validator = validator.step(bytes[i]);
+///         i += 1;
+///     }
+///     validator
+/// }
+///
+/// const GREETING: Utf8Validator = validate("Hello, 世界".as_bytes());
+/// assert!(GREETING.is_at_boundary());
+/// assert_eq!(GREETING.finish(), Ok(()));
+///
+/// let incomplete = validate(b"\xf0\x9f");
+/// assert_eq!(incomplete.error(), None);
+/// assert_eq!(incomplete.finish().unwrap_err().kind(), Utf8ErrorKind::TooFewBytes);
+///
+/// let surrogate = validate(b"\xed\xa0\x80 and more");
+/// assert_eq!(surrogate.error(), Some(Utf8ErrorKind::Utf16ReservedCodepoint));
+/// ```
+#[derive(Clone,Copy, Debug, Default, PartialEq,Eq, Hash)]
+pub struct Utf8Validator {
+    state: u8,
+}
+
+impl Utf8Validator {
+    /// Create a validator that expects the start of a codepoint.
+    pub const fn new() -> Self {
+        Utf8Validator { state: ACCEPT }
+    }
+
+    /// Advance the state machine by one byte.
+    #[inline]
+    #[must_use]
+    pub const fn step(self,  byte: u8) -> Self {
+        let class = BYTE_CLASSES[byte as usize] as usize;
+        Utf8Validator { state: TRANSITIONS[self.state as usize * CLASSES + class] }
+    }
+
+    /// Returns `true` if the bytes so far are valid UTF-8 and did not end
+    /// in the middle of a codepoint.
+    pub const fn is_at_boundary(self) -> bool {
+        self.state == ACCEPT
+    }
+
+    /// Returns `true` if more bytes are needed to complete the current codepoint.
+    ///
+    /// The sequence might already be known to be invalid for other reasons
+    /// than being interrupted, but that is only reported when it's complete,
+    /// or by [`finish()`](#method.finish).
+    pub const fn is_in_sequence(self) -> bool {
+        self.state != ACCEPT  &&  self.state < FIRST_ERROR
+    }
+
+    /// Get what's wrong with the first invalid sequence, if one has been encountered.
+    ///
+    /// This does not consider ending in the middle of a codepoint to be an
+    /// error, see [`finish()`](#method.finish) for that.
+    pub const fn error(self) -> Option<Utf8ErrorKind> {
+        if self.state >= FIRST_ERROR {
+            Some(ERRORS[(self.state - FIRST_ERROR) as usize])
+        } else {
+            None
+        }
+    }
+
+    /// Check that all the bytes were valid UTF-8
+    /// and that the last codepoint was complete.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error from [`error()`](#method.error),
+    /// or if the input ends in the middle of a codepoint either
+    /// [`TooFewBytes`](error/enum.Utf8ErrorKind.html#variant.TooFewBytes)
+    /// or what more bytes can't fix.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use encode_unicode::Utf8Validator;
+    /// use encode_unicode::error::Utf8ErrorKind;
+    ///
+    /// let incomplete = Utf8Validator::new().step(0xe0).step(0xa0);
+    /// assert_eq!(incomplete.finish().unwrap_err().kind(), Utf8ErrorKind::TooFewBytes);
+    /// let overlong = Utf8Validator::new().step(0xe0).step(0x80);
+    /// assert_eq!(overlong.finish().unwrap_err().kind(), Utf8ErrorKind::OverlongEncoding);
+    /// ```
+    pub const fn finish(self) -> Result<(), Utf8Error> {
+        if self.is_in_sequence() {
+            Err(Utf8Error { kind: self.incomplete_error() })
+        } else if let Some(kind) = self.error() {
+            Err(Utf8Error { kind })
+        } else {
+            Ok(())
+        }
+    }
+
+    /// The error for a sequence that ends in the current state.
+    pub(crate) const fn incomplete_error(self) -> Utf8ErrorKind {
+        match self.state {
+            OVERLONG_NEED_1 | OVERLONG_NEED_2 => OverlongEncoding,
+            RESERVED_NEED_1 => Utf16ReservedCodepoint,
+            TOO_HIGH_NEED_1 | TOO_HIGH_NEED_2 => TooHighCodepoint,
+            _ => TooFewBytes,
+        }
+    }
+
+    /// Returns `false` once no more bytes can make the current sequence valid,
+    /// including for sequences whose error is only reported when they're complete.
+    pub(crate) const fn can_become_valid(self) -> bool {
+        self.state < OVERLONG_NEED_1
+    }
+}
+
+
+/// Decode the first codepoint of a slice and return it together with its length in bytes.
+///
+/// Errors are reported in the order they are encountered, except that
+/// overlong encodings, surrogates and too high codepoints are only reported
+/// once all the bytes of the sequence are present or the slice ends.
+/// `TooFewBytes` is therefore only returned if the slice is the start of a
+/// valid sequence.
+///
+/// The returned codepoint is always valid.
+pub(crate) const fn decode_utf8_start(src: &[u8]) -> Result<(u32, usize), Utf8ErrorKind> {
+    if src.is_empty() {
+        return Err(TooFewBytes);
+    }
+    let first = src[0];
+    if first < 0x80 {
+        return Ok((first as u32, 1));
+    }
+    let mut validator = Utf8Validator::new().step(first);
+    if let Some(error) = validator.error() {
+        return Err(error);
+    }
+    // strip the length header
+    let mut codepoint = (first & (0xff >> (first.leading_ones()+1))) as u32;
+    let mut len = 1;
+    while validator.is_in_sequence() {
+        if len == src.len() {
+            return Err(validator.incomplete_error());
+        }
+        validator = validator.step(src[len]);
+        codepoint = (codepoint << 6)  |  (src[len] & 0b0011_1111) as u32;
+        len += 1;
+    }
+    match validator.error() {
+        Some(error) => Err(error),
+        None => Ok((codepoint, len)),
+    }
+}
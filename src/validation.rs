@@ -0,0 +1,249 @@
+/* Copyright 2022 Torbjørn Birch Moltu
+ *
+ * Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+ * http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+ * http://opensource.org/licenses/MIT>, at your option. This file may not be
+ * copied, modified, or distributed except according to those terms.
+ */
+
+//! Validating UTF-16 many units at a time.
+//!
+//! For a block of units, the leading and trailing surrogates are found as
+//! bitmasks, and the block is valid if the trailing surrogates are exactly
+//! the units that come after a leading surrogate, which includes a leading
+//! surrogate at the end of the previous block.
+//! The block implementations only find how much of the slice is valid,
+//! and the first error is then found one unit at a time,
+//! so that every implementation reports the same error.
+//!
+//! AVX2 is used if it's detected at runtime (which requires the `std` feature)
+//! or enabled at compile time, and the same goes for NEON on aarch64
+//! if the `neon` feature is enabled (as `#[target_feature]` on aarch64 requires Rust 1.61).
+//! Otherwise SSE2 is used on x86 and `x86_64` if it's enabled at compile time
+//! (which it is by default on `x86_64`), and `usize`-sized blocks elsewhere.
+
+use crate::errors::{PositionedError, Utf16PairError};
+use crate::errors::Utf16PairError::*;
+
+#[cfg(all(target_arch="x86", any(target_feature="sse2", feature="std", target_feature="avx2")))]
+use core::arch::x86::*;
+#[cfg(all(target_arch="x86_64", any(target_feature="sse2", feature="std", target_feature="avx2")))]
+use core::arch::x86_64::*;
+#[cfg(all(feature="neon", target_arch="aarch64", any(feature="std", target_feature="neon")))]
+use core::arch::aarch64::*;
+
+/// Check that every surrogate in the slice is part of a pair,
+/// starting at the first unit that might be a surrogate.
+pub(crate) fn check_surrogate_pairs(units: &[u16]) -> Result<(), PositionedError<Utf16PairError>> {
+    let first = match units.iter().position(|&u| u & 0xf800 == 0xd800 ) {
+        Some(first) => first,
+        None => return Ok(()),
+    };
+    let mut i = first;
+    while i < units.len() {
+        let error = match units[i] & 0xfc00 {
+            0xd800 => match units.get(i+1) {
+                Some(&next) if next & 0xfc00 == 0xdc00 => {
+                    i += 2;
+                    continue;
+                },
+                Some(_) => UnmatchedLeadingSurrogate,
+                None => Incomplete,
+            },
+            0xdc00 => UnexpectedTrailingSurrogate,
+            _ => {
+                i += 1;
+                continue;
+            }
+        };
+        return Err(PositionedError { offset: i,  error });
+    }
+    Ok(())
+}
+
+/// Find how many units at the start of the slice are valid UTF-16,
+/// checking eight units at a time.
+///
+/// The returned length never ends between the units of a surrogate pair,
+/// but can be less than the length of the slice even if the slice is valid.
+#[cfg(all(any(target_arch="x86", target_arch="x86_64"), target_feature="sse2"))]
+fn valid_blocks(units: &[u16]) -> usize {
+    let (mut checked, mut carry) = (0, 0);
+    while checked + 8 <= units.len() {
+        // SAFETY: there are eight units after checked, and the load doesn't need to be aligned.
+        let (leading, trailing) = unsafe {
+            let block = _mm_loadu_si128(units.as_ptr().add(checked) as *const __m128i);
+            let kinds = _mm_and_si128(block, _mm_set1_epi16(0xfc00u16 as i16));
+            let leading = _mm_cmpeq_epi16(kinds, _mm_set1_epi16(0xd800u16 as i16));
+            let trailing = _mm_cmpeq_epi16(kinds, _mm_set1_epi16(0xdc00u16 as i16));
+            // two bits per unit
+            (_mm_movemask_epi8(leading) as u32,  _mm_movemask_epi8(trailing) as u32)
+        };
+        if trailing != ((leading << 2) | carry) & 0xffff {
+            break;
+        }
+        carry = leading >> 14;
+        checked += 8;
+    }
+    checked - (carry != 0) as usize
+}
+
+/// Find how many units at the start of the slice are valid UTF-16,
+/// checking a `usize` at a time.
+///
+/// The returned length never ends between the units of a surrogate pair,
+/// but can be less than the length of the slice even if the slice is valid.
+#[cfg(not(all(any(target_arch="x86", target_arch="x86_64"), target_feature="sse2")))]
+fn valid_blocks(units: &[u16]) -> usize {
+    use crate::lengths::nonzero_u16_lanes;
+    const UNITS_PER_WORD: usize = core::mem::size_of::<usize>() / 2;
+    const ONES: usize = usize::MAX / 0xffff;
+    const HIGH: usize = ONES * 0x8000;
+    let (mut checked, mut carry) = (0, 0);
+    for word in units.chunks_exact(UNITS_PER_WORD) {
+        let mut lanes = 0;
+        for &unit in word.iter().rev() {
+            lanes = (lanes << 16) | unit as usize;
+        }
+        let kinds = lanes & (ONES * 0xfc00);
+        let leading = !nonzero_u16_lanes(kinds ^ (ONES * 0xd800)) & HIGH;
+        let trailing = !nonzero_u16_lanes(kinds ^ (ONES * 0xdc00)) & HIGH;
+        // the first unit is in the lowest lane
+        if trailing != (leading << 16) | carry {
+            break;
+        }
+        carry = leading >> (usize::BITS - 16);
+        checked += UNITS_PER_WORD;
+    }
+    checked - (carry != 0) as usize
+}
+
+/// Find how many units at the start of the slice are valid UTF-16,
+/// checking sixteen units at a time.
+///
+/// # Safety
+///
+/// The CPU must support AVX2.
+#[cfg(all(any(target_arch="x86", target_arch="x86_64"), any(feature="std", target_feature="avx2")))]
+#[target_feature(enable="avx2")]
+unsafe fn valid_blocks_avx2(units: &[u16]) -> usize {
+    let (mut checked, mut carry) = (0, 0);
+    while checked + 16 <= units.len() {
+        // SAFETY: there are sixteen units after checked, the load doesn't
+        // need to be aligned, and the caller guarantees AVX2 is available.
+        let (leading, trailing) = unsafe {
+            let block = _mm256_loadu_si256(units.as_ptr().add(checked) as *const __m256i);
+            let kinds = _mm256_and_si256(block, _mm256_set1_epi16(0xfc00u16 as i16));
+            let leading = _mm256_cmpeq_epi16(kinds, _mm256_set1_epi16(0xd800u16 as i16));
+            let trailing = _mm256_cmpeq_epi16(kinds, _mm256_set1_epi16(0xdc00u16 as i16));
+            (_mm256_movemask_epi8(leading) as u32,  _mm256_movemask_epi8(trailing) as u32)
+        };
+        if trailing != (leading << 2) | carry {
+            break;
+        }
+        carry = leading >> 30;
+        checked += 16;
+    }
+    // the rest might fill a smaller block
+    let valid = checked - (carry != 0) as usize;
+    valid + valid_blocks(&units[valid..])
+}
+
+/// Find how many units at the start of the slice are valid UTF-16,
+/// checking eight units at a time.
+///
+/// # Safety
+///
+/// The CPU must support NEON.
+#[cfg(all(feature="neon", target_arch="aarch64", any(feature="std", target_feature="neon")))]
+#[target_feature(enable="neon")]
+#[allow(unused_unsafe)] // newer Rust versions make the arithmetic intrinsics safe here
+unsafe fn valid_blocks_neon(units: &[u16]) -> usize {
+    let mut checked = 0;
+    // SAFETY: the caller guarantees NEON is available.
+    let mut leading = unsafe { vdupq_n_u16(0) };
+    while checked + 8 <= units.len() {
+        // SAFETY: there are eight units after checked,
+        // and the caller guarantees NEON is available.
+        let (next_leading, all_paired) = unsafe {
+            let block = vld1q_u16(units.as_ptr().add(checked));
+            let kinds = vandq_u16(block, vdupq_n_u16(0xfc00));
+            let next_leading = vceqq_u16(kinds, vdupq_n_u16(0xd800));
+            let trailing = vceqq_u16(kinds, vdupq_n_u16(0xdc00));
+            // the lanes after a leading surrogate, including the last lane of the previous block
+            let after_leading = vextq_u16::<7>(leading, next_leading);
+            (next_leading,  vminvq_u16(vceqq_u16(trailing, after_leading)) == 0xffff)
+        };
+        if !all_paired {
+            break;
+        }
+        leading = next_leading;
+        checked += 8;
+    }
+    // SAFETY: the caller guarantees NEON is available.
+    let carry = unsafe { vgetq_lane_u16::<7>(leading) };
+    // the rest might fill a smaller block
+    let valid = checked - (carry != 0) as usize;
+    valid + valid_blocks(&units[valid..])
+}
+
+/// Find how many units at the start of the slice are valid UTF-16,
+/// with the widest instructions available.
+fn valid_prefix(units: &[u16]) -> usize {
+    #[cfg(all(any(target_arch="x86", target_arch="x86_64"), any(feature="std", target_feature="avx2")))] {
+        #[cfg(target_feature="avx2")]
+        let has_avx2 = true;
+        #[cfg(not(target_feature="avx2"))]
+        let has_avx2 = std::is_x86_feature_detected!("avx2");
+        if has_avx2 {
+            // SAFETY: AVX2 is available
+            return unsafe { valid_blocks_avx2(units) };
+        }
+    }
+    #[cfg(all(feature="neon", target_arch="aarch64", any(feature="std", target_feature="neon")))] {
+        #[cfg(target_feature="neon")]
+        let has_neon = true;
+        #[cfg(not(target_feature="neon"))]
+        let has_neon = std::arch::is_aarch64_feature_detected!("neon");
+        if has_neon {
+            // SAFETY: NEON is available
+            return unsafe { valid_blocks_neon(units) };
+        }
+    }
+    valid_blocks(units)
+}
+
+/// Check that a slice is valid UTF-16, several units at a time.
+///
+/// This is much faster than decoding the slice when it contains long runs
+/// without surrogates, or many surrogate pairs.
+///
+/// AVX2, and NEON on aarch64 with the `neon` feature, are detected at runtime
+/// when the `std` feature is enabled; without it they are only used if
+/// enabled with `-C target-feature`.
+/// Otherwise SSE2 is used on x86 and `x86_64` when the target has it
+/// (which all `x86_64` targets do).
+///
+/// # Errors
+///
+/// Returns the position and type of the first unpaired surrogate,
+/// which is the same as the first error
+/// [`Utf16CharDecoder`](../iterator/struct.Utf16CharDecoder.html) produces.
+///
+/// # Examples
+///
+/// ```
+/// use encode_unicode::slice::validate_utf16;
+/// use encode_unicode::error::Utf16PairError;
+///
+/// let units = "Wikipedia: 😀 is a smiley".encode_utf16().collect::<Vec<u16>>();
+/// assert_eq!(validate_utf16(&units), Ok(()));
+/// let error = validate_utf16(&units[..12]).unwrap_err();
+/// assert_eq!((error.offset(), *error.error()), (11, Utf16PairError::Incomplete));
+/// ```
+pub fn validate_utf16(units: &[u16]) -> Result<(), PositionedError<Utf16PairError>> {
+    let valid = valid_prefix(units);
+    check_surrogate_pairs(&units[valid..]).map_err(|PositionedError { offset, error }| {
+        PositionedError { offset: valid + offset,  error }
+    })
+}
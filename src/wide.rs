@@ -0,0 +1,310 @@
+/* Copyright 2022 Torbjørn Birch Moltu
+ *
+ * Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+ * http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+ * http://opensource.org/licenses/MIT>, at your option. This file may not be
+ * copied, modified, or distributed except according to those terms.
+ */
+
+//! Interop with the [widestring](https://docs.rs/widestring/1/widestring/) crate
+//! and NUL-terminated C `wchar_t` strings.
+//!
+//! Requires the feature "widestring".
+//!
+//! `wchar_t` is UTF-16 on Windows and UTF-32 on most other platforms.
+//! The functions here are generic over the unit type, so that code using
+//! [`widestring::WideChar`](https://docs.rs/widestring/1/widestring/type.WideChar.html)
+//! works with both.
+//!
+//! This module also has `From`, `TryFrom`, `Extend` and `FromIterator` impls
+//! between `Utf16Char` and widestring's `Utf16String`, `U16String`, `U16CString` and `U32CString`.
+//!
+//! # Examples
+//!
+//! ```
+//! use encode_unicode::wide::{encode_nul_terminated, decode_nul_terminated};
+//! use widestring::WideChar;
+//!
+//! let wide = encode_nul_terminated::<WideChar>("bløtkake 🎂").unwrap();
+//! assert_eq!(wide.last(), Some(&0));
+//! // a C function would get `wide.as_ptr()`
+//! assert_eq!(decode_nul_terminated(&wide).as_deref(), Ok("bløtkake 🎂"));
+//!
+//! let error = encode_nul_terminated::<WideChar>("nul\0byte").unwrap_err();
+//! assert_eq!(error.offset(), 3);
+//! ```
+
+use crate::errors::{CodepointError, FromStrError, InteriorNulError, PositionedError, Utf16PairError};
+use crate::traits::{CharExt, SliceExt};
+use crate::utf16_char::Utf16Char;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::iter::FromIterator;
+use widestring::{U16CString, U32CString, U16String, Utf16Str, Utf16String};
+
+mod private {
+    pub trait Sealed {}
+    impl Sealed for u16 {}
+    impl Sealed for u32 {}
+}
+
+/// A unit of a wide string: `u16` for UTF-16 or `u32` for UTF-32.
+///
+/// This trait is sealed and cannot be implemented outside this crate.
+pub trait WideUnit: Copy + Eq + private::Sealed {
+    /// What's wrong with an invalid unit or sequence of units.
+    type Error;
+    #[doc(hidden)]
+    const NUL: Self;
+    #[doc(hidden)]
+    fn push_char(c: char,  dst: &mut Vec<Self>);
+    #[doc(hidden)]
+    fn decode_into(units: &[Self],  dst: &mut String) -> Result<(), PositionedError<Self::Error>>;
+}
+
+impl WideUnit for u16 {
+    type Error = Utf16PairError;
+    const NUL: Self = 0;
+    fn push_char(c: char,  dst: &mut Vec<Self>) {
+        let units = c.to_utf16_array();
+        dst.push(units[0]);
+        if units[1] != 0 {
+            dst.push(units[1]);
+        }
+    }
+    fn decode_into(units: &[Self],  dst: &mut String) -> Result<(), PositionedError<Self::Error>> {
+        for (offset, result, _) in units.utf16char_indices() {
+            match result {
+                Ok(u16c) => dst.push(u16c.to_char()),
+                Err(error) => return Err(PositionedError { offset,  error }),
+            }
+        }
+        Ok(())
+    }
+}
+
+impl WideUnit for u32 {
+    type Error = CodepointError;
+    const NUL: Self = 0;
+    fn push_char(c: char,  dst: &mut Vec<Self>) {
+        dst.push(c as u32);
+    }
+    fn decode_into(units: &[Self],  dst: &mut String) -> Result<(), PositionedError<Self::Error>> {
+        for (offset, &unit) in units.iter().enumerate() {
+            match char::from_u32_detailed(unit) {
+                Ok(c) => dst.push(c),
+                Err(error) => return Err(PositionedError { offset,  error }),
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Encode a string as UTF-16 or UTF-32 with a NUL terminator,
+/// for passing to C functions that take a `wchar_t` string.
+///
+/// # Errors
+///
+/// Returns the byte offset of the first NUL character in `s`,
+/// as C would see the string as ending there.
+///
+/// # Examples
+///
+/// ```
+/// use encode_unicode::wide::encode_nul_terminated;
+///
+/// assert_eq!(encode_nul_terminated::<u16>("a😀"), Ok(vec![0x61, 0xd83d, 0xde00, 0]));
+/// assert_eq!(encode_nul_terminated::<u32>("a😀"), Ok(vec![0x61, 0x1f600, 0]));
+/// assert_eq!(encode_nul_terminated::<u32>(""), Ok(vec![0]));
+/// assert_eq!(encode_nul_terminated::<u16>("😀\0").unwrap_err().offset(), 4);
+/// ```
+pub fn encode_nul_terminated<W: WideUnit>(s: &str) -> Result<Vec<W>, PositionedError<InteriorNulError>> {
+    if let Some(offset) = s.find('\0') {
+        return Err(PositionedError { offset,  error: InteriorNulError });
+    }
+    // no character is encoded as more units than bytes
+    let mut units = Vec::with_capacity(s.len() + 1);
+    for c in s.chars() {
+        W::push_char(c, &mut units);
+    }
+    units.push(W::NUL);
+    Ok(units)
+}
+
+/// Decode an UTF-16 or UTF-32 string up to the first NUL,
+/// such as a buffer filled by a C function.
+///
+/// Anything after the first NUL is ignored,
+/// and if there is none the whole slice is decoded.
+///
+/// # Errors
+///
+/// Returns the offset and type of the first invalid unit or surrogate.
+///
+/// # Examples
+///
+/// ```
+/// use encode_unicode::wide::decode_nul_terminated;
+/// use encode_unicode::error::{CodepointError, Utf16PairError};
+///
+/// let buffer: [u16; 6] = [0x61, 0xd83d, 0xde00, 0, 0xdc00, 0];
+/// assert_eq!(decode_nul_terminated(&buffer).as_deref(), Ok("a😀"));
+/// let error = decode_nul_terminated(&buffer[2..]).unwrap_err();
+/// assert_eq!((error.offset(), *error.error()), (0, Utf16PairError::UnexpectedTrailingSurrogate));
+///
+/// assert_eq!(decode_nul_terminated(&[0x62u32, 0x1f600]).as_deref(), Ok("b😀"));
+/// let error = decode_nul_terminated(&[0x62u32, 0x110000, 0]).unwrap_err();
+/// assert_eq!((error.offset(), *error.error()), (1, CodepointError::TooHigh));
+/// ```
+pub fn decode_nul_terminated<W: WideUnit>(buf: &[W]) -> Result<String, PositionedError<W::Error>> {
+    let len = buf.iter().position(|&unit| unit == W::NUL ).unwrap_or(buf.len());
+    let mut s = String::with_capacity(len);
+    W::decode_into(&buf[..len], &mut s)?;
+    Ok(s)
+}
+
+/// Decode a NUL-terminated UTF-16 or UTF-32 string returned by a C function.
+///
+/// # Safety
+///
+/// `ptr` must be non-null and point to a readable sequence of units
+/// that ends with a NUL.
+///
+/// # Errors
+///
+/// Returns the offset and type of the first invalid unit or surrogate.
+///
+/// # Examples
+///
+/// ```
+/// use encode_unicode::wide::{decode_nul_terminated_ptr, encode_nul_terminated};
+///
+/// let wide = encode_nul_terminated::<u32>("smørbrød").unwrap();
+/// let decoded = unsafe { decode_nul_terminated_ptr(wide.as_ptr()) };
+/// assert_eq!(decoded.as_deref(), Ok("smørbrød"));
+/// ```
+#[allow(unused_unsafe)] // older Rust versions consider the block redundant
+pub unsafe fn decode_nul_terminated_ptr<W: WideUnit>(ptr: *const W) -> Result<String, PositionedError<W::Error>> {
+    let mut len = 0;
+    // SAFETY: the caller guarantees that every unit up to and including the NUL is readable.
+    unsafe {
+        while *ptr.add(len) != W::NUL {
+            len += 1;
+        }
+        decode_nul_terminated(core::slice::from_raw_parts(ptr, len))
+    }
+}
+
+
+/// Requires the feature "widestring".
+impl From<Utf16Char> for Utf16String {
+    fn from(u16c: Utf16Char) -> Self {
+        let mut s = Utf16String::new();
+        s.push(u16c.to_char());
+        s
+    }
+}
+/// Requires the feature "widestring".
+impl From<Utf16Char> for U16String {
+    fn from(u16c: Utf16Char) -> Self {
+        U16String::from_vec(u16c.as_ref())
+    }
+}
+/// Requires the feature "widestring".
+///
+/// Fails if the character is NUL.
+impl TryFrom<Utf16Char> for U16CString {
+    type Error = InteriorNulError;
+    fn try_from(u16c: Utf16Char) -> Result<Self, InteriorNulError> {
+        // from_vec() would accept NUL as the terminator
+        match u16c.to_char() {
+            '\0' => Err(InteriorNulError),
+            _ => Ok(U16CString::from_vec_truncate(u16c.as_ref())),
+        }
+    }
+}
+/// Requires the feature "widestring".
+///
+/// Fails if the character is NUL.
+impl TryFrom<Utf16Char> for U32CString {
+    type Error = InteriorNulError;
+    fn try_from(u16c: Utf16Char) -> Result<Self, InteriorNulError> {
+        // from_vec() would accept NUL as the terminator
+        match u16c.to_char() {
+            '\0' => Err(InteriorNulError),
+            c => Ok(U32CString::from_vec_truncate([c as u32])),
+        }
+    }
+}
+/// Requires the feature "widestring".
+///
+/// The string must contain exactly one codepoint, like for
+/// [`Utf16Char::from_str()`](../struct.Utf16Char.html#impl-FromStr).
+impl<'a> TryFrom<&'a Utf16Str> for Utf16Char {
+    type Error = FromStrError;
+    fn try_from(s: &'a Utf16Str) -> Result<Self, FromStrError> {
+        match Utf16Char::from_slice_start(s.as_slice()) {
+            Ok((u16c,units)) if units == s.len() => Ok(u16c),
+            Ok((_,_)) => Err(FromStrError::MultipleCodepoints),
+            Err(_) => Err(FromStrError::Empty),
+        }
+    }
+}
+
+/// Requires the feature "widestring".
+impl Extend<Utf16Char> for Utf16String {
+    fn extend<I:IntoIterator<Item=Utf16Char>>(&mut self,  iter: I) {
+        self.extend(iter.into_iter().map(Utf16Char::to_char));
+    }
+}
+/// Requires the feature "widestring".
+impl<'a> Extend<&'a Utf16Char> for Utf16String {
+    fn extend<I:IntoIterator<Item=&'a Utf16Char>>(&mut self,  iter: I) {
+        self.extend(iter.into_iter().cloned());
+    }
+}
+/// Requires the feature "widestring".
+impl FromIterator<Utf16Char> for Utf16String {
+    fn from_iter<I:IntoIterator<Item=Utf16Char>>(iter: I) -> Self {
+        let mut s = Utf16String::new();
+        s.extend(iter);
+        s
+    }
+}
+/// Requires the feature "widestring".
+impl<'a> FromIterator<&'a Utf16Char> for Utf16String {
+    fn from_iter<I:IntoIterator<Item=&'a Utf16Char>>(iter: I) -> Self {
+        Self::from_iter(iter.into_iter().cloned())
+    }
+}
+
+/// Requires the feature "widestring".
+impl Extend<Utf16Char> for U16String {
+    fn extend<I:IntoIterator<Item=Utf16Char>>(&mut self,  iter: I) {
+        let iter = iter.into_iter();
+        self.reserve(iter.size_hint().0);
+        for u16c in iter {
+            self.push_slice(u16c);
+        }
+    }
+}
+/// Requires the feature "widestring".
+impl<'a> Extend<&'a Utf16Char> for U16String {
+    fn extend<I:IntoIterator<Item=&'a Utf16Char>>(&mut self,  iter: I) {
+        self.extend(iter.into_iter().cloned());
+    }
+}
+/// Requires the feature "widestring".
+impl FromIterator<Utf16Char> for U16String {
+    fn from_iter<I:IntoIterator<Item=Utf16Char>>(iter: I) -> Self {
+        let mut s = U16String::new();
+        s.extend(iter);
+        s
+    }
+}
+/// Requires the feature "widestring".
+impl<'a> FromIterator<&'a Utf16Char> for U16String {
+    fn from_iter<I:IntoIterator<Item=&'a Utf16Char>>(iter: I) -> Self {
+        Self::from_iter(iter.into_iter().cloned())
+    }
+}
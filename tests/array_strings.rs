@@ -0,0 +1,217 @@
+/* Copyright 2022 Torbjørn Birch Moltu
+ *
+ * Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+ * http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+ * http://opensource.org/licenses/MIT>, at your option. This file may not be
+ * copied, modified, or distributed except according to those terms.
+ */
+
+//! Test the fixed-capacity strings and `TryExtend` impls,
+//! especially what happens when they get full.
+
+#![cfg(feature="std")]
+
+extern crate encode_unicode;
+use encode_unicode::{Utf8Char, Utf16Char, Utf8ArrayString, Utf16ArrayString, TryExtend, StrExt};
+use encode_unicode::error::CapacityError;
+use std::convert::TryFrom;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+/// Codepoints of every length in both encodings, so that the capacity can
+/// run out in the middle of each of them.
+const MIXED_LENGTHS: &str = "a😀€ø\u{10ffff}b";
+
+fn hash<H: Hash + ?Sized>(value: &H) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// The longest prefix of `MIXED_LENGTHS` that fits in `capacity` elements of the given length.
+fn fitting_prefix(capacity: usize,  len: fn(char) -> usize) -> &'static str {
+    let mut used = 0;
+    let end = MIXED_LENGTHS.char_indices()
+        .find(|&(_, c)| { used += len(c);  used > capacity })
+        .map_or(MIXED_LENGTHS.len(), |(end, _)| end );
+    &MIXED_LENGTHS[..end]
+}
+
+#[test]
+fn utf8_fill_up() {
+    fn check<const N: usize>() {
+        let expected = fitting_prefix(N, char::len_utf8);
+        let mut s = Utf8ArrayString::<N>::new();
+        let result = s.try_extend(MIXED_LENGTHS.utf8chars());
+        assert_eq!(result.is_ok(), expected == MIXED_LENGTHS, "{}", N);
+        assert_eq!(s.as_str(), expected, "{}", N);
+        assert_eq!(Utf8ArrayString::<N>::try_from(MIXED_LENGTHS).is_ok(), expected == MIXED_LENGTHS);
+        let from_utf16 = Utf8ArrayString::<N>::try_from_iter(MIXED_LENGTHS.utf16chars());
+        assert_eq!(from_utf16.is_ok(), expected == MIXED_LENGTHS);
+        for c in expected.chars().rev() {
+            assert_eq!(s.pop(), Some(Utf8Char::from(c)));
+            assert_eq!(s.remaining_capacity(), N - s.len());
+        }
+        assert_eq!(s.pop(), None);
+        assert!(s.is_empty());
+    }
+    check::<0>();
+    check::<1>();
+    check::<2>();
+    check::<5>();
+    check::<7>();
+    check::<9>();
+    check::<13>();
+    check::<14>();
+    check::<15>();
+    check::<64>();
+}
+
+#[test]
+fn utf16_fill_up() {
+    fn check<const N: usize>() {
+        let expected = fitting_prefix(N, char::len_utf16);
+        let mut s = Utf16ArrayString::<N>::new();
+        let result = s.try_extend(MIXED_LENGTHS.utf16chars());
+        assert_eq!(result.is_ok(), expected == MIXED_LENGTHS, "{}", N);
+        assert_eq!(s, expected, "{}", N);
+        assert_eq!(s.as_slice(), &expected.encode_utf16().collect::<Vec<u16>>()[..]);
+        let from_utf8 = Utf16ArrayString::<N>::try_from_iter(MIXED_LENGTHS.utf8chars());
+        assert_eq!(from_utf8.is_ok(), expected == MIXED_LENGTHS);
+        for c in expected.chars().rev() {
+            assert_eq!(s.pop(), Some(Utf16Char::from(c)));
+        }
+        assert_eq!(s.pop(), None);
+    }
+    check::<0>();
+    check::<1>();
+    check::<2>();
+    check::<3>();
+    check::<6>();
+    check::<7>();
+    check::<8>();
+    check::<32>();
+}
+
+#[test]
+fn failed_operations_dont_change_anything() {
+    let mut u8s = Utf8ArrayString::<4>::try_from("ab").unwrap();
+    assert_eq!(u8s.try_push('€'), Err(CapacityError));
+    assert_eq!(u8s.try_push_str("cde"), Err(CapacityError));
+    assert_eq!(u8s, "ab");
+    assert_eq!(u8s.try_push_str("cd"), Ok(()));
+    assert_eq!(u8s.try_push('e'), Err(CapacityError));
+    assert_eq!(u8s, "abcd");
+
+    let mut u16s = Utf16ArrayString::<4>::try_from("abc").unwrap();
+    assert_eq!(u16s.try_push('😀'), Err(CapacityError));
+    assert_eq!(u16s.try_push_str("de"), Err(CapacityError));
+    assert_eq!(u16s, "abc");
+    assert_eq!(u16s.try_push('d'), Ok(()));
+    assert_eq!(u16s, "abcd");
+    assert_eq!(Utf16ArrayString::<1>::try_from("😀"), Err(CapacityError));
+}
+
+#[test]
+fn formatting() {
+    use std::fmt::Write;
+    let mut u8s = Utf8ArrayString::<8>::new();
+    assert_eq!(write!(u8s, "{}-{}", 12, 'ß'), Ok(()));
+    assert_eq!(write!(u8s, "{}", 3456), Err(std::fmt::Error));
+    assert_eq!(u8s, "12-ß");
+    assert_eq!(format!("{} {:?}", u8s, u8s), "12-ß \"12-ß\"");
+
+    let mut u16s = Utf16ArrayString::<8>::new();
+    assert_eq!(write!(u16s, "\t{}", '😀'), Ok(()));
+    assert_eq!(format!("{} {:?}", u16s, u16s), format!("{} {:?}", "\t😀", "\t😀"));
+}
+
+#[test]
+fn comparisons_ignore_unused_capacity() {
+    let mut popped = Utf8ArrayString::<8>::try_from("abc").unwrap();
+    popped.pop();
+    let short = Utf8ArrayString::<8>::try_from("ab").unwrap();
+    let longer = Utf8ArrayString::<16>::try_from("ab").unwrap();
+    assert_eq!(popped, short);
+    assert_eq!(short, longer);
+    assert_eq!(hash(&popped), hash(&short));
+    assert_eq!(hash(&short), hash("ab"));
+    assert!(short < Utf8ArrayString::try_from("ab\0").unwrap());
+    assert!(short > Utf8ArrayString::try_from("a").unwrap());
+
+    let mut popped = Utf16ArrayString::<8>::try_from("a😀").unwrap();
+    popped.pop();
+    let short = Utf16ArrayString::<8>::try_from("a").unwrap();
+    assert_eq!(popped, short);
+    assert_eq!(popped, Utf16ArrayString::<2>::try_from("a").unwrap());
+    assert_eq!(hash(&popped), hash(&short));
+    assert_eq!(hash(&short), hash(&[0x61u16][..]));
+
+    // by codepoint, not by unit
+    assert!(Utf8ArrayString::<4>::try_from("\u{fffd}").unwrap() < Utf8ArrayString::try_from("😀").unwrap());
+    assert!(Utf16ArrayString::<2>::try_from("\u{fffd}").unwrap() < Utf16ArrayString::try_from("😀").unwrap());
+    assert!(Utf16ArrayString::<4>::try_from("a\u{ffff}").unwrap() < Utf16ArrayString::try_from("a\u{10000}").unwrap());
+}
+
+#[test]
+fn const_construction() {
+    const EMPTY: Utf8ArrayString<4> = Utf8ArrayString::new();
+    const EMPTY16: Utf16ArrayString<4> = Utf16ArrayString::new();
+    assert_eq!((EMPTY.len(), EMPTY.capacity()), (0, 4));
+    assert_eq!((EMPTY16.len(), EMPTY16.capacity()), (0, 4));
+    assert_eq!(Utf8ArrayString::<3>::default(), "");
+}
+
+#[cfg(feature="heapless")]
+#[test]
+fn heapless() {
+    let mut s = heapless::String::<6>::new();
+    assert_eq!(s.try_extend("æøå".utf8chars()), Ok(()));
+    assert_eq!(s.try_extend("!".utf16chars()), Err(CapacityError));
+    assert_eq!(s.as_str(), "æøå");
+
+    let s = heapless::String::<5>::try_from_iter("a😀b".utf16chars());
+    assert_eq!(s, Err(CapacityError));
+    let s = heapless::String::<6>::try_from_iter("a😀b".utf16chars()).unwrap();
+    assert_eq!(s.as_str(), "a😀b");
+
+    let mut bytes = heapless::Vec::<u8, 4>::new();
+    assert_eq!(bytes.try_extend("a€".utf8chars()), Ok(()));
+    assert_eq!(bytes.try_extend("b".utf8chars()), Err(CapacityError));
+    let mut bytes = heapless::Vec::<u8, 3>::new();
+    assert_eq!(bytes.try_extend("a€".utf8chars()), Err(CapacityError));
+    assert_eq!(&bytes[..], b"a");
+
+    let units = heapless::Vec::<u16, 3>::try_from_iter("😀a".utf16chars()).unwrap();
+    assert_eq!(&units[..], [0xd83d, 0xde00, 0x61]);
+    let mut units = heapless::Vec::<u16, 2>::new();
+    assert_eq!(units.try_extend("a😀".utf16chars()), Err(CapacityError));
+    assert_eq!(&units[..], [0x61]);
+}
+
+#[cfg(feature="arrayvec")]
+#[test]
+fn arrayvec() {
+    let mut s = arrayvec::ArrayString::<6>::new();
+    assert_eq!(s.try_extend("æøå".utf8chars()), Ok(()));
+    assert_eq!(s.try_extend("!".utf16chars()), Err(CapacityError));
+    assert_eq!(s.as_str(), "æøå");
+
+    let s = arrayvec::ArrayString::<5>::try_from_iter("a😀b".utf16chars());
+    assert_eq!(s, Err(CapacityError));
+    let s = arrayvec::ArrayString::<6>::try_from_iter("a😀b".utf16chars()).unwrap();
+    assert_eq!(s.as_str(), "a😀b");
+
+    let mut bytes = arrayvec::ArrayVec::<u8, 4>::new();
+    assert_eq!(bytes.try_extend("a€".utf8chars()), Ok(()));
+    assert_eq!(bytes.try_extend("b".utf8chars()), Err(CapacityError));
+    let mut bytes = arrayvec::ArrayVec::<u8, 3>::new();
+    assert_eq!(bytes.try_extend("a€".utf8chars()), Err(CapacityError));
+    assert_eq!(&bytes[..], b"a");
+
+    let units = arrayvec::ArrayVec::<u16, 3>::try_from_iter("😀a".utf16chars()).unwrap();
+    assert_eq!(&units[..], [0xd83d, 0xde00, 0x61]);
+    let mut units = arrayvec::ArrayVec::<u16, 2>::new();
+    assert_eq!(units.try_extend("a😀".utf16chars()), Err(CapacityError));
+    assert_eq!(&units[..], [0x61]);
+}
@@ -0,0 +1,329 @@
+/* Copyright 2022 Torbjørn Birch Moltu
+ *
+ * Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+ * http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+ * http://opensource.org/licenses/MIT>, at your option. This file may not be
+ * copied, modified, or distributed except according to those terms.
+ */
+
+//! Test the async readers with a source that returns one byte at a time
+//! and is pending between each, and with invalid input.
+
+#![cfg(any(feature="tokio-io", feature="futures"))]
+
+extern crate encode_unicode;
+use encode_unicode::async_io::{ValidatingReader, Utf16ToUtf8Reader, Utf8ToUtf16Reader};
+use encode_unicode::corrupt::{corrupt_utf8, corrupt_utf16, Utf8Corruption, Utf16Corruption};
+use encode_unicode::error::{PositionedError, Utf8Error, Utf8ErrorKind, Utf16PairError};
+use std::error::Error;
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+/// Lines with codepoints of every length, which are all split between reads,
+/// ending with a codepoint that needs a surrogate pair.
+const LINES: &str = "ascii\nøæ\n€\u{ffff}\n😀\u{10ffff}";
+
+fn positioned<E: Error+Copy+'static>(error: &io::Error) -> (usize, E) {
+    assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    let positioned = error.get_ref()
+        .and_then(|e| e.downcast_ref::<PositionedError<E>>() )
+        .expect("wraps a PositionedError");
+    (positioned.offset(), *positioned.error())
+}
+
+fn assert_utf8(chunk: &[u8]) {
+    assert!(std::str::from_utf8(chunk).is_ok(), "{:?}", chunk);
+}
+fn assert_utf16le(chunk: &[u8]) {
+    assert_eq!(chunk.len() % 2, 0);
+    let units = chunk.chunks(2).map(|pair| u16::from_le_bytes([pair[0], pair[1]]) );
+    assert!(char::decode_utf16(units).all(|r| r.is_ok() ), "{:?}", chunk);
+}
+fn assert_utf16be(chunk: &[u8]) {
+    assert_eq!(chunk.len() % 2, 0);
+    let units = chunk.chunks(2).map(|pair| u16::from_be_bytes([pair[0], pair[1]]) );
+    assert!(char::decode_utf16(units).all(|r| r.is_ok() ), "{:?}", chunk);
+}
+
+/// A source that is pending before every byte.
+struct Trickle {
+    bytes: Vec<u8>,
+    read: usize,
+    ready: bool,
+}
+impl Trickle {
+    fn new(bytes: &[u8]) -> Self {
+        Trickle { bytes: bytes.to_vec(),  read: 0,  ready: false }
+    }
+    fn poll_trickle(&mut self,  cx: &mut Context<'_>,  buf: &mut [u8]) -> Poll<usize> {
+        if !self.ready {
+            self.ready = true;
+            cx.waker().wake_by_ref();
+            return Poll::Pending;
+        }
+        self.ready = false;
+        match (self.bytes.get(self.read), buf.first_mut()) {
+            (Some(&b), Some(dst)) => {
+                *dst = b;
+                self.read += 1;
+                Poll::Ready(1)
+            }
+            _ => Poll::Ready(0),
+        }
+    }
+}
+
+#[cfg(feature="tokio-io")]
+mod with_tokio {
+    use super::*;
+    use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncWriteExt, ReadBuf};
+
+    impl AsyncRead for Trickle {
+        fn poll_read(self: Pin<&mut Self>,  cx: &mut Context<'_>,  buf: &mut ReadBuf<'_>)
+        -> Poll<io::Result<()>> {
+            let mut byte = [0];
+            let space = if buf.remaining() == 0 {&mut byte[..0]} else {&mut byte[..]};
+            self.get_mut().poll_trickle(cx, space).map(|read| {
+                buf.put_slice(&byte[..read]);
+                Ok(())
+            })
+        }
+    }
+
+    /// Read everything with `fill_buf()`, check that every chunk is valid,
+    /// and return what was read and the first error.
+    fn read_chunks<R: AsyncBufRead+Unpin>(mut reader: R,  check: fn(&[u8]))
+    -> (Vec<u8>, Option<io::Error>) {
+        futures_executor::block_on(async {
+            let mut read = Vec::new();
+            loop {
+                let len = match reader.fill_buf().await {
+                    Ok([]) => return (read, None),
+                    Ok(chunk) => {
+                        check(chunk);
+                        read.extend_from_slice(chunk);
+                        chunk.len()
+                    }
+                    Err(e) => {
+                        // the error is sticky
+                        assert!(reader.fill_buf().await.is_err());
+                        return (read, Some(e));
+                    }
+                };
+                reader.consume(len);
+            }
+        })
+    }
+
+    #[test]
+    fn valid() {
+        let utf8 = LINES.as_bytes();
+        let le = LINES.encode_utf16().flat_map(u16::to_le_bytes).collect::<Vec<u8>>();
+        let be = LINES.encode_utf16().flat_map(u16::to_be_bytes).collect::<Vec<u8>>();
+
+        let (read, error) = read_chunks(ValidatingReader::new(Trickle::new(utf8)), assert_utf8);
+        assert_eq!((&read[..], error.is_none()), (utf8, true));
+        let (read, error) = read_chunks(Utf16ToUtf8Reader::new_le(Trickle::new(&le)), assert_utf8);
+        assert_eq!((&read[..], error.is_none()), (utf8, true));
+        let (read, error) = read_chunks(Utf16ToUtf8Reader::new_be(Trickle::new(&be)), assert_utf8);
+        assert_eq!((&read[..], error.is_none()), (utf8, true));
+        let (read, error) = read_chunks(Utf8ToUtf16Reader::new_le(Trickle::new(utf8)), assert_utf16le);
+        assert_eq!((read, error.is_none()), (le, true));
+        let (read, error) = read_chunks(Utf8ToUtf16Reader::new_be(Trickle::new(utf8)), assert_utf16be);
+        assert_eq!((read, error.is_none()), (be, true));
+    }
+
+    #[test]
+    fn invalid_utf8() {
+        let corruptions = [
+            [(6, Utf8Corruption::StrayContinuationByte)],
+            [(11, Utf8Corruption::Overlong)],
+            [(18, Utf8Corruption::Split)],
+            [(22, Utf8Corruption::EncodedSurrogate(0xd800))],
+        ];
+        for corruption in &corruptions {
+            let (bytes, errors) = corrupt_utf8(LINES, corruption);
+            let (offset, kind) = errors[0];
+            let valid = std::str::from_utf8(&bytes[..offset]).unwrap();
+
+            let (read, error) = read_chunks(ValidatingReader::new(Trickle::new(&bytes)), assert_utf8);
+            assert_eq!(read, valid.as_bytes());
+            let (error_offset, error) = positioned::<Utf8Error>(&error.unwrap());
+            assert_eq!((error_offset, error.kind()), (offset, kind));
+
+            let (read, error) = read_chunks(Utf8ToUtf16Reader::new_be(Trickle::new(&bytes)), assert_utf16be);
+            assert_eq!(read, valid.encode_utf16().flat_map(u16::to_be_bytes).collect::<Vec<u8>>());
+            let (error_offset, error) = positioned::<Utf8Error>(&error.unwrap());
+            assert_eq!((error_offset, error.kind()), (offset, kind));
+        }
+    }
+
+    #[test]
+    fn invalid_utf16() {
+        let units = LINES.encode_utf16().collect::<Vec<u16>>();
+        let corruptions = [
+            [(0, Utf16Corruption::LoneTrailingSurrogate)],
+            [(9, Utf16Corruption::LoneLeadingSurrogate)],
+            [(12, Utf16Corruption::Split)],
+            [(14, Utf16Corruption::Split)],
+        ];
+        for corruption in &corruptions {
+            let (corrupted, errors) = corrupt_utf16(&units, corruption);
+            let (offset, expected) = errors[0];
+            let valid = String::from_utf16(&corrupted[..offset]).unwrap();
+            for &(to_bytes, new) in &[
+                (u16::to_le_bytes as fn(u16) -> [u8; 2], Utf16ToUtf8Reader::new_le as fn(Trickle) -> _),
+                (u16::to_be_bytes, Utf16ToUtf8Reader::new_be),
+            ] {
+                let bytes = corrupted.iter().flat_map(|&unit| to_bytes(unit) ).collect::<Vec<u8>>();
+                let (read, error) = read_chunks(new(Trickle::new(&bytes)), assert_utf8);
+                assert_eq!(read, valid.as_bytes());
+                assert_eq!(positioned::<Utf16PairError>(&error.unwrap()), (2*offset, expected));
+            }
+        }
+    }
+
+    #[test]
+    fn incomplete_at_end() {
+        let (read, error) = read_chunks(Utf16ToUtf8Reader::new_le(Trickle::new(b"a\0b")), assert_utf8);
+        assert_eq!(read, b"a");
+        assert_eq!(positioned(&error.unwrap()), (2, Utf16PairError::Incomplete));
+
+        let (read, error) = read_chunks(Utf16ToUtf8Reader::new_be(Trickle::new(b"\0a\xd8\x3d")), assert_utf8);
+        assert_eq!(read, b"a");
+        assert_eq!(positioned(&error.unwrap()), (2, Utf16PairError::Incomplete));
+
+        let (read, error) = read_chunks(ValidatingReader::new(Trickle::new(b"ab\xf0\x9f\x98")), assert_utf8);
+        assert_eq!(read, b"ab");
+        let (offset, error) = positioned::<Utf8Error>(&error.unwrap());
+        assert_eq!((offset, error.kind()), (2, Utf8ErrorKind::TooFewBytes));
+    }
+
+    #[test]
+    fn lines() {
+        futures_executor::block_on(async {
+            let le = LINES.encode_utf16().flat_map(u16::to_le_bytes).collect::<Vec<u8>>();
+            let mut lines = Utf16ToUtf8Reader::new_le(Trickle::new(&le)).lines();
+            for expected in LINES.lines() {
+                assert_eq!(lines.next_line().await.unwrap().as_deref(), Some(expected));
+            }
+            assert_eq!(lines.next_line().await.unwrap(), None);
+        });
+    }
+
+    #[test]
+    fn small_reads() {
+        futures_executor::block_on(async {
+            let mut reader = Utf8ToUtf16Reader::new_le(Trickle::new("€😀".as_bytes()));
+            let mut buf = [0; 3];
+            assert_eq!(reader.read(&mut buf).await.unwrap(), 2);
+            assert_eq!(buf[..2], [0xac, 0x20]);
+            assert_eq!(reader.read(&mut buf).await.unwrap(), 3);
+            assert_eq!(reader.read(&mut buf[..1]).await.unwrap(), 1);
+            assert_eq!(reader.read(&mut buf).await.unwrap(), 0);
+        });
+    }
+
+    #[test]
+    fn duplex() {
+        let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+        runtime.block_on(async {
+            let (mut writer, reader) = tokio::io::duplex(3);
+            let write = async move {
+                for c in LINES.chars() {
+                    let mut units = [0; 2];
+                    for &unit in c.encode_utf16(&mut units).iter() {
+                        writer.write_all(&unit.to_be_bytes()).await.unwrap();
+                    }
+                }
+            };
+            let mut reader = Utf16ToUtf8Reader::new_be(reader);
+            let mut text = String::new();
+            let read = reader.read_to_string(&mut text);
+            let ((), read) = futures_util::future::join(write, read).await;
+            assert_eq!(read.unwrap(), LINES.len());
+            assert_eq!(text, LINES);
+        });
+    }
+}
+
+#[cfg(feature="futures")]
+mod with_futures {
+    use super::*;
+    use futures_util::io::{AsyncBufRead, AsyncBufReadExt, AsyncRead, AsyncReadExt};
+
+    impl AsyncRead for Trickle {
+        fn poll_read(self: Pin<&mut Self>,  cx: &mut Context<'_>,  buf: &mut [u8])
+        -> Poll<io::Result<usize>> {
+            self.get_mut().poll_trickle(cx, buf).map(Ok)
+        }
+    }
+
+    /// Read everything with `fill_buf()`, check that every chunk is valid,
+    /// and return what was read and the first error.
+    fn read_chunks<R: AsyncBufRead+Unpin>(mut reader: R,  check: fn(&[u8]))
+    -> (Vec<u8>, Option<io::Error>) {
+        futures_executor::block_on(async {
+            let mut read = Vec::new();
+            loop {
+                let len = match reader.fill_buf().await {
+                    Ok([]) => return (read, None),
+                    Ok(chunk) => {
+                        check(chunk);
+                        read.extend_from_slice(chunk);
+                        chunk.len()
+                    }
+                    Err(e) => {
+                        // the error is sticky
+                        assert!(reader.fill_buf().await.is_err());
+                        return (read, Some(e));
+                    }
+                };
+                reader.consume_unpin(len);
+            }
+        })
+    }
+
+    #[test]
+    fn valid() {
+        let utf8 = LINES.as_bytes();
+        let le = LINES.encode_utf16().flat_map(u16::to_le_bytes).collect::<Vec<u8>>();
+        let be = LINES.encode_utf16().flat_map(u16::to_be_bytes).collect::<Vec<u8>>();
+
+        let (read, error) = read_chunks(ValidatingReader::new(Trickle::new(utf8)), assert_utf8);
+        assert_eq!((&read[..], error.is_none()), (utf8, true));
+        let (read, error) = read_chunks(Utf16ToUtf8Reader::new_le(Trickle::new(&le)), assert_utf8);
+        assert_eq!((&read[..], error.is_none()), (utf8, true));
+        let (read, error) = read_chunks(Utf8ToUtf16Reader::new_be(Trickle::new(utf8)), assert_utf16be);
+        assert_eq!((read, error.is_none()), (be, true));
+    }
+
+    #[test]
+    fn invalid() {
+        let (bytes, errors) = corrupt_utf8(LINES, &[(11, Utf8Corruption::Overlong)]);
+        let (read, error) = read_chunks(Utf8ToUtf16Reader::new_le(Trickle::new(&bytes)), assert_utf16le);
+        assert_eq!(read, LINES[..11].encode_utf16().flat_map(u16::to_le_bytes).collect::<Vec<u8>>());
+        let (offset, error) = positioned::<Utf8Error>(&error.unwrap());
+        assert_eq!((offset, error.kind()), errors[0]);
+
+        let units = LINES.encode_utf16().collect::<Vec<u16>>();
+        let (units, errors) = corrupt_utf16(&units, &[(6, Utf16Corruption::LoneTrailingSurrogate)]);
+        let bytes = units.iter().flat_map(|&unit| unit.to_be_bytes() ).collect::<Vec<u8>>();
+        let (read, error) = read_chunks(Utf16ToUtf8Reader::new_be(Trickle::new(&bytes)), assert_utf8);
+        assert_eq!(read, &LINES.as_bytes()[..8]);
+        assert_eq!(positioned::<Utf16PairError>(&error.unwrap()), (2*errors[0].0, errors[0].1));
+    }
+
+    #[test]
+    fn read_to_end() {
+        futures_executor::block_on(async {
+            let mut reader = ValidatingReader::new(Trickle::new(b"ok\xffnot ok"));
+            let mut read = Vec::new();
+            let error = reader.read_to_end(&mut read).await.unwrap_err();
+            assert_eq!(read, b"ok");
+            let (offset, error) = positioned::<Utf8Error>(&error);
+            assert_eq!((offset, error.kind()), (2, Utf8ErrorKind::NonUtf8Byte));
+        });
+    }
+}
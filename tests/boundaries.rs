@@ -0,0 +1,138 @@
+/* Copyright 2022 Torbjørn Birch Moltu
+ *
+ * Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+ * http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+ * http://opensource.org/licenses/MIT>, at your option. This file may not be
+ * copied, modified, or distributed except according to those terms.
+ */
+
+//! Test `CharBoundaryExt` and truncation against `str` and the decoding iterators.
+
+#![cfg(feature="std")]
+
+extern crate encode_unicode;
+use encode_unicode::{CharBoundaryExt, SliceExt};
+
+const MIXED: &str = "a\u{e6}\u{20ac}\u{1f600}\u{7ff}\u{800}\u{ffff}\u{10000}\u{10ffff}z";
+
+#[test] fn same_as_str_for_valid_utf8() {
+    let bytes = MIXED.as_bytes();
+    for i in 0..bytes.len()+3 {
+        assert_eq!(bytes.is_char_boundary(i), MIXED.is_char_boundary(i), "index {}", i);
+        let floor = (0..=i.min(bytes.len())).rev().find(|&f| MIXED.is_char_boundary(f) );
+        assert_eq!(bytes.floor_char_boundary(i), floor.unwrap(), "index {}", i);
+        let ceil = (i.min(bytes.len())..=bytes.len()).find(|&c| MIXED.is_char_boundary(c) );
+        assert_eq!(bytes.ceil_char_boundary(i), ceil.unwrap(), "index {}", i);
+    }
+}
+
+#[test] fn same_as_encode_utf16_for_valid_utf16() {
+    let units = MIXED.encode_utf16().collect::<Vec<u16>>();
+    let mut boundaries = MIXED.chars()
+        .scan(0, |offset, c| {*offset += c.len_utf16(); Some(*offset)} )
+        .collect::<Vec<usize>>();
+    boundaries.insert(0, 0);
+    for i in 0..units.len()+2 {
+        assert_eq!(units.is_char_boundary(i), boundaries.contains(&i), "index {}", i);
+        let next = boundaries.iter().cloned().find(|&b| b > i ).unwrap_or(units.len());
+        assert_eq!(units.next_char_start_after(i), next, "index {}", i);
+    }
+}
+
+#[test] fn invalid_utf8() {
+    let bytes = b"\x80\xc0\x80\x80\xf4\x90\x80\x80\x80\xe0\x80a\xff\x80\xe1\x80";
+    let starts = [0, 1, 3, 4, 8, 9, 11, 12, 13, 14, 16];
+    for i in 0..bytes.len()+1 {
+        assert_eq!(bytes.is_char_boundary(i), starts.contains(&i), "index {}", i);
+        let floor = starts.iter().cloned().filter(|&start| start <= i ).max();
+        assert_eq!(bytes.floor_char_boundary(i), floor.unwrap(), "index {}", i);
+        let ceil = starts.iter().cloned().filter(|&start| start >= i ).min();
+        assert_eq!(bytes.ceil_char_boundary(i), ceil.unwrap(), "index {}", i);
+    }
+    // every sequence the decoder accepts is kept together
+    for (offset, result, len) in bytes.utf8char_indices() {
+        if result.is_ok() {
+            assert!(bytes[offset+1..offset+len].iter().all(|&b| b & 0xc0 == 0x80 ));
+            assert_eq!(bytes.next_char_start_after(offset), offset+len);
+        }
+    }
+}
+
+#[test] fn invalid_utf16() {
+    let units: &[u16] = &[0xdc00, 0xd800, 0xd800, 0xdfff, 0xdfff, 0xd800];
+    let starts = [0, 1, 2, 4, 5, 6];
+    for i in 0..units.len()+1 {
+        assert_eq!(units.is_char_boundary(i), starts.contains(&i), "index {}", i);
+    }
+    assert_eq!(units.floor_char_boundary(3), 2);
+    assert_eq!(units.ceil_char_boundary(3), 4);
+}
+
+#[test] fn chunks() {
+    for &max_len in &[1, 2, 3, 4, 5, 7, 100] {
+        let bytes = MIXED.as_bytes();
+        let chunks = bytes.char_boundary_chunks(max_len).collect::<Vec<&[u8]>>();
+        assert_eq!(chunks.concat(), bytes);
+        for chunk in &chunks {
+            let s = std::str::from_utf8(chunk).unwrap();
+            assert!(s.len() <= max_len  ||  s.chars().count() == 1);
+        }
+        let (min, max) = bytes.char_boundary_chunks(max_len).size_hint();
+        assert!(min <= chunks.len()  &&  max.unwrap() >= chunks.len());
+
+        let units = MIXED.encode_utf16().collect::<Vec<u16>>();
+        let chunks = units.char_boundary_chunks(max_len).collect::<Vec<&[u16]>>();
+        assert_eq!(chunks.concat(), units);
+        for chunk in &chunks {
+            let s = String::from_utf16(chunk).unwrap();
+            assert!(chunk.len() <= max_len  ||  s.chars().count() == 1);
+        }
+    }
+}
+
+#[test] fn truncate_across_encodings() {
+    use encode_unicode::{CharExt, Utf8Char};
+    use encode_unicode::slice::{truncate_str_to_utf16_len, truncate_utf8_to_utf16_len};
+    use encode_unicode::slice::truncate_utf16_to_utf8_len;
+    let units = MIXED.encode_utf16().collect::<Vec<u16>>();
+    for &ellipsis in &[None, Some('.'), Some('\u{2026}'), Some('\u{1f4a5}')] {
+        for max in 0..MIXED.len()+2 {
+            let expected_utf16 = MIXED.char_indices().map(|(i, _)| &MIXED[..i] ).chain(Some(MIXED))
+                .rev().find(|prefix| {
+                    let len = prefix.encode_utf16().count();
+                    if prefix.len() == MIXED.len() {
+                        len <= max
+                    } else {
+                        len + ellipsis.map_or(0, char::len_utf16) <= max
+                    }
+                }).unwrap_or("");
+            let ellipsis8 = ellipsis.map(Utf8Char::from);
+            assert_eq!(truncate_str_to_utf16_len(MIXED, max, ellipsis8), expected_utf16);
+            let truncated = truncate_utf8_to_utf16_len(MIXED.as_bytes(), max, ellipsis8);
+            assert_eq!(truncated, expected_utf16.as_bytes());
+
+            let expected_utf8 = MIXED.char_indices().map(|(i, _)| &MIXED[..i] ).chain(Some(MIXED))
+                .rev().find(|prefix| {
+                    if prefix.len() == MIXED.len() {
+                        prefix.len() <= max
+                    } else {
+                        prefix.len() + ellipsis.map_or(0, char::len_utf8) <= max
+                    }
+                }).unwrap_or("");
+            let truncated = truncate_utf16_to_utf8_len(&units, max, ellipsis.map(|c| c.to_utf16() ));
+            assert_eq!(String::from_utf16(truncated).unwrap(), expected_utf8, "max {}", max);
+        }
+    }
+}
+
+#[test] fn truncate_invalid_utf8_to_utf16_len() {
+    use encode_unicode::slice::truncate_utf8_to_utf16_len;
+    // sequences: \x80 | \xc0\x80 | \xf4\x90\x80\x80 | \xf0\x9f\x98 | a | \xff
+    let bytes = b"\x80\xc0\x80\xf4\x90\x80\x80\xf0\x9f\x98a\xff";
+    let ends_by_units = [0, 1, 3, 3, 7, 10, 11, 12];
+    for (max_units, &end) in ends_by_units.iter().enumerate() {
+        assert_eq!(truncate_utf8_to_utf16_len(bytes, max_units, None), &bytes[..end],
+                   "max {} units", max_units);
+    }
+    assert_eq!(truncate_utf8_to_utf16_len(bytes, 100, None), &bytes[..]);
+}
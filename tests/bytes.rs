@@ -0,0 +1,183 @@
+/* Copyright 2022 Torbjørn Birch Moltu
+ *
+ * Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+ * http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+ * http://opensource.org/licenses/MIT>, at your option. This file may not be
+ * copied, modified, or distributed except according to those terms.
+ */
+
+//! Test the `Buf` and `BufMut` extension traits with codepoints split between chunks.
+
+#![cfg(all(feature="bytes", feature="std"))]
+
+extern crate encode_unicode;
+use encode_unicode::{BufExt, BufMutExt, Utf8Char, Utf16Char};
+use encode_unicode::corrupt::{corrupt_utf8, Utf8Corruption};
+use encode_unicode::error::{Utf8ErrorKind, Utf16PairError};
+use bytes::{Buf, BytesMut};
+
+/// Codepoints of every length in both encodings, ordered so that they start
+/// at different offsets within the chunks for every chunk size.
+const MIXED_LENGTHS: &str = "😀a€ø\u{10ffff}\u{ffff}\u{7ff}\0";
+const MIXED_LENGTHS_UTF16BE: &[u8] = b"\xd8\x3d\xde\x00\x00\x61\x20\xac\x00\xf8\xdb\xff\xdf\xff\xff\xff\x07\xff\x00\x00";
+const MIXED_LENGTHS_UTF16LE: &[u8] = b"\x3d\xd8\x00\xde\x61\x00\xac\x20\xf8\x00\xff\xdb\xff\xdf\xff\xff\xff\x07\x00\x00";
+
+/// A `Buf` whose chunks are at most `step` bytes long.
+struct Pieces<'a> {
+    bytes: &'a [u8],
+    read: usize,
+    step: usize,
+}
+impl<'a> Pieces<'a> {
+    fn new(bytes: &'a [u8],  step: usize) -> Self {
+        Pieces { bytes,  read: 0,  step }
+    }
+}
+impl<'a> Buf for Pieces<'a> {
+    fn remaining(&self) -> usize {
+        self.bytes.len() - self.read
+    }
+    fn chunk(&self) -> &[u8] {
+        let end = usize::min((self.read/self.step + 1) * self.step, self.bytes.len());
+        &self.bytes[self.read..end]
+    }
+    fn advance(&mut self,  cnt: usize) {
+        assert!(cnt <= self.remaining());
+        self.read += cnt;
+    }
+}
+
+fn decode_utf8(bytes: &[u8],  step: usize) -> Vec<Result<Utf8Char, Utf8ErrorKind>> {
+    let mut buf = Pieces::new(bytes, step);
+    let mut decoded = Vec::new();
+    while buf.has_remaining() {
+        decoded.push(buf.get_utf8_char().map_err(|e| e.kind() ));
+    }
+    decoded
+}
+
+fn decode_utf16(bytes: &[u8],  step: usize,  little_endian: bool) -> Vec<Result<Utf16Char, Utf16PairError>> {
+    let mut buf = Pieces::new(bytes, step);
+    let mut decoded = Vec::new();
+    while buf.has_remaining() {
+        decoded.push(match little_endian {
+            true => buf.get_utf16le_char(),
+            false => buf.get_utf16_char(),
+        });
+    }
+    decoded
+}
+
+#[test]
+fn valid_in_pieces() {
+    for step in 1..=5 {
+        let decoded = decode_utf8(MIXED_LENGTHS.as_bytes(), step);
+        assert!(decoded.into_iter().eq(MIXED_LENGTHS.chars().map(|c| Ok(Utf8Char::from(c)) )), "{}", step);
+        for &(bytes, little_endian) in &[(MIXED_LENGTHS_UTF16BE, false), (MIXED_LENGTHS_UTF16LE, true)] {
+            let decoded = decode_utf16(bytes, step, little_endian);
+            assert!(decoded.into_iter().eq(MIXED_LENGTHS.chars().map(|c| Ok(Utf16Char::from(c)) )), "{}", step);
+        }
+    }
+}
+
+#[test]
+fn utf8_errors_dont_depend_on_chunks() {
+    let inserted = [
+        (0, Utf8Corruption::StrayContinuationByte),
+        (5, Utf8Corruption::EncodedSurrogate(0xdfff)),
+        (10, Utf8Corruption::StrayContinuationByte),
+        (17, Utf8Corruption::EncodedSurrogate(0xd800)),
+    ];
+    let replaced = [
+        (5, Utf8Corruption::Overlong),
+        (10, Utf8Corruption::Split),
+        (14, Utf8Corruption::Split),
+    ];
+    // the overlong and split codepoints are lost
+    for &(corruptions, lost) in &[(&inserted[..], 0), (&replaced[..], 3)] {
+        let (bytes, errors) = corrupt_utf8(MIXED_LENGTHS, corruptions);
+        let whole = decode_utf8(&bytes, bytes.len());
+        let first_error = whole.iter().find_map(|result| result.err() );
+        assert_eq!(first_error, Some(errors[0].1));
+        for step in 1..=4 {
+            assert_eq!(decode_utf8(&bytes, step), whole, "{}", step);
+        }
+        let decoded = whole.iter().filter_map(|result| result.ok() ).count();
+        assert_eq!(decoded, MIXED_LENGTHS.chars().count() - lost);
+    }
+}
+
+#[test]
+fn utf8_error_consumption() {
+    let decoded = decode_utf8(b"\xe0\x80\x80a\xe2\x82b\xc0\x80\xf0\x9f\x98", 1);
+    assert_eq!(decoded, [
+        Err(Utf8ErrorKind::OverlongEncoding),
+        Ok(Utf8Char::from('a')),
+        Err(Utf8ErrorKind::InterruptedSequence),
+        Ok(Utf8Char::from('b')),
+        Err(Utf8ErrorKind::NonUtf8Byte),
+        Err(Utf8ErrorKind::UnexpectedContinuationByte),
+        Err(Utf8ErrorKind::TooFewBytes),
+    ]);
+}
+
+#[test]
+fn utf16_errors() {
+    // "a😀b" with a trailing surrogate after 'a', a leading surrogate after '😀'
+    // and half a leading surrogate at the end
+    let be = b"\x00\x61\xdc\x00\xd8\x3d\xde\x00\xd8\x00\x00\x62\xd8";
+    let le = b"\x61\x00\x00\xdc\x3d\xd8\x00\xde\x00\xd8\x62\x00\xd8";
+    for &(bytes, little_endian) in &[(be, false), (le, true)] {
+        for step in 1..=5 {
+            let mut expected = vec![
+                Ok(Utf16Char::from('a')),
+                Err(Utf16PairError::UnexpectedTrailingSurrogate),
+                Ok(Utf16Char::from('😀')),
+                Err(Utf16PairError::UnmatchedLeadingSurrogate),
+                Ok(Utf16Char::from('b')),
+                Err(Utf16PairError::Incomplete),
+            ];
+            if little_endian && step == 1 {
+                // the low byte of 'b' has to be consumed to see that it's not a trailing surrogate
+                let _ = expected.remove(4);
+            }
+            assert_eq!(decode_utf16(bytes, step, little_endian), expected, "{} {}", step, little_endian);
+        }
+    }
+
+    let mut buf = Pieces::new(b"\xd8\x3d\xde", 1);
+    assert_eq!(buf.get_utf16_char(), Err(Utf16PairError::Incomplete));
+    assert!(!buf.has_remaining());
+}
+
+#[test]
+fn put() {
+    let mut buf = BytesMut::new();
+    for c in MIXED_LENGTHS.chars() {
+        buf.put_utf8_char(c);
+    }
+    assert_eq!(&buf[..], MIXED_LENGTHS.as_bytes());
+
+    for &(bytes, little_endian) in &[(MIXED_LENGTHS_UTF16BE, false), (MIXED_LENGTHS_UTF16LE, true)] {
+        let mut buf = BytesMut::new();
+        for c in MIXED_LENGTHS.chars() {
+            match little_endian {
+                true => buf.put_utf16le_char(Utf16Char::from(c)),
+                false => buf.put_utf16_char(c),
+            }
+        }
+        assert_eq!(&buf[..], bytes);
+    }
+}
+
+#[test]
+fn put_doesnt_write_half_a_codepoint() {
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+    let mut storage = [0u8; 3];
+    let result = catch_unwind(AssertUnwindSafe(|| {
+        let mut buf = &mut storage[..];
+        buf.put_utf16le_char('😀');
+    }));
+    assert!(result.is_err());
+    assert_eq!(storage, [0; 3]);
+}
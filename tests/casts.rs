@@ -0,0 +1,59 @@
+/* Copyright 2022 Torbjørn Birch Moltu
+ *
+ * Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+ * http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+ * http://opensource.org/licenses/MIT>, at your option. This file may not be
+ * copied, modified, or distributed except according to those terms.
+ */
+
+//! Test casting slices of `Utf8Char` and `Utf16Char` with `bytemuck` and `zerocopy`.
+
+#![cfg(all(feature="std", any(feature="bytemuck", feature="zerocopy")))]
+
+use encode_unicode::{Utf8Char, Utf16Char};
+
+const STR: &str = "a\0ñ\u{10ffff}";
+const UTF8: &[u8] = b"a\0\0\0\0\0\0\x01\xc3\xb1\0\0\xf4\x8f\xbf\xbf";
+const UTF16: &[u16] = &[0x61, 0, 0, 1, 0xf1, 0, 0xdbff, 0xdfff];
+
+#[cfg(feature="bytemuck")]
+#[test]
+fn bytemuck() {
+    use bytemuck::checked::{try_cast_slice, try_pod_read_unaligned, CheckedCastError};
+    let utf8 = STR.chars().map(Utf8Char::from).collect::<Vec<_>>();
+    let bytes: &[u8] = bytemuck::cast_slice(&utf8);
+    assert_eq!(bytes, UTF8);
+    assert_eq!(try_cast_slice::<u8, Utf8Char>(bytes), Ok(&utf8[..]));
+    let mut packed = utf8.iter().map(|u8c| u8c.to_packed_u32().to_le() ).collect::<Vec<u32>>();
+    assert_eq!(try_cast_slice::<u32, Utf8Char>(&packed), Ok(&utf8[..]));
+    packed[1] = 0;
+    assert_eq!(try_cast_slice::<u32, Utf8Char>(&packed), Err(CheckedCastError::InvalidBitPattern));
+    assert_eq!(<Option<Utf8Char> as bytemuck::Zeroable>::zeroed(), None);
+    assert_eq!(try_pod_read_unaligned::<Utf8Char>(&UTF8[8..12]), Ok(utf8[2]));
+    for invalid in [b"\xc3\xb1\0\x01", b"\xed\xa0\x80\0", b"\xf4\x90\x80\x80", b"\0\0\0\0"] {
+        assert_eq!(try_pod_read_unaligned::<Utf8Char>(invalid), Err(CheckedCastError::InvalidBitPattern));
+    }
+
+    let utf16 = STR.chars().map(Utf16Char::from).collect::<Vec<_>>();
+    let units: &[u16] = bytemuck::cast_slice(&utf16);
+    assert_eq!(units, UTF16);
+    assert_eq!(try_cast_slice::<u16, Utf16Char>(units), Ok(&utf16[..]));
+    let mut packed = utf16.iter().map(|u16c| u16c.to_packed_u32() ).collect::<Vec<u32>>();
+    if cfg!(target_endian="little") {
+        assert_eq!(try_cast_slice::<u32, Utf16Char>(&packed), Ok(&utf16[..]));
+    }
+    packed[3] = 0xdfff_dfff;
+    assert_eq!(try_cast_slice::<u32, Utf16Char>(&packed), Err(CheckedCastError::InvalidBitPattern));
+    assert_eq!(<Option<Utf16Char> as bytemuck::Zeroable>::zeroed(), None);
+}
+
+#[cfg(feature="zerocopy")]
+#[test]
+fn zerocopy() {
+    use zerocopy::IntoBytes;
+    let utf8 = STR.chars().map(Utf8Char::from).collect::<Vec<Utf8Char>>();
+    assert_eq!(IntoBytes::as_bytes(&utf8[..]), UTF8);
+    let utf16 = STR.chars().map(Utf16Char::from).collect::<Vec<Utf16Char>>();
+    let units = UTF16.iter().flat_map(|unit| unit.to_ne_bytes() ).collect::<Vec<u8>>();
+    assert_eq!(IntoBytes::as_bytes(&utf16[..]), &units[..]);
+}
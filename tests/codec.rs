@@ -0,0 +1,151 @@
+/* Copyright 2022 Torbjørn Birch Moltu
+ *
+ * Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+ * http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+ * http://opensource.org/licenses/MIT>, at your option. This file may not be
+ * copied, modified, or distributed except according to those terms.
+ */
+
+//! Test the tokio-util codecs with input arriving in pieces, and with invalid input.
+
+#![cfg(feature="tokio-codec")]
+
+extern crate encode_unicode;
+use encode_unicode::Utf8Char;
+use encode_unicode::codec::{Utf8CharCodec, Utf8ChunkCodec};
+use encode_unicode::corrupt::{corrupt_utf8, Utf8Corruption};
+use encode_unicode::error::{PositionedError, Utf8Error, Utf8ErrorKind};
+use bytes::BytesMut;
+use tokio_util::codec::{Decoder, Encoder};
+use std::io;
+
+/// Starts and ends with multi-byte codepoints, and has short runs of ASCII between them,
+/// so that the chunk codec has both complete and incomplete codepoints at the edges of a piece.
+const PIECES: &str = "€ab\u{7ff}c\u{10ffff}\u{80}😀";
+
+fn positioned(error: &io::Error) -> (usize, Utf8ErrorKind) {
+    assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    let positioned = error.get_ref()
+        .and_then(|e| e.downcast_ref::<PositionedError<Utf8Error>>() )
+        .expect("wraps a PositionedError<Utf8Error>");
+    (positioned.offset(), positioned.error().kind())
+}
+
+/// Feed `bytes` to the decoder `step` bytes at a time,
+/// and return the items and the errors.
+fn decode_in_pieces<D: Decoder<Error=io::Error>>(mut decoder: D,  bytes: &[u8],  step: usize)
+-> (Vec<D::Item>, Vec<(usize, Utf8ErrorKind)>) {
+    let (mut items, mut errors) = (Vec::new(), Vec::new());
+    let mut buf = BytesMut::new();
+    let mut pieces = bytes.chunks(step);
+    loop {
+        let piece = pieces.next();
+        buf.extend_from_slice(piece.unwrap_or_default());
+        loop {
+            let decoded = match piece {
+                Some(_) => decoder.decode(&mut buf),
+                None => decoder.decode_eof(&mut buf),
+            };
+            match decoded {
+                Ok(Some(item)) => items.push(item),
+                Ok(None) => break,
+                Err(e) => errors.push(positioned(&e)),
+            }
+        }
+        if piece.is_none() {
+            assert!(buf.is_empty());
+            return (items, errors);
+        }
+    }
+}
+
+#[test]
+fn valid_in_pieces() {
+    for step in 1..=5 {
+        let (chars, errors) = decode_in_pieces(Utf8CharCodec::new(), PIECES.as_bytes(), step);
+        assert_eq!(errors, []);
+        assert!(chars.iter().cloned().eq(PIECES.chars().map(Utf8Char::from)), "{}", step);
+
+        let (chunks, errors) = decode_in_pieces(Utf8ChunkCodec::new(), PIECES.as_bytes(), step);
+        assert_eq!(errors, []);
+        assert_eq!(chunks.concat(), PIECES);
+        assert!(chunks.iter().all(|chunk| !chunk.is_empty() ), "{}: {:?}", step, chunks);
+        if step == 1 {
+            // every codepoint is returned as soon as it is complete
+            assert_eq!(chunks.len(), PIECES.chars().count());
+        }
+    }
+}
+
+#[test]
+fn errors_in_pieces() {
+    let corruptions = [
+        (0, Utf8Corruption::Overlong),
+        (3, Utf8Corruption::EncodedSurrogate(0xdc00)),
+        (5, Utf8Corruption::StrayContinuationByte),
+        (8, Utf8Corruption::Split),
+        (12, Utf8Corruption::Split),
+    ];
+    let (bytes, expected) = corrupt_utf8(PIECES, &corruptions);
+    for step in 1..=5 {
+        let (chars, errors) = decode_in_pieces(Utf8CharCodec::new(), &bytes, step);
+        assert_eq!(errors, expected, "{}", step);
+        // the overlong and split codepoints are lost
+        assert_eq!(chars.len(), PIECES.chars().count() - 3);
+
+        let (chunks, errors) = decode_in_pieces(Utf8ChunkCodec::new(), &bytes, step);
+        assert_eq!(errors, expected, "{}", step);
+        assert_eq!(chunks.concat().chars().count(), chars.len());
+    }
+}
+
+#[test]
+fn incomplete_at_eof() {
+    let mut codec = Utf8ChunkCodec::new();
+    let mut buf = BytesMut::from(&b"ab\xf4\x8f\xbf"[..]);
+    assert_eq!(codec.decode(&mut buf).unwrap(), Some("ab".to_string()));
+    assert_eq!(codec.decode(&mut buf).unwrap(), None);
+    let error = codec.decode_eof(&mut buf).unwrap_err();
+    assert_eq!(positioned(&error), (2, Utf8ErrorKind::TooFewBytes));
+    assert_eq!(codec.offset(), 5);
+    assert_eq!(codec.decode_eof(&mut buf).unwrap(), None);
+}
+
+#[test]
+fn encode() {
+    let mut buf = BytesMut::new();
+    Utf8CharCodec::new().encode('æ', &mut buf).unwrap();
+    Utf8CharCodec::new().encode(Utf8Char::from('€'), &mut buf).unwrap();
+    Utf8ChunkCodec::new().encode(" og ", &mut buf).unwrap();
+    Utf8ChunkCodec::new().encode(String::from("ø"), &mut buf).unwrap();
+    assert_eq!(&buf[..], "æ€ og ø".as_bytes());
+}
+
+#[test]
+fn framed_read() {
+    use futures_util::StreamExt;
+    use tokio::io::AsyncWriteExt;
+    use tokio_util::codec::FramedRead;
+
+    let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+    runtime.block_on(async {
+        let (mut writer, reader) = tokio::io::duplex(4);
+        let write = async move {
+            for piece in PIECES.as_bytes().chunks(3) {
+                writer.write_all(piece).await.unwrap();
+            }
+            writer.write_all(b"\xff").await.unwrap();
+        };
+        let read = FramedRead::new(reader, Utf8ChunkCodec::new()).collect::<Vec<_>>();
+        let ((), results) = futures_util::future::join(write, read).await;
+        let (text, errors) = results.into_iter().fold((String::new(), Vec::new()), |(mut text, mut errors), result| {
+            match result {
+                Ok(chunk) => text.push_str(&chunk),
+                Err(e) => errors.push(positioned(&e)),
+            }
+            (text, errors)
+        });
+        assert_eq!(text, PIECES);
+        assert_eq!(errors, [(PIECES.len(), Utf8ErrorKind::NonUtf8Byte)]);
+    });
+}
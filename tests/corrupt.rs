@@ -0,0 +1,115 @@
+/* Copyright 2022 Torbjørn Birch Moltu
+ *
+ * Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+ * http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+ * http://opensource.org/licenses/MIT>, at your option. This file may not be
+ * copied, modified, or distributed except according to those terms.
+ */
+
+//! Test that the decoders report exactly the errors the corruptors say they will.
+
+#![cfg(feature="std")]
+
+extern crate encode_unicode;
+use encode_unicode::SliceExt;
+use encode_unicode::corrupt::*;
+
+/// The lowest and highest codepoints of every UTF-8 length and on each side
+/// of the surrogate range, as those are the ones that overlong encodings,
+/// split sequences and inserted surrogates are most likely to be confused with.
+/// The `z` is removed to test corrupting the last codepoint.
+const BOUNDARIES: &str = "\0\u{7f}\u{80}\u{7ff}\u{800}\u{d7ff}\u{e000}\u{ffff}\u{10000}\u{10ffff}z";
+
+const UTF8_CORRUPTIONS: [Utf8Corruption; 6] = [
+    Utf8Corruption::Overlong,
+    Utf8Corruption::Split,
+    Utf8Corruption::StrayContinuationByte,
+    Utf8Corruption::EncodedSurrogate(0xd800),
+    Utf8Corruption::EncodedSurrogate(0xdbff),
+    Utf8Corruption::EncodedSurrogate(0xdfff),
+];
+const UTF16_CORRUPTIONS: [Utf16Corruption; 3] = [
+    Utf16Corruption::LoneLeadingSurrogate,
+    Utf16Corruption::LoneTrailingSurrogate,
+    Utf16Corruption::Split,
+];
+
+fn applicable_utf8(c: char,  corruption: Utf8Corruption) -> bool {
+    match corruption {
+        Utf8Corruption::Overlong => c.len_utf8() < 4,
+        Utf8Corruption::Split => c.len_utf8() > 1,
+        _ => true,
+    }
+}
+
+fn check_utf8(text: &str,  corruptions: &[(usize, Utf8Corruption)]) {
+    let (bytes, expected) = corrupt_utf8(text, corruptions);
+    let decoded = bytes.utf8char_indices()
+        .filter_map(|(offset, result, _)| Some((offset, result.err()?.kind())) )
+        .collect::<Vec<_>>();
+    assert_eq!(decoded, expected, "{:?} {:x?}", corruptions, bytes);
+}
+
+#[test]
+fn utf8_pairs() {
+    for &text in &[BOUNDARIES, BOUNDARIES.trim_end_matches('z')] {
+        let chars = text.char_indices().collect::<Vec<_>>();
+        for (i, &(a_offset, a)) in chars.iter().enumerate() {
+            for &a_corruption in UTF8_CORRUPTIONS.iter().filter(|&&co| applicable_utf8(a, co) ) {
+                check_utf8(text, &[(a_offset, a_corruption)]);
+                for &(b_offset, b) in &chars[i+1..] {
+                    for &b_corruption in UTF8_CORRUPTIONS.iter().filter(|&&co| applicable_utf8(b, co) ) {
+                        check_utf8(text, &[(a_offset, a_corruption), (b_offset, b_corruption)]);
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn check_utf16(units: &[u16],  corruptions: &[(usize, Utf16Corruption)]) {
+    let (corrupted, expected) = corrupt_utf16(units, corruptions);
+    let decoded = corrupted.utf16char_indices()
+        .filter_map(|(offset, result, _)| Some((offset, result.err()?)) )
+        .collect::<Vec<_>>();
+    assert_eq!(decoded, expected, "{:?} {:x?}", corruptions, corrupted);
+}
+
+#[test]
+fn utf16_pairs() {
+    for &text in &[BOUNDARIES, BOUNDARIES.trim_end_matches('z')] {
+        let units = text.encode_utf16().collect::<Vec<u16>>();
+        let starts = text.chars()
+            .scan(0, |offset, c| { *offset += c.len_utf16();  Some((*offset - c.len_utf16(), c)) })
+            .collect::<Vec<_>>();
+        let applicable = |c: char,  corruption| corruption != Utf16Corruption::Split || c.len_utf16() == 2;
+        for (i, &(a_offset, a)) in starts.iter().enumerate() {
+            for &a_corruption in UTF16_CORRUPTIONS.iter().filter(|&&co| applicable(a, co) ) {
+                check_utf16(&units, &[(a_offset, a_corruption)]);
+                for &(b_offset, b) in &starts[i+1..] {
+                    for &b_corruption in UTF16_CORRUPTIONS.iter().filter(|&&co| applicable(b, co) ) {
+                        check_utf16(&units, &[(a_offset, a_corruption), (b_offset, b_corruption)]);
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[test]
+#[should_panic(expected = "increasing")]
+fn utf8_same_codepoint_twice() {
+    corrupt_utf8("ab", &[(1, Utf8Corruption::Overlong), (1, Utf8Corruption::Overlong)]);
+}
+
+#[test]
+#[should_panic(expected = "cannot be split")]
+fn utf8_split_ascii() {
+    corrupt_utf8("ab", &[(1, Utf8Corruption::Split)]);
+}
+
+#[test]
+#[should_panic(expected = "not at the start of a codepoint")]
+fn utf16_trailing_surrogate_offset() {
+    corrupt_utf16(&[0xd83d, 0xde00], &[(1, Utf16Corruption::LoneLeadingSurrogate)]);
+}
@@ -186,6 +186,37 @@ fn from_utf16_tuple() {
     assert_eq!(kind(char::from_utf8_slice_start(&[0xed, 0xbf, 0xbf])), Err(Utf16ReservedCodepoint));
 }
 
+#[test] fn utf8_truncated_sequence_reports_what_more_bytes_cant_fix() {
+    use encode_unicode::iterator::Utf8CharDecoder;
+    let truncated: [(&[u8], Utf8ErrorKind); 12] = [
+        (&[0xe0, 0x80], OverlongEncoding),
+        (&[0xe0, 0x9f], OverlongEncoding),
+        (&[0xf0, 0x8f], OverlongEncoding),
+        (&[0xf0, 0x80, 0xbf], OverlongEncoding),
+        (&[0xed, 0xa0], Utf16ReservedCodepoint),
+        (&[0xed, 0xbf], Utf16ReservedCodepoint),
+        (&[0xf4, 0x90], TooHighCodepoint),
+        (&[0xf4, 0xbf, 0x80], TooHighCodepoint),
+        // these can still become valid
+        (&[0xe0, 0xa0], TooFewBytes),
+        (&[0xed, 0x9f], TooFewBytes),
+        (&[0xf0, 0x90, 0x80], TooFewBytes),
+        (&[0xf4, 0x8f], TooFewBytes),
+    ];
+    for &(bytes, expected) in &truncated {
+        assert_eq!(kind(Utf8Char::from_slice_start(bytes)), Err(expected), "{:x?}", bytes);
+        assert_eq!(kind(char::from_utf8_slice_start(bytes)), Err(expected), "{:x?}", bytes);
+        let mut decoder: Utf8CharDecoder = bytes.utf8char_indices();
+        let (offset, result, len) = decoder.next().unwrap();
+        assert_eq!((offset, kind(result), len), (0, Err(expected), 1), "{:x?}", bytes);
+        let mut validator = Utf8Validator::new();
+        for &b in bytes {
+            validator = validator.step(b);
+        }
+        assert_eq!(kind(validator.finish()), Err(expected), "{:x?}", bytes);
+    }
+}
+
 #[test] fn utf8_first_is_continuation_byte() {
     for first in 0x80..0xc0 {
         let arr = [first, first<<2, first<<4, first<<6];
@@ -225,3 +256,153 @@ fn from_utf16_tuple() {
         }
     }
 }
+
+#[test] fn slice_lengths_report_first_error() {
+    use encode_unicode::slice::{utf8_len, utf16_codepoint_count, utf16_len_of_utf8};
+    let units = [0x41, 0xd83d, 0xde00, 0xde00, 0xd800, 0x41];
+    let first_error = units.utf16char_indices().find(|&(_, result, _)| result.is_err() ).unwrap();
+    let error = utf8_len(&units).unwrap_err();
+    assert_eq!((error.offset(), Err(*error.error())), (first_error.0, first_error.1));
+    assert_eq!(utf16_codepoint_count(&units), Err(error));
+    for (offset, result, _) in b"ab\xe2\x82\xac\xc0\x80".utf8char_indices() {
+        if let Err(expected) = result {
+            let error = utf16_len_of_utf8(b"ab\xe2\x82\xac\xc0\x80").unwrap_err();
+            assert_eq!((error.offset(), *error.error()), (offset, expected));
+            break;
+        }
+    }
+    // errors after the first block, and codepoints that cross block ends
+    let mut long = "\u{1f600}".repeat(1300).into_bytes();
+    for &(end, error) in &[(4097, b"\xff"), (5000, b"\xc1"), (long.len(), b"\xf4")] {
+        let mut with_error = long[..end].to_vec();
+        with_error.extend_from_slice(error);
+        let offset = core::str::from_utf8(&with_error).unwrap_err().valid_up_to();
+        let expected = with_error.utf8char_indices().find(|&(_, r, _)| r.is_err() ).unwrap();
+        let error = utf16_len_of_utf8(&with_error).unwrap_err();
+        assert_eq!((error.offset(), Err(*error.error())), (expected.0, expected.1));
+        assert_eq!(error.offset(), offset);
+    }
+    long.push(b'a');
+    assert_eq!(utf16_len_of_utf8(&long), Ok(2*1300+1));
+}
+
+#[test] fn validate_utf16_reports_same_error_as_decoder() {
+    use encode_unicode::slice::validate_utf16;
+    // pairs at every alignment, and long runs without surrogates
+    let valid = "a😀bc😀😀d€€😀".repeat(5).encode_utf16()
+        .chain([0x41; 40].iter().cloned())
+        .collect::<Vec<u16>>();
+    assert_eq!(validate_utf16(&valid), Ok(()));
+    for i in 0..valid.len() {
+        for &bad in &[0xd800, 0xdbff, 0xdc00, 0xdfff, 0x41] {
+            let mut units = valid.clone();
+            units[i] = bad;
+            for end in &[i+1, i+2, units.len()] {
+                let units = &units[..(*end).min(units.len())];
+                let first_error = units.utf16char_indices().find(|&(_, result, _)| result.is_err() );
+                match validate_utf16(units) {
+                    Ok(()) => assert_eq!(first_error, None, "{:x?}", units),
+                    Err(e) => {
+                        let expected = first_error.unwrap();
+                        assert_eq!((e.offset(), Err(*e.error())), (expected.0, expected.1), "{:x?}", units);
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[test] fn utf8_decoders_agree() {
+    use encode_unicode::iterator::Utf8CharDecoder;
+    // every kind of byte, at the edges of the ranges the decoders treat differently
+    const BYTES: [u8; 18] = [
+        0x00, 0x7f, 0x80, 0x8f, 0x90, 0x9f, 0xa0, 0xbf, 0xc1,
+        0xc2, 0xe0, 0xe1, 0xed, 0xef, 0xf0, 0xf3, 0xf4, 0xf5,
+    ];
+    let mut bytes = Vec::new();
+    for n in 0..BYTES.len().pow(4) {
+        bytes.clear();
+        let mut n = n;
+        for _ in 0..4 {
+            bytes.push(BYTES[n % BYTES.len()]);
+            n /= BYTES.len();
+        }
+        for len in 0..=bytes.len() {
+            let bytes = &bytes[..len];
+            let forward = bytes.utf8char_indices().collect::<Vec<_>>();
+            let mut backward = bytes.utf8char_indices().rev().collect::<Vec<_>>();
+            backward.reverse();
+            assert_eq!(forward, backward, "{:x?}", bytes);
+            let merged = bytes.iter().to_utf8chars().collect::<Vec<_>>();
+            let decoded = forward.iter().map(|&(_, r, _)| r ).collect::<Vec<_>>();
+            assert_eq!(merged, decoded, "{:x?}", bytes);
+            // alternating between the ends
+            let mut iter: Utf8CharDecoder = bytes.utf8char_indices();
+            let (mut front, mut back) = (Vec::new(), Vec::new());
+            while let Some(item) = iter.next() {
+                front.push(item);
+                back.extend(iter.next_back());
+            }
+            back.reverse();
+            front.extend(back);
+            assert_eq!(front, forward, "{:x?}", bytes);
+
+            let first = forward.first().map(|&(_, r, len)| r.map(|u8c| (u8c, len)) );
+            let first = first.unwrap_or(Err(Utf8Char::from_slice_start(&[]).unwrap_err()));
+            assert_eq!(Utf8Char::from_slice_start(bytes), first, "{:x?}", bytes);
+            let as_char = first.map(|(u8c, len)| (u8c.to_char(), len) );
+            assert_eq!(char::from_utf8_slice_start(bytes), as_char, "{:x?}", bytes);
+
+            let mut validator = Utf8Validator::new();
+            for &b in bytes {
+                validator = validator.step(b);
+            }
+            let first_error = forward.iter().find_map(|&(_, r, _)| r.err() );
+            assert_eq!(validator.finish().map_err(|e| e.kind() ), first_error.map_or(Ok(()), |e| Err(e.kind())));
+            assert_eq!(validator.is_at_boundary(), core::str::from_utf8(bytes).is_ok());
+        }
+    }
+}
+
+#[test]
+fn packed_u32_rejects_what_to_packed_u32_never_returns() {
+    use encode_unicode::error::PackedCharError::*;
+    // every combination of lead byte and the top byte, with valid middle bytes
+    for first in 0..=255u32 {
+        for last in 0..=255u32 {
+            let packed = first | 0x00_80_80_00 | last << 24;
+            let expected = match Utf8Char::from_packed_u32(packed) {
+                Ok(utf8) => utf8.to_packed_u32(),
+                Err(_) => continue,
+            };
+            assert_eq!(expected, packed);
+        }
+    }
+    assert_eq!(Utf8Char::from_packed_u32(0xff_bf_bf_f4), Err(InvalidCodepoint));
+    assert_eq!(Utf8Char::from_packed_u32(0x80_00_00_00), Err(InvalidPadding));
+    assert_eq!(Utf8Char::from_packed_u32(0x00_80_b1_c3), Err(InvalidPadding));
+    assert_eq!(Utf8Char::from_packed_u32(0x01_00_00_61), Err(InvalidPadding));
+    assert_eq!(Utf16Char::from_packed_u32(0x0000_d800), Err(InvalidCodepoint));
+    assert_eq!(Utf16Char::from_packed_u32(0xdc00_dc00), Err(InvalidCodepoint));
+    assert_eq!(Utf16Char::from_packed_u32(0xdc00_0061), Err(InvalidPadding));
+    assert_eq!(Utf16Char::from_packed_u32(0x0001_0061), Err(InvalidPadding));
+}
+
+#[cfg(any(feature="std", feature="core_error"))]
+#[test] fn wrapping_errors_have_source() {
+    use std::error::Error;
+    let error = slice::validate_utf16(&[b'a' as u16, 0xdc00]).unwrap_err();
+    let source = error.source().expect("PositionedError has a source");
+    assert_eq!(source.downcast_ref(), Some(&Utf16PairError::UnexpectedTrailingSurrogate));
+    assert!(source.source().is_none());
+    assert_eq!(error.to_string(), "invalid text at offset 1");
+
+    let mut buf = [0u16; 4];
+    let (_, _, result) = slice::transcode_utf8_to_utf16(b"\xff", &mut buf);
+    let error = result.unwrap_err();
+    let source = error.source().and_then(|source| source.downcast_ref::<Utf8Error>() );
+    assert_eq!(source.map(Utf8Error::kind), Some(NonUtf8Byte));
+    assert_eq!(error.to_string(), "invalid text");
+    let (_, _, result) = slice::transcode_utf8_to_utf16("😀".as_bytes(), &mut buf[..1]);
+    assert!(result.unwrap_err().source().is_none());
+}
@@ -16,8 +16,8 @@
 
 extern crate encode_unicode;
 
-use encode_unicode::{IterExt, SliceExt, CharExt, Utf8Char};
-use encode_unicode::iterator::Utf8CharSplitter;
+use encode_unicode::{IterExt, SliceExt, StrExt, CharExt, Utf8Char};
+use encode_unicode::iterator::{Utf8CharSplitter, CharPosition, ColumnUnit};
 use encode_unicode::error::Utf8ErrorKind::*;
 use encode_unicode::error::Utf16PairError::*;
 use std::io::Read;
@@ -184,3 +184,171 @@ use std::cmp::min;
         assert_eq!(iter.read(&mut buf[..]).unwrap(), 0);
     }
 }
+
+#[test] fn utf8char_positions_match_str_positions() {
+    let s = "ab\u{e6}\n\u{1f600}\u{20ac}\n\n\u{10ffff}x";
+    for &unit in &[ColumnUnit::Utf8Bytes, ColumnUnit::Utf16Units, ColumnUnit::Codepoints] {
+        let mut expected = Vec::new();
+        let (mut line, mut column) = (0, 0);
+        for (codepoint, (byte, c)) in s.char_indices().enumerate() {
+            let utf16 = s[..byte].encode_utf16().count();
+            expected.push((CharPosition{byte, utf16, codepoint, line, column}, c.to_utf8()));
+            if c == '\n' {
+                line += 1;
+                column = 0;
+            } else {
+                column += match unit {
+                    ColumnUnit::Utf8Bytes => c.len_utf8(),
+                    ColumnUnit::Utf16Units => c.len_utf16(),
+                    ColumnUnit::Codepoints => 1,
+                };
+            }
+        }
+        let from_str = StrExt::utf8char_positions(s, unit).collect::<Vec<_>>();
+        assert_eq!(from_str, expected);
+        let from_slice = s.as_bytes().utf8char_positions(unit)
+            .map(|(position, result, _)| (position, result.unwrap()) )
+            .collect::<Vec<_>>();
+        assert_eq!(from_slice, expected);
+    }
+}
+
+#[test] fn utf8char_position_decoder_errors() {
+    let slice = b"\xf0\x9f\x98\n\xe2\x82\xacz\xff";
+    let mut iter = slice.utf8char_positions(ColumnUnit::Utf8Bytes);
+    for i in 0..3 {
+        let (position, result, len) = iter.next().unwrap();
+        assert_eq!(position, CharPosition{byte: i, utf16: i, codepoint: i, line: 0, column: i});
+        assert!(result.is_err());
+        assert_eq!(len, 1);
+    }
+    assert_eq!(iter.next().unwrap().0.line, 0);
+    let (position, result, len) = iter.next().unwrap();
+    assert_eq!(position, CharPosition{byte: 4, utf16: 4, codepoint: 4, line: 1, column: 0});
+    assert_eq!((result, len), (Ok(Utf8Char::from('\u{20ac}')), 3));
+    let (position, _, _) = iter.next().unwrap();
+    assert_eq!(position, CharPosition{byte: 7, utf16: 5, codepoint: 5, line: 1, column: 3});
+    let (position, result, _) = iter.next().unwrap();
+    assert_eq!((position.byte, position.column), (8, 4));
+    assert!(result.is_err());
+    assert_eq!(iter.next(), None);
+    let end = iter.position();
+    assert_eq!(end, CharPosition{byte: 9, utf16: 7, codepoint: 7, line: 1, column: 5});
+}
+
+#[test] fn ascii_runs_at_every_alignment() {
+    use encode_unicode::iterator::AsciiRunOrChar;
+    for ascii_len in 0..20 {
+        let run = "abcdefghijklmnopqrstuvwxyz"[..ascii_len].to_owned();
+        let s = [run.as_str(), "æ", &run, "\u{1f600}\u{20ac}", &run].concat();
+        let chars = s.char_indices()
+            .map(|(i, c)| (i, Utf8Char::from(c)) )
+            .collect::<Vec<_>>();
+        assert_eq!(StrExt::utf8char_indices(s.as_str()).collect::<Vec<_>>(), chars, "{:?}", s);
+        let mut reversed = StrExt::utf8char_indices(s.as_str()).rev().collect::<Vec<_>>();
+        reversed.reverse();
+        assert_eq!(reversed, chars);
+        let decoded = s.as_bytes().utf8char_indices()
+            .map(|(i, r, _)| (i, r.unwrap()) )
+            .collect::<Vec<_>>();
+        assert_eq!(decoded, chars);
+        let merged = s.bytes().to_utf8chars().map(Result::unwrap).collect::<Vec<Utf8Char>>();
+        assert_eq!(merged, s.utf8chars().collect::<Vec<Utf8Char>>());
+        // alternating between the ends, which limits the ASCII runs from the back
+        let mut iter = s.as_bytes().utf8char_indices();
+        let mut from_both = Vec::new();
+        while let Some((i, r, _)) = iter.next() {
+            from_both.push((i, r.unwrap()));
+            if let Some((i, r, _)) = iter.next_back() {
+                from_both.push((i, r.unwrap()));
+            }
+        }
+        from_both.sort_by_key(|&(i, _)| i );
+        assert_eq!(from_both, chars);
+
+        let runs = s.ascii_runs().collect::<Vec<_>>();
+        assert_eq!(runs.iter().map(AsciiRunOrChar::as_str).collect::<String>(), s);
+        for pair in runs.windows(2) {
+            let consecutive = matches!(pair, [AsciiRunOrChar::Ascii(_), AsciiRunOrChar::Ascii(_)]);
+            assert!(!consecutive, "{:?}", runs);
+        }
+        for item in &runs {
+            match *item {
+                AsciiRunOrChar::Ascii(run) => assert!(!run.is_empty()  &&  run.is_ascii()),
+                AsciiRunOrChar::Char(c) => assert!(!c.is_ascii()),
+            }
+        }
+        let mut reversed = s.ascii_runs().rev().collect::<Vec<_>>();
+        reversed.reverse();
+        assert_eq!(reversed, runs);
+    }
+}
+
+#[test] fn utf8char_chunks_match_lossy_and_decoder() {
+    const BYTES: [u8; 12] = [b'a', 0x80, 0xa0, 0xbf, 0xc1, 0xc3, 0xe0, 0xed, 0xef, 0xf0, 0xf4, 0xff];
+    for n in 0..BYTES.len().pow(5) {
+        let mut n = n;
+        let bytes = (0..5).map(|_| {
+            let b = BYTES[n % BYTES.len()];
+            n /= BYTES.len();
+            b
+        }).collect::<Vec<u8>>();
+        let errors = bytes.utf8char_indices()
+            .filter_map(|(offset, r, _)| r.err().map(|e| (offset, e)) )
+            .collect::<Vec<_>>();
+        let mut lossy = String::new();
+        let mut offset = 0;
+        for (valid, invalid) in bytes.utf8char_chunks() {
+            lossy.push_str(valid);
+            offset += valid.len();
+            if let Some((invalid, error)) = invalid {
+                lossy.push('\u{fffd}');
+                assert!(errors.contains(&(offset, error)), "{:x?} at {}", bytes, offset);
+                offset += invalid.len();
+            }
+        }
+        assert_eq!(offset, bytes.len());
+        assert_eq!(lossy, String::from_utf8_lossy(&bytes), "{:x?}", bytes);
+    }
+    assert_eq!(b"".utf8char_chunks().next(), None);
+}
+
+#[test] fn utf16char_chunks_match_decoder() {
+    const UNITS: [u16; 5] = [0x41, 0xd800, 0xdbff, 0xdc00, 0xdfff];
+    for n in 0..UNITS.len().pow(6) {
+        let mut n = n;
+        let units = (0..6).map(|_| {
+            let u = UNITS[n % UNITS.len()];
+            n /= UNITS.len();
+            u
+        }).collect::<Vec<u16>>();
+        let errors = units.utf16char_indices()
+            .filter_map(|(offset, r, _)| r.err().map(|e| (offset, units[offset], e)) )
+            .collect::<Vec<_>>();
+        let mut from_chunks = Vec::new();
+        let mut offset = 0;
+        for (valid, invalid) in units.utf16char_chunks() {
+            assert!(String::from_utf16(valid).is_ok(), "{:x?}", units);
+            offset += valid.len();
+            if let Some((unit, error)) = invalid {
+                from_chunks.push((offset, unit, error));
+                offset += 1;
+            }
+        }
+        assert_eq!(offset, units.len());
+        assert_eq!(from_chunks, errors);
+    }
+}
+
+#[test] fn chunks_of_slice_references() {
+    // std's inherent <[u8]>::utf8_chunks() would take priority over a trait method with that name.
+    let bytes: &[u8] = b"ab\xffc";
+    let chunks = bytes.utf8char_chunks().collect::<Vec<_>>();
+    assert_eq!(chunks.len(), 2);
+    assert_eq!(chunks[0].0, "ab");
+    assert_eq!(chunks[0].1.map(|(invalid, error)| (invalid, error.kind()) ), Some((&b"\xff"[..], NonUtf8Byte)));
+    assert_eq!(chunks[1], ("c", None));
+    let units: &[u16] = &[0x61, 0xdc00, 0x62];
+    let chunks = units.utf16char_chunks().collect::<Vec<_>>();
+    assert_eq!(chunks, [(&units[..1], Some((0xdc00, UnexpectedTrailingSurrogate))), (&units[2..], None)]);
+}
@@ -0,0 +1,167 @@
+/* Copyright 2022 Torbjørn Birch Moltu
+ *
+ * Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+ * http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+ * http://opensource.org/licenses/MIT>, at your option. This file may not be
+ * copied, modified, or distributed except according to those terms.
+ */
+
+//! Test the nom parsers with complete, truncated and invalid input.
+
+#![cfg(all(feature="std", feature="nom"))]
+
+extern crate encode_unicode;
+use encode_unicode::{Utf8Char, Utf16Char};
+use encode_unicode::error::{Utf8Error, Utf8ErrorKind, Utf16PairError};
+use encode_unicode::nom_parsers::*;
+use nom::{Err, IResult, Needed, Parser};
+use nom::error::{ErrorKind, ParseError, FromExternalError};
+
+/// Codepoints of every length in both encodings, so that the streaming parsers
+/// report every amount of needed input, and a `z` for `take_utf8_while()` to stop at.
+const STREAM: &str = "ø\u{ffff}a\u{10ffff}\u{80}😀\u{7ff}z";
+
+/// Records the error and how much input remained when it happened.
+#[derive(Clone,Copy, Debug, PartialEq,Eq)]
+enum Invalid {
+    Nom(usize, ErrorKind),
+    Utf8(usize, Utf8ErrorKind),
+    Utf16(usize, Utf16PairError),
+}
+impl<'a> ParseError<&'a [u8]> for Invalid {
+    fn from_error_kind(input: &'a [u8],  kind: ErrorKind) -> Self {
+        Invalid::Nom(input.len(), kind)
+    }
+    fn append(_: &'a [u8],  _: ErrorKind,  other: Self) -> Self {
+        other
+    }
+}
+impl<'a> FromExternalError<&'a [u8], Utf8Error> for Invalid {
+    fn from_external_error(input: &'a [u8],  kind: ErrorKind,  e: Utf8Error) -> Self {
+        assert_eq!(kind, ErrorKind::Char);
+        Invalid::Utf8(input.len(), e.kind())
+    }
+}
+impl<'a> FromExternalError<&'a [u8], Utf16PairError> for Invalid {
+    fn from_external_error(input: &'a [u8],  kind: ErrorKind,  e: Utf16PairError) -> Self {
+        assert_eq!(kind, ErrorKind::Char);
+        Invalid::Utf16(input.len(), e)
+    }
+}
+
+fn utf16_char(input: &[u8],  big_endian: bool) -> IResult<&[u8], Utf16Char, Invalid> {
+    if big_endian {utf16be_char(input)} else {utf16le_char(input)}
+}
+
+#[test]
+fn utf8_chars() {
+    let mut input = STREAM.as_bytes();
+    for c in STREAM.chars() {
+        let encoded = &input[..c.len_utf8()];
+        for len in 0..encoded.len() {
+            // the length is unknown until the first byte is available
+            let needed = Needed::new(if len == 0 {1} else {encoded.len() - len});
+            assert_eq!(utf8_char::<Invalid>(&encoded[..len]), Err(Err::Incomplete(needed)), "{:?}", c);
+        }
+        let (rest, u8c) = utf8_char::<Invalid>(input).unwrap();
+        assert_eq!(u8c, c);
+        input = rest;
+    }
+    assert_eq!(input, b"");
+}
+
+#[test]
+fn utf8_errors() {
+    let remaining = |input: &[u8]| utf8_char::<Invalid>(input).unwrap_err();
+    let error = |remaining, kind| Err::Error(Invalid::Utf8(remaining, kind));
+    assert_eq!(remaining(b"\xe0\x80\x80a"), error(4, Utf8ErrorKind::OverlongEncoding));
+    assert_eq!(remaining(b"\xe0\x80"), error(2, Utf8ErrorKind::OverlongEncoding));
+    assert_eq!(remaining(b"\xed\xa0\x80"), error(3, Utf8ErrorKind::Utf16ReservedCodepoint));
+    assert_eq!(remaining(b"\xf4\x90"), error(2, Utf8ErrorKind::TooHighCodepoint));
+    assert_eq!(remaining(b"\xc3a"), error(2, Utf8ErrorKind::InterruptedSequence));
+    assert_eq!(remaining(b"\x80"), error(1, Utf8ErrorKind::UnexpectedContinuationByte));
+    assert_eq!(remaining(b"\xff"), error(1, Utf8ErrorKind::NonUtf8Byte));
+}
+
+#[test]
+fn utf16_chars() {
+    for &big_endian in &[false, true] {
+        let parser = |input| utf16_char(input, big_endian);
+        let to_bytes = if big_endian {u16::to_be_bytes} else {u16::to_le_bytes};
+        let bytes = STREAM.encode_utf16().flat_map(to_bytes).collect::<Vec<u8>>();
+        let mut input = &bytes[..];
+        for c in STREAM.chars() {
+            let encoded = &input[..2*c.len_utf16()];
+            for len in 0..encoded.len() {
+                let needed = Needed::new(if len < 2 {2 - len} else {encoded.len() - len});
+                assert_eq!(parser(&encoded[..len]), Err(Err::Incomplete(needed)), "{:?}", c);
+            }
+            let (rest, u16c) = parser(input).unwrap();
+            assert_eq!(u16c, c);
+            input = rest;
+        }
+        assert_eq!(input, b"");
+
+        let error = |units: [u16; 2]| {
+            let bytes = units.iter().flat_map(|&unit| to_bytes(unit) ).collect::<Vec<u8>>();
+            utf16_char(&bytes, big_endian).unwrap_err()
+        };
+        let unexpected = Err::Error(Invalid::Utf16(4, Utf16PairError::UnexpectedTrailingSurrogate));
+        assert_eq!(error([0xdc00, 0xd800]), unexpected);
+        let unmatched = Err::Error(Invalid::Utf16(4, Utf16PairError::UnmatchedLeadingSurrogate));
+        assert_eq!(error([0xdbff, 0x0041]), unmatched);
+        assert_eq!(error([0xd800, 0xd800]), unmatched);
+    }
+}
+
+#[test]
+fn take_while() {
+    let mut parser = take_utf8_while::<_, Invalid>(|u8c| u8c != 'z' );
+    assert_eq!(parser(STREAM.as_bytes()), Ok((&b"z"[..], STREAM.trim_end_matches('z'))));
+    assert_eq!(parser(b"z"), Ok((&b"z"[..], "")));
+    assert_eq!(parser(b""), Err(Err::Incomplete(Needed::new(1))));
+    assert_eq!(parser(b"ab\xf0\x90"), Err(Err::Incomplete(Needed::new(2))));
+    let error = Err::Error(Invalid::Utf8(3, Utf8ErrorKind::UnexpectedContinuationByte));
+    assert_eq!(parser(b"ab\x80cz"), Err(error));
+    // the predicate sees every codepoint up to and including the one that stops it
+    let mut seen = Vec::new();
+    let mut recording = take_utf8_while::<_, Invalid>(|u8c: Utf8Char| { seen.push(u8c.to_char());  u8c.is_ascii() });
+    assert_eq!(recording("ab\u{80}".as_bytes()), Ok(("\u{80}".as_bytes(), "ab")));
+    drop(recording);
+    assert_eq!(seen, ['a', 'b', '\u{80}']);
+}
+
+#[test]
+fn str_len() {
+    for (end, _) in STREAM.char_indices().chain(Some((STREAM.len(), 'z'))) {
+        let mut parser = utf8_str_len::<Invalid>(end);
+        assert_eq!(parser(STREAM.as_bytes()), Ok((&STREAM.as_bytes()[end..], &STREAM[..end])));
+        if end > 0 {
+            let needed = Needed::new(1);
+            assert_eq!(parser(&STREAM.as_bytes()[..end-1]), Err(Err::Incomplete(needed)));
+        }
+    }
+    // a codepoint that crosses the end of the field is an error, not incomplete
+    let cut = Err::Error(Invalid::Utf8(3, Utf8ErrorKind::TooFewBytes));
+    assert_eq!(utf8_str_len::<Invalid>(3)("a€".as_bytes()), Err(cut));
+    let invalid = Err::Error(Invalid::Utf8(3, Utf8ErrorKind::NonUtf8Byte));
+    assert_eq!(utf8_str_len::<Invalid>(3)(b"a\xffbc"), Err(invalid));
+}
+
+#[test]
+fn with_combinators() {
+    // fields prefixed by their length in bytes, in a stream that might have been cut off
+    let mut field = nom::combinator::flat_map(nom::number::streaming::u8, |len| utf8_str_len::<Invalid>(len as usize) );
+    assert_eq!(field.parse(&b"\x03\xe2\x82\xacz"[..]), Ok((&b"z"[..], "€")));
+    assert_eq!(field.parse(&b"\x03\xe2\x82"[..]), Err(Err::Incomplete(Needed::new(1))));
+
+    let mut field = nom::sequence::preceded(
+        nom::bytes::streaming::tag(&b"\x00"[..]),
+        nom::sequence::pair(utf16be_char, utf8_char),
+    );
+    let (rest, (a, b)) = field.parse(&b"\x00\xd8\x3d\xde\x00z\x00"[..]).unwrap();
+    assert_eq!((a.to_char(), b.to_char(), rest), ('😀', 'z', &b"\x00"[..]));
+    assert_eq!(field.parse(rest), Err(Err::Incomplete(Needed::new(2))));
+    let error = Err::Error(Invalid::Utf16(3, Utf16PairError::UnexpectedTrailingSurrogate));
+    assert_eq!(field.parse(&b"\x00\xdc\x00z"[..]), Err(error));
+}
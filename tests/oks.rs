@@ -35,6 +35,63 @@ fn same_size_as_char() {
     use std::mem::size_of;
     assert_eq!(size_of::<Utf8Char>(), size_of::<char>());
     assert_eq!(size_of::<Utf16Char>(), size_of::<char>());
+    assert_eq!(size_of::<Option<Utf8Char>>(), size_of::<Option<char>>());
+    assert_eq!(size_of::<Option<Utf16Char>>(), size_of::<Option<char>>());
+}
+
+#[test]
+fn utf16_macros() {
+    const EDGES: &str = "\u{1}\u{7f}\u{80}\u{7ff}\u{800}\u{d7ff}\u{e000}\u{ffff}\u{10000}\u{10ffff}";
+    let mut units = EDGES.encode_utf16().collect::<Vec<u16>>();
+    assert_eq!(&utf16!(EDGES)[..], &units[..]);
+    units.push(0);
+    assert_eq!(&utf16z!(EDGES)[..], &units[..]);
+    assert_eq!(utf16!("\0"), &[0]);
+}
+
+#[test]
+fn const_decoding() {
+    const NUL: Utf8Char = utf8char!("\0");
+    const SIGMA: Utf8Char = utf8char!("\u{3a3}");
+    const PAIR: Utf16Char = utf16char!("\u{1f600}");
+    assert_eq!(NUL, Utf8Char::from('\0'));
+    assert_eq!(SIGMA, Utf8Char::from('\u{3a3}'));
+    assert_eq!(PAIR, Utf16Char::from('\u{1f600}'));
+
+    const FROM_SLICE: Result<(Utf8Char, usize), error::Utf8Error> = Utf8Char::from_slice_start(b"\xe2\x82\xacuro");
+    assert_eq!(FROM_SLICE, Ok((Utf8Char::from('\u{20ac}'), 3)));
+    const FROM_ARRAY: Result<Utf8Char, error::Utf8Error> = Utf8Char::from_array([0xf0, 0x9f, 0x98, 0x80]);
+    assert_eq!(FROM_ARRAY, Ok(Utf8Char::from('\u{1f600}')));
+    const FROM_UNITS: Result<(Utf16Char, usize), error::Utf16SliceError> = Utf16Char::from_slice_start(&[0xd83d, 0xde00, 0x20]);
+    assert_eq!(FROM_UNITS, Ok((PAIR, 2)));
+    const CHARS: [char; 3] = [NUL.to_char(), SIGMA.to_char(), PAIR.to_char()];
+    assert_eq!(CHARS, ['\0', '\u{3a3}', '\u{1f600}']);
+}
+
+#[test]
+fn nul_is_stored_like_other_codepoints() {
+    let u8c = Utf8Char::from('\0');
+    assert_eq!(u8c.to_array(), ([0, 0, 0, 0], 1));
+    assert_eq!(u8c.as_bytes(), b"\0");
+    assert_eq!(u8c.len(), 1);
+    assert_eq!(u8c, 0u8);
+    assert_eq!(u8c.to_ascii_uppercase(), u8c);
+    assert_eq!(Some(u8c), Utf8Char::from_slice_start(b"\0").ok().map(|(u8c, _)| u8c ));
+    assert_eq!(unsafe { Utf8Char::from_array_unchecked([0, 0, 0, 0]) }, u8c);
+    assert_eq!(Vec::<u8>::from_iter(Some(u8c)), vec![0]);
+    let u16c = Utf16Char::from('\0');
+    assert_eq!(u16c.to_array(), [0, 0]);
+    assert_eq!(u16c.to_tuple(), (0, None));
+    assert_eq!(&u16c[..], &[0]);
+    assert!(u16c.is_bmp());
+    assert_eq!(u16c, 0u16);
+    assert_eq!(Some(u16c), Utf16Char::from_slice_start(&[0, 0xdc00]).ok().map(|(u16c, _)| u16c ));
+    assert_eq!(unsafe { Utf16Char::from_array_unchecked([0, 0]) }, u16c);
+    assert_eq!(Vec::<u16>::from_iter(Some(u16c)), vec![0]);
+    for c in &['\u{1}', '\u{100}', '\u{10000}'] {
+        assert!(u8c < c.to_utf8());
+        assert!(u16c < c.to_utf16());
+    }
 }
 
 #[test]
@@ -194,6 +251,10 @@ fn test(c: char) {
     assert_eq!(Utf8Char::from_array(arr), Ok(u8c));
     assert_eq!(Utf8Char::new(c), u8c);
     assert_eq!(c.to_utf8_array(),  (arr, len));
+    let packed = u8c.to_packed_u32();
+    assert_eq!(packed, u32::from_le_bytes(arr) | if c == '\0' {0x01_00_00_00} else {0});
+    assert_eq!(Utf8Char::from_packed_u32(packed), Ok(u8c));
+    assert_eq!(unsafe { Utf8Char::from_packed_u32_unchecked(packed) }, u8c);
 
     let str_ = str::from_utf8(reference).unwrap();
     let ustr = Utf8Char::from_str(str_).unwrap();
@@ -261,6 +322,10 @@ fn test(c: char) {
     assert_eq!(tuple, (reference[0],reference.get(1).cloned()));
     assert_eq!(char::from_utf16_array(array), Ok(c));
     assert_eq!(char::from_utf16_tuple(tuple), Ok(c));
+    let packed = u16c.to_packed_u32();
+    assert_eq!(packed, array[0] as u32 | (array[1] as u32) << 16 | if c == '\0' {0x0001_0000} else {0});
+    assert_eq!(Utf16Char::from_packed_u32(packed), Ok(u16c));
+    assert_eq!(unsafe { Utf16Char::from_packed_u32_unchecked(packed) }, u16c);
     assert_eq!(c.to_utf16().to_char(), c);
     assert_eq!(&Vec::<u16>::from_iter(Some(u16c))[..], reference);
     assert_eq!(format!("{:?}", u16c), format!("{:?}", c));
@@ -309,3 +374,24 @@ test_codepoint_range!{all_d0000_e0000,   0xd0000..0xe0000}
 test_codepoint_range!{all_e0000_f0000,   0xe0000..0xf0000}
 test_codepoint_range!{all_f0000_100000,  0xf0000..0x100000}
 test_codepoint_range!{all_100000_110000, 0x100000..0x110000}
+
+#[test]
+fn lengths_of_text_longer_than_a_block() {
+    use encode_unicode::slice::*;
+    let text = "Hello, wørld! Ærlig talt 😀🦀 ∞ \u{10ffff}".repeat(100);
+    let units = text.encode_utf16().collect::<Vec<u16>>();
+    for end in (0..text.len()).filter(|&end| text.is_char_boundary(end) ) {
+        let s = &text[..end];
+        assert_eq!(codepoint_count(s), s.chars().count());
+        assert_eq!(utf16_len(s), s.encode_utf16().count());
+        assert_eq!(utf8_codepoint_count(s.as_bytes()), Ok(s.chars().count()));
+        assert_eq!(utf16_len_of_utf8(s.as_bytes()), Ok(s.encode_utf16().count()));
+    }
+    for end in (0..units.len()).filter(|&end| units.is_char_boundary(end) ) {
+        let s = String::from_utf16(&units[..end]).unwrap();
+        assert_eq!(utf8_len(&units[..end]), Ok(s.len()));
+        assert_eq!(utf16_codepoint_count(&units[..end]), Ok(s.chars().count()));
+    }
+    assert_eq!(utf8_len(&units), Ok(text.len()));
+    assert_eq!(utf16_codepoint_count(&units), Ok(text.chars().count()));
+}
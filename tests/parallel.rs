@@ -0,0 +1,138 @@
+/* Copyright 2022 Torbjørn Birch Moltu
+ *
+ * Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+ * http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+ * http://opensource.org/licenses/MIT>, at your option. This file may not be
+ * copied, modified, or distributed except according to those terms.
+ */
+
+//! Test that the parallel functions give the same results as the decoders,
+//! especially for invalid sequences at the seams between chunks.
+
+#![cfg(all(feature="rayon", feature="std"))]
+
+extern crate encode_unicode;
+use encode_unicode::SliceExt;
+use encode_unicode::parallel::*;
+use encode_unicode::error::{Utf8ErrorKind, Utf16PairError};
+
+/// Chunks are at least this long, so the first seam is right after it.
+const MIN_CHUNK_LEN: usize = 64*1024;
+
+fn first_utf8_error(bytes: &[u8]) -> Option<(usize, Utf8ErrorKind)> {
+    bytes.utf8char_indices().find_map(|(offset, result, _)| Some((offset, result.err()?.kind())) )
+}
+
+fn first_utf16_error(units: &[u16]) -> Option<(usize, Utf16PairError)> {
+    units.utf16char_indices().find_map(|(offset, result, _)| Some((offset, result.err()?)) )
+}
+
+/// Place `snippet` at every offset around the first seam, and near the end.
+///
+/// `padding` must be single bytes or units, so that cutting it doesn't cause errors.
+fn around_seams<T: Copy>(padding: &[T],  snippet: &[T]) -> Vec<Vec<T>> {
+    let mut haystacks = Vec::new();
+    for before in MIN_CHUNK_LEN-6..MIN_CHUNK_LEN+2 {
+        let mut haystack = padding.iter().cycle().take(before).cloned().collect::<Vec<T>>();
+        haystack.extend_from_slice(snippet);
+        haystack.extend(padding.iter().take(20));
+        haystacks.push(haystack);
+    }
+    let mut at_end = padding.iter().cycle().take(MIN_CHUNK_LEN+10).cloned().collect::<Vec<T>>();
+    at_end.extend_from_slice(snippet);
+    haystacks.push(at_end);
+    haystacks
+}
+
+#[test]
+fn utf8_at_seams() {
+    let padding = b"bl\x7fkake";
+    let snippets: &[&[u8]] = &[
+        "€😀".as_bytes(),
+        b"\xe2\x82a",
+        b"\xf0\x9f\x98",
+        b"\xe0\x80\x80",
+        b"\xed\xa0\x80",
+        b"\x80\x80\x80\x80\x80",
+        b"\xc3",
+        b"\xff",
+    ];
+    for snippet in snippets {
+        for haystack in around_seams(padding, snippet) {
+            let expected = first_utf8_error(&haystack);
+            let error = validate_utf8(&haystack).err().map(|e| (e.offset(), e.error().kind()) );
+            assert_eq!(error, expected, "{:?} after {}", snippet, haystack.len());
+            match transcode_utf8_to_utf16(&haystack) {
+                Ok(units) => {
+                    assert_eq!(expected, None);
+                    let text = String::from_utf8(haystack.clone()).unwrap();
+                    assert!(units.iter().cloned().eq(text.encode_utf16()));
+                }
+                Err(e) => assert_eq!(Some((e.offset(), e.error().kind())), expected),
+            }
+        }
+    }
+}
+
+#[test]
+fn utf16_at_seams() {
+    let padding = "blø€\u{ffff}".encode_utf16().collect::<Vec<u16>>();
+    let snippets: &[&[u16]] = &[
+        &[0xd83d, 0xde00],
+        &[0xd83d, 0x61],
+        &[0xd83d, 0xd83d, 0xde00],
+        &[0xde00, 0xde00, 0xde00],
+        &[0xd83d],
+    ];
+    for snippet in snippets {
+        for haystack in around_seams(&padding, snippet) {
+            let expected = first_utf16_error(&haystack);
+            let error = validate_utf16(&haystack).err().map(|e| (e.offset(), *e.error()) );
+            assert_eq!(error, expected, "{:?} after {}", snippet, haystack.len());
+            match transcode_utf16_to_utf8(&haystack) {
+                Ok(text) => {
+                    assert_eq!(expected, None);
+                    assert_eq!(text, String::from_utf16(&haystack).unwrap());
+                }
+                Err(e) => assert_eq!(Some((e.offset(), *e.error())), expected),
+            }
+        }
+    }
+}
+
+#[test]
+fn first_of_many_errors() {
+    let mut bytes = "bløtkake".repeat(200_000).into_bytes();
+    for i in (0..bytes.len()).step_by(70_001).rev() {
+        bytes[i] = 0xff;
+    }
+    bytes[123_457] = 0x80;
+    let error = validate_utf8(&bytes).unwrap_err();
+    assert_eq!((error.offset(), error.error().kind()), (0, Utf8ErrorKind::NonUtf8Byte));
+    bytes[0] = b'b';
+    let error = transcode_utf8_to_utf16(&bytes).unwrap_err();
+    assert_eq!((error.offset(), error.error().kind()), (70_001, Utf8ErrorKind::NonUtf8Byte));
+    assert_eq!(Some((error.offset(), error.error().kind())), first_utf8_error(&bytes));
+}
+
+#[test]
+fn splitting() {
+    let text = "æ😀€a".repeat(1000);
+    for target_len in [1, 2, 3, 7, 100, 10_000].iter().cloned() {
+        let pieces = split_utf8(text.as_bytes(), target_len);
+        assert_eq!(pieces.concat(), text.as_bytes());
+        for piece in &pieces[..pieces.len()-1] {
+            assert!(std::str::from_utf8(piece).is_ok());
+            assert!(piece.len() >= target_len  &&  piece.len() < target_len + 4);
+        }
+        let units = text.encode_utf16().collect::<Vec<u16>>();
+        let pieces = split_utf16(&units, target_len);
+        assert_eq!(pieces.concat(), units);
+        for piece in &pieces[..pieces.len()-1] {
+            assert!(String::from_utf16(piece).is_ok());
+            assert!(piece.len() >= target_len  &&  piece.len() < target_len + 2);
+        }
+    }
+    assert!(split_utf8(b"", 10).is_empty());
+    assert!(split_utf16(&[], 10).is_empty());
+}
@@ -0,0 +1,132 @@
+/* Copyright 2022 Torbjørn Birch Moltu
+ *
+ * Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+ * http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+ * http://opensource.org/licenses/MIT>, at your option. This file may not be
+ * copied, modified, or distributed except according to those terms.
+ */
+
+//! Compare the search functions against naive implementations,
+//! on long and invalid haystacks.
+
+#![cfg(feature="std")]
+
+extern crate encode_unicode;
+use encode_unicode::{Utf8Char, Utf16Char};
+use encode_unicode::corrupt::{corrupt_utf8, corrupt_utf16, Utf8Corruption, Utf16Corruption};
+use encode_unicode::slice::*;
+
+/// Codepoints that share a first byte, most of their bytes, a leading surrogate,
+/// or the bytes of a unit in the other order, so that a search which only
+/// compares part of the needle finds the wrong one.
+const NEAR_MISSES: &str = "øÃ€₂😀😁\u{2c00},\u{10ffff}\u{ffff}z";
+
+fn naive_find<T: PartialEq>(haystack: &[T],  needle: &[T]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|window| window == needle )
+}
+
+fn naive_rfind<T: PartialEq>(haystack: &[T],  needle: &[T]) -> Option<usize> {
+    haystack.windows(needle.len()).rposition(|window| window == needle )
+}
+
+fn naive_split<'a, T: PartialEq>(mut haystack: &'a [T],  needle: &[T]) -> Vec<&'a [T]> {
+    let mut parts = Vec::new();
+    while let Some(found) = naive_find(haystack, needle) {
+        parts.push(&haystack[..found]);
+        haystack = &haystack[found+needle.len()..];
+    }
+    parts.push(haystack);
+    parts
+}
+
+/// Valid and invalid UTF-8 with the needles both near the ends and far from them.
+fn utf8_haystacks() -> Vec<Vec<u8>> {
+    let (corrupted, _) = corrupt_utf8(NEAR_MISSES, &[
+        (0, Utf8Corruption::StrayContinuationByte),
+        (2, Utf8Corruption::Overlong),
+        (7, Utf8Corruption::Split),
+        (14, Utf8Corruption::EncodedSurrogate(0xdc00)),
+        (22, Utf8Corruption::Split),
+    ]);
+    let padding = "bløtkake ".repeat(20);
+    vec![
+        Vec::new(),
+        NEAR_MISSES.as_bytes().to_vec(),
+        corrupted.clone(),
+        [padding.as_bytes(), NEAR_MISSES.as_bytes(), padding.as_bytes()].concat(),
+        [padding.as_bytes(), &corrupted, padding.as_bytes(), &corrupted].concat(),
+    ]
+}
+
+/// Valid and invalid UTF-16, including ASCII which makes every other byte zero.
+fn utf16_haystacks() -> Vec<Vec<u16>> {
+    let units = NEAR_MISSES.encode_utf16().collect::<Vec<u16>>();
+    let (corrupted, _) = corrupt_utf16(&units, &[
+        (0, Utf16Corruption::LoneTrailingSurrogate),
+        (2, Utf16Corruption::LoneLeadingSurrogate),
+        (6, Utf16Corruption::Split),
+        (12, Utf16Corruption::LoneLeadingSurrogate),
+    ]);
+    let padding = "bløtkake \u{100}\u{1ff} ".repeat(20).encode_utf16().collect::<Vec<u16>>();
+    vec![
+        Vec::new(),
+        units.clone(),
+        corrupted.clone(),
+        [&padding[..], &units, &padding].concat(),
+        [&padding[..], &corrupted, &padding, &corrupted].concat(),
+    ]
+}
+
+#[test]
+fn utf8() {
+    for haystack in utf8_haystacks() {
+        for c in NEAR_MISSES.chars().chain("øbk \u{100}€".chars()) {
+            let needle = Utf8Char::from(c);
+            let bytes = needle.as_bytes();
+            assert_eq!(find_utf8char(&haystack, needle), naive_find(&haystack, bytes), "{:?}", c);
+            assert_eq!(rfind_utf8char(&haystack, needle), naive_rfind(&haystack, bytes), "{:?}", c);
+            let parts = naive_split(&haystack, bytes);
+            assert_eq!(count_utf8char(&haystack, needle), parts.len() - 1, "{:?}", c);
+            assert_eq!(split_on_utf8char(&haystack, needle).collect::<Vec<_>>(), parts, "{:?}", c);
+            let reversed = parts.iter().rev().cloned().collect::<Vec<_>>();
+            assert_eq!(split_on_utf8char(&haystack, needle).rev().collect::<Vec<_>>(), reversed);
+        }
+    }
+}
+
+#[test]
+fn utf16() {
+    for haystack in utf16_haystacks() {
+        for c in NEAR_MISSES.chars().chain("øbk \u{100}\u{1ff}\u{2ff}€".chars()) {
+            let needle = Utf16Char::from(c);
+            let units = needle.as_ref();
+            assert_eq!(find_utf16char(&haystack, needle), naive_find(&haystack, units), "{:?}", c);
+            assert_eq!(rfind_utf16char(&haystack, needle), naive_rfind(&haystack, units), "{:?}", c);
+            let parts = naive_split(&haystack, units);
+            assert_eq!(count_utf16char(&haystack, needle), parts.len() - 1, "{:?}", c);
+            assert_eq!(split_on_utf16char(&haystack, needle).collect::<Vec<_>>(), parts, "{:?}", c);
+            let reversed = parts.iter().rev().cloned().collect::<Vec<_>>();
+            assert_eq!(split_on_utf16char(&haystack, needle).rev().collect::<Vec<_>>(), reversed);
+        }
+    }
+}
+
+#[test]
+fn split_from_both_ends() {
+    let mut parts = split_on_utf8char(",a,,b,".as_bytes(), Utf8Char::from(','));
+    assert_eq!(parts.next(), Some(&b""[..]));
+    assert_eq!(parts.next_back(), Some(&b""[..]));
+    assert_eq!(parts.next(), Some(&b"a"[..]));
+    assert_eq!(parts.next_back(), Some(&b"b"[..]));
+    assert_eq!(parts.next_back(), Some(&b""[..]));
+    assert_eq!(parts.next(), None);
+    assert_eq!(parts.next_back(), None);
+
+    let units = [0x2c, 0x2c];
+    let mut parts = split_on_utf16char(&units, Utf16Char::from(','));
+    assert_eq!(parts.size_hint(), (1, Some(3)));
+    assert_eq!(parts.next_back(), Some(&[][..]));
+    assert_eq!(parts.next_back(), Some(&[][..]));
+    assert_eq!(parts.next(), Some(&[][..]));
+    assert_eq!(parts.next(), None);
+}
@@ -0,0 +1,85 @@
+/* Copyright 2022 Torbjørn Birch Moltu
+ *
+ * Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+ * http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+ * http://opensource.org/licenses/MIT>, at your option. This file may not be
+ * copied, modified, or distributed except according to those terms.
+ */
+
+//! Test (de)serializing with a human-readable and a binary format.
+
+#![cfg(all(feature="std", feature="serde"))]
+
+extern crate encode_unicode;
+use encode_unicode::{Utf8Char, Utf16Char, slice};
+use encode_unicode::error::{FromStrError, Utf8ErrorKind, Utf16PairError, EmptyStrError};
+
+const EDGES: &str = "\0\u{7f}\u{80}\u{7ff}\u{800}\u{d7ff}\u{e000}\u{ffff}\u{10000}\u{10ffff}";
+
+#[test]
+fn roundtrip() {
+    for c in EDGES.chars() {
+        let u8c = Utf8Char::from(c);
+        let json = serde_json::to_string(&u8c).unwrap();
+        assert_eq!(json, serde_json::to_string(&c).unwrap());
+        assert_eq!(serde_json::from_str::<Utf8Char>(&json).unwrap(), u8c);
+        let binary = bincode::serialize(&u8c).unwrap();
+        assert_eq!(&binary[8..], u8c.as_bytes());
+        assert_eq!(bincode::deserialize::<Utf8Char>(&binary).unwrap(), u8c);
+
+        let u16c = Utf16Char::from(c);
+        assert_eq!(serde_json::to_string(&u16c).unwrap(), json);
+        assert_eq!(serde_json::from_str::<Utf16Char>(&json).unwrap(), u16c);
+        let binary = bincode::serialize(&u16c).unwrap();
+        assert_eq!(binary, bincode::serialize(&u16c[..]).unwrap());
+        assert_eq!(bincode::deserialize::<Utf16Char>(&binary).unwrap(), u16c);
+    }
+}
+
+#[test]
+fn invalid() {
+    for s in &["\"\"", "\"ab\"", "\"e\\u0301\""] {
+        let error = serde_json::from_str::<Utf8Char>(s).unwrap_err().to_string();
+        assert!(error.starts_with("string "), "{}", error);
+        let error = serde_json::from_str::<Utf16Char>(s).unwrap_err().to_string();
+        assert!(error.starts_with("string "), "{}", error);
+    }
+    let error = serde_json::from_str::<Utf8Char>("\"\"").unwrap_err().to_string();
+    assert!(error.contains(&FromStrError::Empty.to_string()), "{}", error);
+
+    // bincode encodes byte sequences the same way as bytes
+    let bytes = |bytes: &[u8]| bincode::serialize(bytes).unwrap();
+    let error = bincode::deserialize::<Utf8Char>(&bytes(b"\xed\xa0\x80")).unwrap_err().to_string();
+    assert!(error.contains("Utf16ReservedCodepoint"), "{}", error);
+    let error = bincode::deserialize::<Utf8Char>(&bytes(b"\xc3")).unwrap_err().to_string();
+    assert!(error.contains("TooFewBytes"), "{}", error);
+    assert!(bincode::deserialize::<Utf8Char>(&bytes(b"ab")).is_err());
+
+    let units = |units: &[u16]| bincode::serialize(units).unwrap();
+    assert!(bincode::deserialize::<Utf16Char>(&units(&[])).is_err());
+    assert!(bincode::deserialize::<Utf16Char>(&units(&[0xdc00])).is_err());
+    assert!(bincode::deserialize::<Utf16Char>(&units(&[0xd800])).is_err());
+    assert!(bincode::deserialize::<Utf16Char>(&units(&[b'a' as u16, b'b' as u16])).is_err());
+    assert!(bincode::deserialize::<Utf16Char>(&units(&[0xd83d, 0xde00, 0])).is_err());
+}
+
+#[test]
+fn errors() {
+    assert_eq!(serde_json::to_string(&EmptyStrError).unwrap(), "null");
+    assert_eq!(serde_json::to_string(&FromStrError::MultipleCodepoints).unwrap(), "\"MultipleCodepoints\"");
+    let error = Utf8Char::from_slice_start(b"\xc0\x80").unwrap_err();
+    assert_eq!(error.kind(), Utf8ErrorKind::NonUtf8Byte);
+    assert_eq!(serde_json::to_string(&error).unwrap(), r#"{"kind":"NonUtf8Byte"}"#);
+
+    let error = slice::validate_utf16(&[b'a' as u16, 0xd800]).unwrap_err();
+    assert_eq!(error.error(), &Utf16PairError::Incomplete);
+    assert_eq!(serde_json::to_string(&error).unwrap(), r#"{"offset":1,"error":"Incomplete"}"#);
+
+    let mut buf = [0u16; 1];
+    let (_, _, result) = slice::transcode_utf8_to_utf16("😀".as_bytes(), &mut buf);
+    assert_eq!(serde_json::to_string(&result).unwrap(), r#"{"Err":{"OutputFull":{"needed":2}}}"#);
+    let (_, _, result) = slice::transcode_utf8_to_utf16(b"\xff", &mut buf);
+    assert_eq!(serde_json::to_string(&result).unwrap(), r#"{"Err":{"Invalid":{"kind":"NonUtf8Byte"}}}"#);
+    // the variant index is used by binary formats
+    assert_eq!(bincode::serialize(&Utf8ErrorKind::TooHighCodepoint).unwrap(), [6, 0, 0, 0]);
+}
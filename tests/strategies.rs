@@ -0,0 +1,102 @@
+/* Copyright 2022 Torbjørn Birch Moltu
+ *
+ * Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+ * http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+ * http://opensource.org/licenses/MIT>, at your option. This file may not be
+ * copied, modified, or distributed except according to those terms.
+ */
+
+//! Test that the proptest strategies produce what they promise,
+//! and that the `arbitrary` impls only produce valid characters.
+
+#![cfg(all(feature="std", any(feature="proptest", feature="arbitrary")))]
+
+extern crate encode_unicode;
+
+#[cfg(feature="proptest")]
+mod strategies {
+    use encode_unicode::{Utf8Char, Utf16Char, SliceExt, slice};
+    use encode_unicode::error::{Utf8ErrorKind, Utf16PairError};
+    use encode_unicode::iterator::Utf16CharDecoder;
+    use encode_unicode::strategies::*;
+    use proptest::prelude::*;
+
+    const UTF8_KINDS: [Utf8ErrorKind; 7] = [
+        Utf8ErrorKind::TooFewBytes,
+        Utf8ErrorKind::NonUtf8Byte,
+        Utf8ErrorKind::UnexpectedContinuationByte,
+        Utf8ErrorKind::InterruptedSequence,
+        Utf8ErrorKind::OverlongEncoding,
+        Utf8ErrorKind::Utf16ReservedCodepoint,
+        Utf8ErrorKind::TooHighCodepoint,
+    ];
+    const UTF16_ERRORS: [Utf16PairError; 3] = [
+        Utf16PairError::UnexpectedTrailingSurrogate,
+        Utf16PairError::UnmatchedLeadingSurrogate,
+        Utf16PairError::Incomplete,
+    ];
+
+    fn utf8_kind() -> impl Strategy<Value=Utf8ErrorKind> {
+        prop::sample::select(&UTF8_KINDS[..])
+    }
+    fn utf16_error() -> impl Strategy<Value=Utf16PairError> {
+        prop::sample::select(&UTF16_ERRORS[..])
+    }
+
+    proptest! {
+        #[test]
+        fn characters(u8c in any::<Utf8Char>(), u16c in any::<Utf16Char>()) {
+            prop_assert_eq!(Utf8Char::from_slice_start(u8c.as_bytes()), Ok((u8c, u8c.len())));
+            prop_assert_eq!(Utf16Char::from_slice_start(&u16c), Ok((u16c, u16c.len())));
+        }
+
+        #[test]
+        fn valid(s in valid_utf8(), units in valid_utf16()) {
+            prop_assert!(slice::utf8_codepoint_count(s.as_bytes()).is_ok());
+            prop_assert!(slice::validate_utf16(&units).is_ok());
+        }
+
+        #[test]
+        fn utf8_errors((kind, bytes) in utf8_kind().prop_flat_map(|kind| (Just(kind), invalid_utf8(kind)) )) {
+            let error = Utf8Char::from_slice_start(&bytes).unwrap_err();
+            prop_assert_eq!(error.kind(), kind, "{:x?}", bytes);
+        }
+
+        #[test]
+        fn utf8_errors_in_valid_text((kind, (bytes, offset)) in utf8_kind().prop_flat_map(|kind| (Just(kind), utf8_with_error(kind)) )) {
+            let error = slice::utf8_codepoint_count(&bytes).unwrap_err();
+            prop_assert_eq!(error.offset(), offset, "{:x?}", bytes);
+            prop_assert_eq!(error.error().kind(), kind, "{:x?}", bytes);
+            let first_error = bytes.utf8char_indices().find(|&(_, r, _)| r.is_err() );
+            prop_assert_eq!(first_error.map(|(offset, r, _)| (offset, r.unwrap_err().kind()) ), Some((offset, kind)));
+        }
+
+        #[test]
+        fn utf16_errors((error, units) in utf16_error().prop_flat_map(|error| (Just(error), invalid_utf16(error)) )) {
+            let first = Utf16CharDecoder::from(&units[..]).next().map(|(offset, r, _)| (offset, r) );
+            prop_assert_eq!(first, Some((0, Err(error))), "{:x?}", units);
+        }
+
+        #[test]
+        fn utf16_errors_in_valid_text((error, (units, offset)) in utf16_error().prop_flat_map(|error| (Just(error), utf16_with_error(error)) )) {
+            let found = slice::validate_utf16(&units).unwrap_err();
+            prop_assert_eq!(found.offset(), offset, "{:x?}", units);
+            prop_assert_eq!(found.error(), &error, "{:x?}", units);
+        }
+    }
+}
+
+#[cfg(feature="arbitrary")]
+#[test]
+fn arbitrary() {
+    use arbitrary::{Arbitrary, Unstructured};
+    use encode_unicode::{Utf8Char, Utf16Char};
+    let bytes = (0..=255).cycle().step_by(7).take(4096).collect::<Vec<u8>>();
+    let mut u = Unstructured::new(&bytes);
+    while !u.is_empty() {
+        let u8c = Utf8Char::arbitrary(&mut u).unwrap();
+        assert_eq!(Utf8Char::from_slice_start(u8c.as_bytes()), Ok((u8c, u8c.len())));
+        let u16c = Utf16Char::arbitrary(&mut u).unwrap();
+        assert_eq!(Utf16Char::from_slice_start(&u16c), Ok((u16c, u16c.len())));
+    }
+}
@@ -0,0 +1,114 @@
+/* Copyright 2022 Torbjørn Birch Moltu
+ *
+ * Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+ * http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+ * http://opensource.org/licenses/MIT>, at your option. This file may not be
+ * copied, modified, or distributed except according to those terms.
+ */
+
+//! Test converting slices between UTF-8 and UTF-16 against std and the decoding iterators.
+
+#![cfg(feature="std")]
+
+extern crate encode_unicode;
+use encode_unicode::SliceExt;
+use encode_unicode::slice::{transcode_utf8_to_utf16, transcode_utf16_to_utf8};
+use encode_unicode::error::{TranscodeError, Utf8ErrorKind, Utf16PairError};
+
+const TEXT: &str = "ASCII long enough for a block or two, then æøå € 😀 and \u{10ffff}.";
+
+#[test] fn in_chunks() {
+    let text = TEXT.repeat(3);
+    let units = text.encode_utf16().collect::<Vec<u16>>();
+    for &(src_chunk, dst_chunk) in &[(1, 2), (3, 1), (7, 5), (16, 16), (17, 33), (1000, 1000)] {
+        // UTF-8 -> UTF-16, with incomplete sequences carried over
+        let mut out = Vec::new();
+        let mut pending = Vec::new();
+        for chunk in text.as_bytes().chunks(src_chunk) {
+            pending.extend_from_slice(chunk);
+            loop {
+                // two units is enough for any codepoint
+                let mut buf = vec![0; dst_chunk.max(2)];
+                let (read, written, result) = transcode_utf8_to_utf16(&pending, &mut buf);
+                out.extend_from_slice(&buf[..written]);
+                pending.drain(..read);
+                match result {
+                    Ok(()) => break,
+                    Err(TranscodeError::OutputFull{needed}) => assert!(written + needed > buf.len()),
+                    Err(TranscodeError::Invalid(e)) => {
+                        assert_eq!(e.kind(), Utf8ErrorKind::TooFewBytes);
+                        break;
+                    }
+                }
+            }
+        }
+        assert!(pending.is_empty());
+        assert_eq!(out, units);
+
+        // UTF-16 -> UTF-8
+        let mut out = Vec::new();
+        let mut pending = Vec::new();
+        for chunk in units.chunks(src_chunk) {
+            pending.extend_from_slice(chunk);
+            loop {
+                let mut buf = vec![0; dst_chunk.max(4)];
+                let (read, written, result) = transcode_utf16_to_utf8(&pending, &mut buf);
+                out.extend_from_slice(&buf[..written]);
+                pending.drain(..read);
+                match result {
+                    Ok(()) => break,
+                    Err(TranscodeError::OutputFull{needed}) => assert!(written + needed > buf.len()),
+                    Err(TranscodeError::Invalid(e)) => {
+                        assert_eq!(e, Utf16PairError::Incomplete);
+                        break;
+                    }
+                }
+            }
+        }
+        assert!(pending.is_empty());
+        assert_eq!(String::from_utf8(out).unwrap(), text);
+    }
+}
+
+#[test] fn too_short_output() {
+    let mut buf = [0u16; 1];
+    let result = transcode_utf8_to_utf16("😀".as_bytes(), &mut buf);
+    assert_eq!(result, (0, 0, Err(TranscodeError::OutputFull{needed: 2})));
+    let mut buf = [0u8; 3];
+    let result = transcode_utf16_to_utf8(&[0xd83d, 0xde00], &mut buf);
+    assert_eq!(result, (0, 0, Err(TranscodeError::OutputFull{needed: 4})));
+    let result = transcode_utf16_to_utf8(&[0x41; 40], &mut buf);
+    assert_eq!(result, (3, 3, Err(TranscodeError::OutputFull{needed: 1})));
+    assert_eq!(transcode_utf8_to_utf16(b"", &mut []), (0, 0, Ok(())));
+}
+
+#[test] fn errors_match_decoder() {
+    let bytes = b"Some ASCII to take the fast path \xe2\x82\xac\xed\xa0\x80 rest";
+    let (offset, error, _) = bytes.utf8char_indices().find(|(_, r, _)| r.is_err() ).unwrap();
+    let mut buf = [0u16; 100];
+    let (read, written, result) = transcode_utf8_to_utf16(bytes, &mut buf);
+    assert_eq!((read, written), (offset, offset-2));
+    assert_eq!(result, Err(TranscodeError::Invalid(error.unwrap_err())));
+
+    let units = [0x41; 20].iter().cloned().chain(vec![0xd800, 0x41]).collect::<Vec<u16>>();
+    let mut buf = [0u8; 100];
+    let (read, written, result) = transcode_utf16_to_utf8(&units, &mut buf);
+    assert_eq!((read, written), (20, 20));
+    assert_eq!(result, Err(TranscodeError::Invalid(Utf16PairError::UnmatchedLeadingSurrogate)));
+}
+
+#[test] fn ascii_blocks() {
+    // non-ASCII at every position of a block, in both directions
+    for i in 0..40 {
+        let mut text = "a".repeat(40);
+        text.replace_range(i..i+1, "\u{7f}");
+        text.insert(i, 'ø');
+        let units = text.encode_utf16().collect::<Vec<u16>>();
+        let mut buf16 = vec![0; units.len()];
+        assert_eq!(transcode_utf8_to_utf16(text.as_bytes(), &mut buf16), (text.len(), units.len(), Ok(())));
+        assert_eq!(buf16, units);
+        let mut buf8 = vec![0; text.len()];
+        assert_eq!(transcode_utf16_to_utf8(&units, &mut buf8), (units.len(), text.len(), Ok(())));
+        assert_eq!(buf8, text.as_bytes());
+    }
+}
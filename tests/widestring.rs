@@ -0,0 +1,113 @@
+/* Copyright 2022 Torbjørn Birch Moltu
+ *
+ * Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+ * http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+ * http://opensource.org/licenses/MIT>, at your option. This file may not be
+ * copied, modified, or distributed except according to those terms.
+ */
+
+//! Test the `wchar_t` helpers and the conversions to and from widestring's types.
+
+#![cfg(all(feature="widestring", feature="std"))]
+
+extern crate encode_unicode;
+use encode_unicode::{SliceExt, Utf16Char};
+use encode_unicode::corrupt::{corrupt_utf16, Utf16Corruption};
+use encode_unicode::error::{CodepointError, FromStrError, InteriorNulError};
+use encode_unicode::wide::*;
+use widestring::{U16CString, U32CString, U16String, Utf16String, WideCString, WideChar};
+use std::convert::TryFrom;
+
+/// Codepoints whose units have a zero byte or a zero half, which mustn't be
+/// mistaken for the terminator, and a surrogate pair right before it.
+const NUL_LOOKALIKES: &str = "\u{100}\u{ff00}a\u{10000}\u{2000}😀";
+
+#[test]
+fn same_as_widestring() {
+    let utf16 = encode_nul_terminated::<u16>(NUL_LOOKALIKES).unwrap();
+    assert_eq!(utf16, U16CString::from_str(NUL_LOOKALIKES).unwrap().into_vec_with_nul());
+    let utf32 = encode_nul_terminated::<u32>(NUL_LOOKALIKES).unwrap();
+    assert_eq!(utf32, U32CString::from_str(NUL_LOOKALIKES).unwrap().into_vec_with_nul());
+    let wide = encode_nul_terminated::<WideChar>(NUL_LOOKALIKES).unwrap();
+    assert_eq!(wide, WideCString::from_str(NUL_LOOKALIKES).unwrap().into_vec_with_nul());
+
+    assert_eq!(decode_nul_terminated(&utf16).as_deref(), Ok(NUL_LOOKALIKES));
+    assert_eq!(decode_nul_terminated(&utf32).as_deref(), Ok(NUL_LOOKALIKES));
+    assert_eq!(unsafe { decode_nul_terminated_ptr(wide.as_ptr()) }.as_deref(), Ok(NUL_LOOKALIKES));
+}
+
+#[test]
+fn interior_nul() {
+    for (offset, c) in NUL_LOOKALIKES.char_indices().chain(Some((NUL_LOOKALIKES.len(), '?'))) {
+        let mut s = NUL_LOOKALIKES.to_string();
+        s.insert(offset, '\0');
+        let error = encode_nul_terminated::<u16>(&s).unwrap_err();
+        assert_eq!((error.offset(), *error.error()), (offset, InteriorNulError), "before {:?}", c);
+        let error = encode_nul_terminated::<u32>(&s).unwrap_err();
+        assert_eq!((error.offset(), *error.error()), (offset, InteriorNulError), "before {:?}", c);
+    }
+}
+
+#[test]
+fn decode_stops_at_nul() {
+    let mut units = encode_nul_terminated::<u16>("bløt").unwrap();
+    units.extend([0xdc00, 0x61, 0]);
+    assert_eq!(decode_nul_terminated(&units).as_deref(), Ok("bløt"));
+    assert_eq!(decode_nul_terminated(&units[..3]).as_deref(), Ok("blø"));
+    assert_eq!(decode_nul_terminated::<u32>(&[]).as_deref(), Ok(""));
+    assert_eq!(unsafe { decode_nul_terminated_ptr(units[4..].as_ptr()) }.as_deref(), Ok(""));
+}
+
+#[test]
+fn utf16_errors_same_as_decoder() {
+    let units = NUL_LOOKALIKES.encode_utf16().collect::<Vec<u16>>();
+    for &corruption in &[Utf16Corruption::LoneTrailingSurrogate, Utf16Corruption::LoneLeadingSurrogate] {
+        for offset in (0..units.len()).filter(|&i| units[i] & 0xfc00 != 0xdc00 ) {
+            let (corrupted, _) = corrupt_utf16(&units, &[(offset, corruption)]);
+            let (expected_offset, result, _) = corrupted.utf16char_indices()
+                .find(|(_, result, _)| result.is_err() )
+                .unwrap();
+            let error = decode_nul_terminated(&corrupted).unwrap_err();
+            assert_eq!((error.offset(), *error.error()), (expected_offset, result.unwrap_err()));
+        }
+    }
+}
+
+#[test]
+fn utf32_errors() {
+    for &(unit, expected) in &[
+        (0xd800u32, CodepointError::Utf16Reserved),
+        (0xdfff, CodepointError::Utf16Reserved),
+        (0x110000, CodepointError::TooHigh),
+        (0xffff_ffff, CodepointError::TooHigh),
+    ] {
+        let error = decode_nul_terminated(&[0x61, 0x10ffff, unit, 0x62, 0]).unwrap_err();
+        assert_eq!((error.offset(), *error.error()), (2, expected), "{:x}", unit);
+    }
+}
+
+#[test]
+fn conversions() {
+    let chars = NUL_LOOKALIKES.chars().map(Utf16Char::from).collect::<Vec<Utf16Char>>();
+    let units = NUL_LOOKALIKES.encode_utf16().collect::<Vec<u16>>();
+
+    let utf16 = chars.iter().collect::<Utf16String>();
+    assert_eq!(utf16.to_string(), NUL_LOOKALIKES);
+    let mut collected = U16String::new();
+    collected.extend(chars.iter().cloned());
+    assert_eq!(collected.as_slice(), &units[..]);
+    assert_eq!(chars.iter().collect::<U16String>(), collected);
+
+    for &u16c in &chars {
+        let utf16 = Utf16String::from(u16c);
+        assert_eq!(Utf16Char::try_from(&utf16[..]), Ok(u16c));
+        assert_eq!(U16String::from(u16c).as_slice(), u16c.as_ref());
+        assert_eq!(U16CString::try_from(u16c).unwrap().as_slice(), u16c.as_ref());
+        assert_eq!(U32CString::try_from(u16c).unwrap().as_slice(), [u16c.to_char() as u32]);
+    }
+
+    assert_eq!(Utf16Char::try_from(&utf16[..]), Err(FromStrError::MultipleCodepoints));
+    assert_eq!(Utf16Char::try_from(&Utf16String::new()[..]), Err(FromStrError::Empty));
+    assert_eq!(U16CString::try_from(Utf16Char::from('\0')), Err(InteriorNulError));
+    assert_eq!(U32CString::try_from(Utf16Char::from('\0')), Err(InteriorNulError));
+}